@@ -1,34 +1,105 @@
-use cosmwasm_std::{ensure, Addr, Coin, Deps, Env, MessageInfo, Storage, Uint128};
+use cosmwasm_std::{coin, ensure, Addr, Coin, Deps, Env, MessageInfo, Storage, Timestamp, Uint128};
+use cw4::Cw4Contract;
+use cw721::{Cw721QueryMsg, TokensResponse};
 use cw_storage_plus::Map;
-use cw_utils::must_pay;
+use cw_utils::{must_pay, Expiration};
 
 use crate::{
-    state::{PlayerInfo, State, PLAYER_COUNTER},
+    state::{
+        Allowlist, GameStatus, PauseInfo, PendingOwner, PlayerInfo, Pricing, PrizeTier,
+        ProgressiveJackpot, ScratchPrize, State, WinnerSelection, ALLOWLIST, ALLOWLIST_MEMBERS,
+        NFT_GATE, OWNER, PAUSE, PLATFORM, PLAYER_COUNTER, REFERRERS, USED_GATE_TOKENS,
+    },
     ContractError,
 };
 
 pub type UnitResult = Result<(), ContractError>;
 
+const GATE_TOKENS_PAGE: u32 = 30;
+const MAX_SPONSOR_NAME_LEN: usize = 64;
+
+/// The sponsored amount, paid in the denom of the tickets
+pub fn validate_sponsor(
+    state: &State,
+    info: &MessageInfo,
+    name: Option<&str>,
+) -> Result<Uint128, ContractError> {
+    let amount = must_pay(info, &state.unit_price.denom)?;
+
+    ensure!(
+        !state.selection.is_scratch(),
+        ContractError::SponsorshipUnsupported {}
+    );
+
+    ensure!(
+        name.is_none_or(
+            |name| !name.trim().is_empty() && name.chars().count() <= MAX_SPONSOR_NAME_LEN
+        ),
+        ContractError::InvalidSponsorName {
+            max_len: MAX_SPONSOR_NAME_LEN
+        }
+    );
+
+    validate_status(state)?;
+
+    Ok(amount)
+}
+
+/// A ticket for each of the `players`, returns the amount paid
 pub fn validate_buy(
-    storage: &dyn Storage,
+    deps: Deps,
     state: &State,
     info: &MessageInfo,
     denom: &str,
     env: &Env,
-) -> UnitResult {
+    players: &[Addr],
+) -> Result<Uint128, ContractError> {
     let amount = must_pay(info, denom)?;
 
+    let quantity = u32::try_from(players.len()).map_err(|_| ContractError::NoTicketToBuy {})?;
+    ensure!(quantity > 0, ContractError::NoTicketToBuy {});
+
+    for player in players {
+        validate_allowlist(deps, player)?;
+    }
+
     validate_winner_selection(state)?;
 
     validate_denom(state, denom)?;
 
-    validate_price(state, amount)?;
+    validate_price(&state.quote(quantity, env.block.time), amount)?;
 
-    validate_player_counter(storage, state)?;
+    validate_player_counter(deps.storage, state, quantity)?;
 
     validate_status(state)?;
 
-    validate_timestamp(state, env)
+    validate_timestamp(state, env)?;
+
+    Ok(amount)
+}
+
+/// Phases in ascending start time with a price, bundles larger than their price
+pub fn validate_pricing(pricing: &Pricing) -> UnitResult {
+    let phases_valid = pricing
+        .phases
+        .windows(2)
+        .all(|pair| pair[0].starts_at < pair[1].starts_at)
+        && pricing.phases.iter().all(|phase| !phase.amount.is_zero());
+    let tiers_valid = pricing
+        .tiers
+        .iter()
+        .all(|tier| tier.paid > 0 && tier.paid < tier.quantity)
+        && pricing.tiers.iter().enumerate().all(|(i, tier)| {
+            pricing.tiers[..i]
+                .iter()
+                .all(|t| t.quantity != tier.quantity)
+        });
+    ensure!(
+        phases_valid && tiers_valid,
+        ContractError::InvalidPricing {}
+    );
+
+    Ok(())
 }
 
 pub fn validate_draw(
@@ -47,9 +118,241 @@ pub fn validate_draw(
     validate_status(state)
 }
 
+pub fn validate_allowlist(deps: Deps, player: &Addr) -> UnitResult {
+    let allowed = match ALLOWLIST.may_load(deps.storage)? {
+        None => true,
+        Some(Allowlist::Addresses {}) => ALLOWLIST_MEMBERS.has(deps.storage, player),
+        Some(Allowlist::Group { group }) => Cw4Contract::new(group)
+            .is_member(&deps.querier, player, None)?
+            .is_some(),
+    };
+    ensure!(
+        allowed,
+        ContractError::NotAllowlisted {
+            player: player.clone()
+        }
+    );
+
+    Ok(())
+}
+
+/// Gate tokens the ticket consumes, only recorded when a token admits a single ticket
+pub fn validate_nft_gate(deps: Deps, player: &Addr) -> Result<Vec<String>, ContractError> {
+    let Some(gate) = NFT_GATE.may_load(deps.storage)? else {
+        return Ok(vec![]);
+    };
+
+    let required = gate.min_tokens as usize;
+    let mut eligible = vec![];
+    let mut start_after = None;
+    while eligible.len() < required {
+        let resp: TokensResponse = deps.querier.query_wasm_smart(
+            &gate.collection,
+            &Cw721QueryMsg::Tokens {
+                owner: player.to_string(),
+                start_after,
+                limit: Some(GATE_TOKENS_PAGE),
+            },
+        )?;
+        let is_last_page = resp.tokens.len() < GATE_TOKENS_PAGE as usize;
+        start_after = resp.tokens.last().cloned();
+
+        eligible.extend(resp.tokens.into_iter().filter(|token_id| {
+            !gate.once_per_token || !USED_GATE_TOKENS.has(deps.storage, token_id)
+        }));
+
+        if is_last_page {
+            break;
+        }
+    }
+
+    ensure!(
+        eligible.len() >= required,
+        ContractError::NotEnoughGateTokens {
+            player: player.clone(),
+            required: gate.min_tokens,
+            held: eligible.len() as u32,
+        }
+    );
+
+    if !gate.once_per_token {
+        return Ok(vec![]);
+    }
+
+    eligible.truncate(required);
+    Ok(eligible)
+}
+
+/// Reject referrals looping back to the `player`, through the referrers' own referrers
+pub fn validate_referral(storage: &dyn Storage, player: &Addr, referrer: &Addr) -> UnitResult {
+    ensure!(
+        player != referrer,
+        ContractError::SelfReferral {
+            player: player.clone()
+        }
+    );
+
+    // Every player has one referrer at most, so the chain is walked once
+    let mut current = referrer.clone();
+    while let Some(next) = REFERRERS.may_load(storage, &current)? {
+        ensure!(
+            next != *player,
+            ContractError::CircularReferral {
+                player: player.clone(),
+                referrer: referrer.clone(),
+            }
+        );
+        current = next;
+    }
+
+    Ok(())
+}
+
+pub fn validate_pause(
+    env: &Env,
+    sender: &Addr,
+    reason: String,
+    expires_at: Option<u64>,
+) -> Result<PauseInfo, ContractError> {
+    ensure!(
+        !reason.trim().is_empty(),
+        ContractError::PauseReasonMissing {}
+    );
+    if let Some(expires_at) = expires_at {
+        ensure!(
+            expires_at > env.block.time.seconds(),
+            ContractError::InvalidPauseExpiry { expires_at }
+        );
+    }
+
+    Ok(PauseInfo {
+        reason,
+        paused_by: sender.clone(),
+        paused_at: env.block.time,
+        expires_at: expires_at.map(Timestamp::from_seconds),
+    })
+}
+
+/// A pause lifts itself once it expires
+pub fn validate_not_paused(storage: &dyn Storage, env: &Env) -> UnitResult {
+    if let Some(pause) = PAUSE.may_load(storage)? {
+        ensure!(
+            !pause.is_active(env.block.time),
+            ContractError::Paused {
+                reason: pause.reason
+            }
+        );
+    }
+
+    Ok(())
+}
+
+/// The owner, or the platform which created the lottery, can pause it
+pub fn validate_guardian(storage: &dyn Storage, sender: &Addr) -> UnitResult {
+    let owner = OWNER.may_load(storage)?;
+    let platform = PLATFORM.may_load(storage)?;
+    ensure!(
+        owner.as_ref() == Some(sender) || platform.as_ref() == Some(sender),
+        ContractError::Unauthorized {}
+    );
+
+    Ok(())
+}
+
+pub fn validate_progressive_jackpot(jackpot: &ProgressiveJackpot) -> UnitResult {
+    ensure!(
+        jackpot.contribution_bps <= 10_000 && jackpot.odds > 0,
+        ContractError::InvalidProgressiveJackpot {}
+    );
+
+    Ok(())
+}
+
+pub fn validate_number_pick(pick: u8, max_number: u8, tiers: &[PrizeTier]) -> UnitResult {
+    ensure!(
+        pick > 0 && pick <= max_number,
+        ContractError::InvalidNumberPick { pick, max_number }
+    );
+
+    let mut matches: Vec<u8> = tiers.iter().map(|tier| tier.matches).collect();
+    matches.sort_unstable();
+    matches.dedup();
+    let pct: u32 = tiers.iter().map(|tier| u32::from(tier.pct)).sum();
+    ensure!(
+        !tiers.is_empty()
+            && matches.len() == tiers.len()
+            && matches.iter().all(|m| (1..=pick).contains(m))
+            && pct == 100,
+        ContractError::InvalidPrizeTiers { pick }
+    );
+
+    Ok(())
+}
+
+/// The prize table is funded along the instantiation, and has a ticket for every prize
+pub fn validate_scratch_table(
+    info: &MessageInfo,
+    prizes: &[ScratchPrize],
+    max_players: u64,
+    denom: &str,
+) -> UnitResult {
+    let count: u64 = prizes.iter().map(|prize| u64::from(prize.count)).sum();
+    ensure!(
+        !prizes.is_empty()
+            && prizes
+                .iter()
+                .all(|prize| !prize.amount.is_zero() && prize.count > 0)
+            && count <= max_players,
+        ContractError::InvalidScratchTable { max_players }
+    );
+
+    let total: Uint128 = prizes
+        .iter()
+        .map(|prize| prize.amount * Uint128::from(prize.count))
+        .sum();
+    let expected = Coin::new(total.u128(), denom);
+    ensure!(
+        must_pay(info, denom).ok() == Some(total),
+        ContractError::ScratchNotFunded { expected }
+    );
+
+    Ok(())
+}
+
+/// The picked numbers in ascending order, required by number-pick lotteries only
+pub fn validate_numbers(
+    state: &State,
+    numbers: Option<Vec<u8>>,
+) -> Result<Option<Vec<u8>>, ContractError> {
+    let WinnerSelection::NumberPick {
+        pick, max_number, ..
+    } = state.selection
+    else {
+        ensure!(numbers.is_none(), ContractError::NumbersNotAllowed {});
+        return Ok(None);
+    };
+
+    let numbers = numbers.unwrap_or_default();
+    let mut sorted = numbers.clone();
+    sorted.sort_unstable();
+    sorted.dedup();
+    ensure!(
+        sorted.len() == pick as usize && sorted.iter().all(|n| (1..=max_number).contains(n)),
+        ContractError::InvalidNumbers {
+            numbers,
+            pick,
+            max_number
+        }
+    );
+
+    Ok(Some(sorted))
+}
+
 pub fn validate_winner_selection(state: &State) -> UnitResult {
     ensure!(
-        state.selection.is_jackpot(),
+        state.selection.is_jackpot()
+            || state.selection.is_number_pick()
+            || state.selection.is_scratch(),
         ContractError::UnSupportedWinnerSelection {
             selection: state.selection.clone()
         }
@@ -67,9 +370,9 @@ pub fn validate_denom(state: &State, denom: &str) -> UnitResult {
     Ok(())
 }
 
-pub fn validate_price(state: &State, payment_amount: Uint128) -> UnitResult {
+pub fn validate_price(price: &Coin, payment_amount: Uint128) -> UnitResult {
     ensure!(
-        payment_amount >= state.unit_price.amount,
+        payment_amount >= price.amount,
         ContractError::PaymentNotEnough {
             amount: payment_amount
         }
@@ -77,10 +380,10 @@ pub fn validate_price(state: &State, payment_amount: Uint128) -> UnitResult {
     Ok(())
 }
 
-pub fn validate_player_counter(storage: &dyn Storage, state: &State) -> UnitResult {
+pub fn validate_player_counter(storage: &dyn Storage, state: &State, quantity: u32) -> UnitResult {
     let player_counter = PLAYER_COUNTER.load(storage)?;
     ensure!(
-        player_counter < state.max_players,
+        player_counter + u64::from(quantity) <= state.max_players,
         ContractError::PlayerExceededMaximum {
             max_players: player_counter,
         }
@@ -92,7 +395,7 @@ pub fn validate_player_counter(storage: &dyn Storage, state: &State) -> UnitResu
 pub fn validate_timestamp(state: &State, env: &Env) -> UnitResult {
     let current_time = env.block.time;
     ensure!(
-        current_time <= state.expiratoin,
+        current_time <= state.expiration,
         ContractError::AlreadyExpired {}
     );
 
@@ -118,6 +421,43 @@ pub fn validate_height(state: &State, env: &Env) -> UnitResult {
 pub fn validate_status(state: &State) -> UnitResult {
     // Can't buy lottery after lottery is already closed
     ensure!(!state.is_closed(), ContractError::LotteryAlreadyClosed {});
+    ensure!(!state.is_cancelled(), ContractError::LotteryCancelled {});
+
+    Ok(())
+}
+
+/// The owner, `Unauthorized` once the ownership is renounced
+pub fn load_owner(storage: &dyn Storage) -> Result<Addr, ContractError> {
+    OWNER
+        .may_load(storage)?
+        .ok_or(ContractError::Unauthorized {})
+}
+
+/// `expiry` defaults to never
+pub fn validate_owner_proposal(
+    env: &Env,
+    new_owner: Addr,
+    expiry: Option<Expiration>,
+) -> Result<PendingOwner, ContractError> {
+    let expiry = expiry.unwrap_or_default();
+    ensure!(
+        !expiry.is_expired(&env.block),
+        ContractError::InvalidOwnershipExpiry { expiry }
+    );
+
+    Ok(PendingOwner {
+        address: new_owner,
+        expiry,
+    })
+}
+
+pub fn validate_acceptance(env: &Env, pending: Option<PendingOwner>, sender: &Addr) -> UnitResult {
+    let pending = pending.ok_or(ContractError::NoPendingOwner {})?;
+    ensure!(pending.address == sender, ContractError::Unauthorized {});
+    ensure!(
+        !pending.expiry.is_expired(&env.block),
+        ContractError::OwnershipTransferExpired {}
+    );
 
     Ok(())
 }
@@ -130,7 +470,7 @@ pub fn validate_owner(owner: &Addr, info: &MessageInfo) -> UnitResult {
 
 pub fn validate_timestamp_or_activing(state: &State, env: &Env, player_counter: u64) -> UnitResult {
     ensure!(
-        env.block.time >= state.expiratoin || player_counter >= state.player_count,
+        env.block.time >= state.expiration || player_counter >= state.player_count,
         ContractError::LotteryIsActiving {}
     );
 
@@ -147,6 +487,69 @@ pub fn validate_balance(balance: &Coin, to_withdraw: u128) -> UnitResult {
     Ok(())
 }
 
+/// The pot denom is held for what the lottery still owes, the `locked` amount: the ticket
+/// pot until the draw, the refunds of a cancelled lottery, the prize left to the winner or
+/// the scratch prizes still to win. The winner takes the `prize` left, the owner what's
+/// beyond `locked`. Other denoms are never part of the pot.
+pub fn validate_withdraw(
+    state: &State,
+    balance: &Coin,
+    to_withdraw: u128,
+    locked: &Coin,
+    prize: &Coin,
+    is_winner: bool,
+) -> UnitResult {
+    validate_balance(balance, to_withdraw)?;
+
+    if balance.denom != state.unit_price.denom {
+        return Ok(());
+    }
+
+    if is_winner {
+        ensure!(
+            to_withdraw <= prize.amount.u128(),
+            ContractError::PrizeExceeded {
+                prize: prize.clone()
+            }
+        );
+        return Ok(());
+    }
+
+    let excess = coin(
+        balance.amount.saturating_sub(locked.amount).u128(),
+        &balance.denom,
+    );
+    ensure!(
+        to_withdraw <= excess.amount.u128(),
+        match state.status {
+            _ if state.selection.is_scratch() => ContractError::ScratchPrizesLocked { excess },
+            GameStatus::Activing => ContractError::PotLocked { excess },
+            GameStatus::Cancelled => ContractError::RefundsPending { excess },
+            GameStatus::Closed => ContractError::ExcessExceeded { excess },
+        }
+    );
+
+    Ok(())
+}
+
+pub fn validate_cancel(state: &State, owner: &Addr, info: &MessageInfo) -> UnitResult {
+    validate_owner(owner, info)?;
+
+    validate_status(state)
+}
+
+pub fn validate_refund(state: &State, refunded: bool, player: &Addr) -> UnitResult {
+    ensure!(state.is_cancelled(), ContractError::LotteryNotCancelled {});
+    ensure!(
+        !refunded,
+        ContractError::AlreadyRefunded {
+            player: player.clone()
+        }
+    );
+
+    Ok(())
+}
+
 pub fn validate_double_buy(
     deps: Deps,
     players: Map<&Addr, PlayerInfo>,
@@ -0,0 +1,386 @@
+//! Replay a lottery draw from query dumps, without trusting any frontend or node.
+//!
+//! Usage:
+//!   verify_draw --state state.json --players players.json [--players page2.json ...]
+//!     --proof proof.json [--proof page2.json ...]
+//!
+//! `state.json` is the `CurrentState` response, `players.json` the `Players` (or
+//! `TicketsByIndex`) responses and `proof.json` the `DrawProof` responses. Paginated
+//! responses can be passed as several files. Only local files are read.
+use std::{collections::BTreeMap, env, fs, process};
+
+use common::hash::seed::{
+    finalize, init, memo_digest, pick_numbers, random_numbers, update, winner_indices,
+};
+use cosmwasm_std::from_json;
+use lottery::{
+    msg::{CurrentStateResp, DrawProofResp, PlayersResp},
+    state::{DrawRecord, PlayerInfo, SeedContribution, State, WinnerSelection},
+};
+
+#[derive(Debug, Default)]
+struct Args {
+    state: Option<String>,
+    players: Vec<String>,
+    proof: Vec<String>,
+}
+
+/// The paginated dumps merged by ticket index
+#[derive(Debug)]
+struct Dumps {
+    state: State,
+    players: BTreeMap<u64, PlayerInfo>,
+    initial_seed: String,
+    contributions: BTreeMap<u64, SeedContribution>,
+    draw: Option<DrawRecord>,
+}
+
+struct Report {
+    failures: usize,
+}
+
+impl Report {
+    fn check(&mut self, name: &str, passed: bool, detail: String) {
+        if passed {
+            println!("ok    {name}");
+        } else {
+            self.failures += 1;
+            println!("FAIL  {name}: {detail}");
+        }
+    }
+}
+
+fn main() {
+    let args = match parse_args(env::args().skip(1)) {
+        Ok(args) => args,
+        Err(err) => {
+            eprintln!("{err}");
+            eprintln!("usage: verify_draw --state <file> --players <file>... --proof <file>...");
+            process::exit(2);
+        }
+    };
+
+    match load(&args).map(|dumps| verify(&dumps)) {
+        Ok(0) => println!("the on-chain winner matches the replayed draw"),
+        Ok(failures) => {
+            println!("{failures} check(s) failed, the on-chain winner can't be reproduced");
+            process::exit(1);
+        }
+        Err(err) => {
+            eprintln!("{err}");
+            process::exit(2);
+        }
+    }
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Args, String> {
+    let mut parsed = Args::default();
+
+    while let Some(flag) = args.next() {
+        let value = args
+            .next()
+            .ok_or_else(|| format!("missing file after {flag}"))?;
+        match flag.as_str() {
+            "--state" => parsed.state = Some(value),
+            "--players" => parsed.players.push(value),
+            "--proof" => parsed.proof.push(value),
+            _ => return Err(format!("unknown argument: {flag}")),
+        }
+    }
+
+    if parsed.state.is_none() || parsed.players.is_empty() || parsed.proof.is_empty() {
+        return Err("--state, --players and --proof are required".to_string());
+    }
+
+    Ok(parsed)
+}
+
+fn read_json<T: serde::de::DeserializeOwned>(path: &str) -> Result<T, String> {
+    let data = fs::read(path).map_err(|err| format!("can't read {path}: {err}"))?;
+    from_json(&data).map_err(|err| format!("can't parse {path}: {err}"))
+}
+
+fn load(args: &Args) -> Result<Dumps, String> {
+    let state: CurrentStateResp = read_json(args.state.as_deref().unwrap_or_default())?;
+
+    let mut players = BTreeMap::new();
+    for path in &args.players {
+        let resp: PlayersResp = read_json(path)?;
+        for player in resp.players {
+            let idx = player.ticket_index().map_err(|err| err.to_string())?;
+            players.insert(idx, player);
+        }
+    }
+
+    let mut initial_seed = String::new();
+    let mut contributions = BTreeMap::new();
+    let mut draw = None;
+    for path in &args.proof {
+        let resp: DrawProofResp = read_json(path)?;
+        initial_seed = resp.initial_seed;
+        for contribution in resp.contributions {
+            contributions.insert(contribution.counter, contribution);
+        }
+        draw = draw.or(resp.draw);
+    }
+
+    Ok(Dumps {
+        state: state.state,
+        players,
+        initial_seed,
+        contributions,
+        draw,
+    })
+}
+
+fn verify(dumps: &Dumps) -> usize {
+    let Dumps {
+        state,
+        players,
+        initial_seed,
+        contributions,
+        draw,
+    } = dumps;
+    let mut report = Report { failures: 0 };
+
+    let ticket_count = players.len() as u64;
+    report.check(
+        "tickets are complete",
+        players.keys().copied().eq(1..=ticket_count) && ticket_count == state.player_count,
+        format!(
+            "got tickets {:?}, the lottery sold {}",
+            players.keys().collect::<Vec<_>>(),
+            state.player_count
+        ),
+    );
+
+    // The seed is initialised by the lottery address and its instantiate height
+    let mut seed = match players.values().next() {
+        Some(player) => {
+            let seed = init(player.lottery_addr.as_str(), state.height);
+            report.check(
+                "initial seed",
+                &seed == initial_seed,
+                format!("replayed {seed}, proof has {initial_seed}"),
+            );
+            seed
+        }
+        None => initial_seed.clone(),
+    };
+
+    for (idx, player) in players {
+        let memo = memo_digest(&player.memo);
+        match contributions.get(idx) {
+            Some(contribution) => report.check(
+                &format!("ticket {idx} contribution"),
+                contribution.player == player.player_addr
+                    && contribution.height == player.height
+                    && contribution.memo_digest == memo,
+                format!("proof has {contribution:?}, player is {player:?}"),
+            ),
+            // Tickets bought before the proof was recorded only have the player entry
+            None => println!("skip  ticket {idx} contribution: not in the proof"),
+        }
+
+        seed = update(
+            &seed,
+            &player.player_addr,
+            *idx,
+            player.height,
+            &player.memo,
+        );
+    }
+
+    let Some(draw) = draw else {
+        report.check("draw record", false, "the lottery isn't drawn".to_string());
+        return report.failures;
+    };
+
+    let seed = finalize(&seed, &draw.sender, draw.height, &draw.lucky_phrase);
+    report.check(
+        "final seed",
+        seed == draw.seed && seed == state.seed,
+        format!(
+            "replayed {seed}, proof has {}, state has {}",
+            draw.seed, state.seed
+        ),
+    );
+
+    let numbers = match random_numbers(&seed, draw.random_numbers.len()) {
+        Ok(numbers) => numbers,
+        Err(err) => {
+            report.check("random numbers", false, err.to_string());
+            return report.failures;
+        }
+    };
+    report.check(
+        "random numbers",
+        numbers == draw.random_numbers,
+        format!("replayed {numbers:?}, proof has {:?}", draw.random_numbers),
+    );
+
+    // A number pick is won by the tickets matching enough winning numbers
+    let indices = match (&state.selection, &draw.winning_numbers) {
+        (WinnerSelection::NumberPick { max_number, .. }, Some(on_chain)) => {
+            let winning_numbers = pick_numbers(&numbers, *max_number);
+            report.check(
+                "winning numbers",
+                &winning_numbers == on_chain,
+                format!("replayed {winning_numbers:?}, proof has {on_chain:?}"),
+            );
+
+            players
+                .iter()
+                .filter(|(_, player)| {
+                    state
+                        .selection
+                        .tier_of(player.matches(&winning_numbers))
+                        .is_some()
+                })
+                .map(|(idx, _)| *idx)
+                .collect()
+        }
+        _ => winner_indices(&numbers, ticket_count),
+    };
+    report.check(
+        "winner indices",
+        indices == draw.winner_indices,
+        format!("replayed {indices:?}, proof has {:?}", draw.winner_indices),
+    );
+
+    let expected: Vec<_> = indices
+        .iter()
+        .filter_map(|idx| players.get(idx))
+        .map(|player| (player.player_addr.clone(), player.ticket_id.clone()))
+        .collect();
+    let on_chain: Vec<_> = state
+        .winner
+        .iter()
+        .map(|winner| (winner.address.clone(), winner.ticket_id.clone()))
+        .collect();
+    report.check(
+        "on-chain winner",
+        expected == on_chain,
+        format!("replayed {expected:?}, state has {on_chain:?}"),
+    );
+
+    // The progressive jackpot ticket is chosen by the second number following the draw's
+    if let Some(on_chain) = draw.jackpot_index {
+        let count = draw.random_numbers.len();
+        match random_numbers(&seed, count + 2) {
+            Ok(numbers) => {
+                let index = winner_indices(&numbers[count + 1..], ticket_count)[0];
+                report.check(
+                    "jackpot ticket",
+                    index == on_chain,
+                    format!("replayed {index}, proof has {on_chain}"),
+                );
+            }
+            Err(err) => report.check("jackpot ticket", false, err.to_string()),
+        }
+    }
+
+    report.failures
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn testdata(path: &str) -> String {
+        format!("{}/src/bin/testdata/{path}", env!("CARGO_MANIFEST_DIR"))
+    }
+
+    fn args(list: &[&str]) -> impl Iterator<Item = String> {
+        list.iter()
+            .map(|arg| arg.to_string())
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+
+    fn jackpot_dumps() -> Dumps {
+        let args = Args {
+            state: Some(testdata("jackpot/state.json")),
+            players: vec![
+                testdata("jackpot/players.json"),
+                testdata("jackpot/players_page2.json"),
+            ],
+            proof: vec![testdata("jackpot/proof.json")],
+        };
+        load(&args).unwrap()
+    }
+
+    #[test]
+    fn parse_args_should_works() {
+        let parsed = parse_args(args(&[
+            "--state",
+            "s.json",
+            "--players",
+            "p1.json",
+            "--proof",
+            "d.json",
+            "--players",
+            "p2.json",
+        ]))
+        .unwrap();
+        assert_eq!(parsed.state.as_deref(), Some("s.json"));
+        assert_eq!(parsed.players, vec!["p1.json", "p2.json"]);
+        assert_eq!(parsed.proof, vec!["d.json"]);
+
+        let err = parse_args(args(&["--state", "s.json", "--players"])).unwrap_err();
+        assert_eq!(err, "missing file after --players");
+
+        let err = parse_args(args(&["--seed", "s.json"])).unwrap_err();
+        assert_eq!(err, "unknown argument: --seed");
+
+        let err = parse_args(args(&["--state", "s.json", "--players", "p.json"])).unwrap_err();
+        assert_eq!(err, "--state, --players and --proof are required");
+    }
+
+    #[test]
+    fn replay_jackpot_draw_should_works() {
+        let dumps = jackpot_dumps();
+        assert_eq!(dumps.players.len(), 3);
+        assert_eq!(verify(&dumps), 0);
+    }
+
+    #[test]
+    fn replay_number_pick_draw_should_works() {
+        let args = Args {
+            state: Some(testdata("number_pick/state.json")),
+            players: vec![testdata("number_pick/players.json")],
+            proof: vec![testdata("number_pick/proof.json")],
+        };
+        let dumps = load(&args).unwrap();
+        assert!(dumps.draw.as_ref().unwrap().winning_numbers.is_some());
+        assert_eq!(verify(&dumps), 0);
+    }
+
+    #[test]
+    fn tampered_proof_should_fail() {
+        // another lucky phrase changes the final seed and everything derived from it
+        let mut dumps = jackpot_dumps();
+        dumps.draw.as_mut().unwrap().lucky_phrase = Some("1".to_string());
+        assert!(verify(&dumps) > 0);
+
+        // so does a ticket whose memo doesn't match its contribution
+        let mut dumps = jackpot_dumps();
+        dumps.players.get_mut(&2).unwrap().memo = Some("second".to_string());
+        assert!(verify(&dumps) > 0);
+
+        // a missing page leaves the tickets incomplete
+        let mut dumps = jackpot_dumps();
+        dumps.players.remove(&3);
+        assert!(verify(&dumps) > 0);
+    }
+
+    #[test]
+    fn unreadable_dump_should_fail() {
+        let args = Args {
+            state: Some(testdata("missing.json")),
+            players: vec![testdata("jackpot/players.json")],
+            proof: vec![testdata("jackpot/proof.json")],
+        };
+        assert!(load(&args).unwrap_err().starts_with("can't read"));
+    }
+}
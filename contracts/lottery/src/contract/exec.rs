@@ -1,22 +1,33 @@
 use cosmwasm_std::{
-    attr, coins, to_binary, Addr, BankMsg, DepsMut, Env, MessageInfo, Response, Storage, WasmMsg,
+    attr, coin, coins, ensure, to_json_binary, Addr, BankMsg, Coin, CosmosMsg, DepsMut, Empty, Env,
+    MessageInfo, Order, Response, StdError, StdResult, Storage, Uint128, WasmMsg,
 };
 
+use cw721::Cw721ReceiveMsg;
 use cw_storage_plus::Map;
 
 use crate::{
     auth::exec::{
-        validate_balance, validate_buy, validate_double_buy, validate_draw, validate_owner,
+        load_owner, validate_buy, validate_cancel, validate_double_buy, validate_draw,
+        validate_guardian, validate_nft_gate, validate_not_paused, validate_numbers,
+        validate_owner, validate_pause, validate_referral, validate_refund, validate_sponsor,
+        validate_status, validate_withdraw,
     },
     hash,
-    msg::{ExecuteMsg, QueryMsg},
+    msg::{ExecuteMsg, JackpotWinner, LotteryHookMsg, ReferralPayout},
     state::{
-        GameStatus, PlayerInfo, State, WinnerInfo, IDX_2_ADDR, OWNER, PLAYERS, PLAYER_COUNTER,
-        STATE,
+        Allowlist, DrawRecord, GameStatus, LotteryPeriod, NftPrize, PlayerInfo, Referral,
+        SeedContribution, Sponsorship, State, WinnerInfo, WinnerSelection, ALLOWLIST,
+        ALLOWLIST_MEMBERS, CLAIMED, COMMISSION, DRAW_RECORD, IDX_2_ADDR, NFT_PRIZES, PAUSE,
+        PLATFORM, PLAYERS, PLAYER_COUNTER, POT_OWED, PRIZE_WITHDRAWN, PROGRESSIVE_JACKPOT,
+        REFERRALS, REFERRAL_BPS, REFERRERS, REFUNDED_TICKETS, REFUNDS, SCRATCH_PENDING,
+        SEED_CONTRIBUTIONS, SPONSORED, SPONSORS, STATE, USED_GATE_TOKENS,
     },
     ContractError, Cw721MetadataContract, Extension,
 };
 
+use super::{market, ownership, scratch};
+
 pub trait BaseExecute {
     fn base_execute(
         &self,
@@ -49,7 +60,7 @@ impl<'a> BaseExecute for Cw721MetadataContract<'a> {
 }
 
 pub fn execute(
-    deps: DepsMut,
+    mut deps: DepsMut,
     env: Env,
     info: MessageInfo,
     msg: ExecuteMsg,
@@ -59,48 +70,238 @@ pub fn execute(
     let contract = Cw721MetadataContract::default();
 
     match msg {
-        BuyTicket { denom, memo } => buy_ticket(deps, &env, &info, &denom, memo),
+        BuyTicket {
+            denom,
+            memo,
+            referrer,
+            numbers,
+        } => buy_ticket(deps, &env, &info, &denom, memo, referrer, numbers),
+        BuyTickets {
+            denom,
+            recipients,
+            memo,
+        } => buy_tickets(deps, &env, &info, &denom, recipients, memo),
         DrawLottery {} => draw_lottery(deps, &env, &info),
         ClaimLottery {} => claim_lottery(deps, &env, &info),
+        CancelLottery {} => cancel_lottery(deps, &env, &info),
+        Refund {} => refund(deps, &env, &info),
+        ClaimReferralRewards {} => claim_referral_rewards(deps, &env, &info),
+        Sponsor { name } => sponsor(deps, &env, &info, name),
+        SettleTicket {} => scratch::settle_ticket(deps, &env, &info),
+        Pause { reason, expires_at } => pause(deps, &env, &info, reason, expires_at),
+        Unpause {} => unpause(deps, &env, &info),
+        ReceiveNft(msg) => receive_nft(deps, &env, &info, msg),
         WithdrawFunds {
             amount,
             denom,
             recipient,
         } => withdraw(deps, &env, &info, amount, denom.as_str(), recipient),
-        Transfer { recipient } => transfer(deps, &env, &info, recipient),
+        ProposeOwner { new_owner, expiry } => {
+            ownership::propose_owner(deps, &env, &info, new_owner, expiry)
+        }
+        AcceptOwnership {} => ownership::accept_ownership(deps, &env, &info),
+        CancelOwnershipTransfer {} => ownership::cancel_ownership_transfer(deps, &env, &info),
+        RenounceOwnership {} => ownership::renounce_ownership(deps, &env, &info),
+        ListTicket { token_id, price } => market::list_ticket(deps, &env, &info, token_id, price),
+        CancelListing { token_id } => market::cancel_listing(deps, &env, &info, token_id),
+        BuyListedTicket { token_id } => market::buy_listed_ticket(deps, &env, &info, token_id),
+        UpdateAllowlist { add, remove } => update_allowlist(deps, &env, &info, add, remove),
+        TransferNft { ref token_id, .. } | SendNft { ref token_id, .. } | Burn { ref token_id } => {
+            let token_id = token_id.clone();
+            let res = contract.base_execute(deps.branch(), env, info, msg)?;
+            market::drop_listing(deps.storage, &token_id);
+            Ok(res)
+        }
         _ => contract.base_execute(deps, env, info, msg),
     }
 }
 
 #[allow(clippy::too_many_arguments)]
 pub fn buy_ticket(
-    deps: DepsMut,
+    mut deps: DepsMut,
     env: &Env,
     info: &MessageInfo,
     denom: &str,
     memo: Option<String>,
+    referrer: Option<String>,
+    numbers: Option<Vec<u8>>,
 ) -> Result<Response, ContractError> {
     let mut state = STATE.load(deps.storage)?;
 
-    validate_buy(deps.storage, &state, info, denom, env)?;
+    validate_not_paused(deps.storage, env)?;
 
     let sender = &info.sender;
 
+    let amount = validate_buy(
+        deps.as_ref(),
+        &state,
+        info,
+        denom,
+        env,
+        std::slice::from_ref(sender),
+    )?;
+    let paid = coin(amount.u128(), denom);
+
+    let numbers = validate_numbers(&state, numbers)?;
+
     validate_double_buy(deps.as_ref(), PLAYERS, sender)?;
 
-    update_state_with_buy(deps, env, &mut state, sender, memo)?;
+    // A scratch purchase settles the tickets bought in the previous blocks before its own
+    let mut settled = Response::new();
+    if state.selection.is_scratch() {
+        settled = scratch::settle_pending(deps.branch(), env, &mut state)?;
+
+        if state.is_closed() {
+            STATE.save(deps.storage, &state)?;
+            return Ok(scratch::sold_out(settled, info));
+        }
+        if !SCRATCH_PENDING.exists(deps.storage) {
+            SCRATCH_PENDING.save(deps.storage, &(state.player_count + 1))?;
+        }
+    }
+
+    let gate_tokens = validate_nft_gate(deps.as_ref(), sender)?;
+    for token_id in &gate_tokens {
+        USED_GATE_TOKENS.save(deps.storage, token_id, sender)?;
+    }
+
+    let referral = referrer
+        .map(|referrer| record_referral(deps.branch(), &paid, sender, &referrer))
+        .transpose()?;
+
+    update_state_with_buy(
+        deps.branch(),
+        env,
+        &mut state,
+        sender,
+        memo,
+        numbers,
+        paid.clone(),
+    )?;
+    if state.selection.is_scratch() {
+        scratch::record_block_seed(deps.storage, env, &state)?;
+    }
 
     // mint nft
     let token_id = &state.player_count.to_string();
     let resp = mint_nft(env, token_id, sender, None, Default::default())?;
 
-    let attributes = vec![
+    let pot = deps.querier.query_balance(&env.contract.address, denom)?;
+    let hook = platform_hook(
+        deps.storage,
+        LotteryHookMsg::TicketBought {
+            player: sender.to_string(),
+            ticket_id: token_id.to_owned(),
+            player_count: state.player_count,
+            pot,
+            referrer: referral.as_ref().map(|(referrer, _)| referrer.to_string()),
+            referral_reward: referral.as_ref().and_then(|(_, reward)| reward.clone()),
+            paid: Some(paid),
+        },
+    )?;
+
+    let mut attributes = vec![
         attr("action", "buy_ticket"),
         attr("sender", sender.as_str()),
         attr("denom", denom),
     ];
+    if !gate_tokens.is_empty() {
+        attributes.push(attr("gate_tokens", gate_tokens.join(",")));
+    }
+    if let Some((referrer, reward)) = &referral {
+        attributes.push(attr("referrer", referrer.as_str()));
+        if let Some(reward) = reward {
+            attributes.push(attr("referral_reward", reward.to_string()));
+        }
+    }
+
+    Ok(resp
+        .add_submessages(settled.messages)
+        .add_messages(hook)
+        .add_attributes(settled.attributes)
+        .add_attributes(attributes))
+}
+
+pub fn buy_tickets(
+    mut deps: DepsMut,
+    env: &Env,
+    info: &MessageInfo,
+    denom: &str,
+    recipients: Vec<String>,
+    memo: Option<String>,
+) -> Result<Response, ContractError> {
+    let mut state = STATE.load(deps.storage)?;
+
+    validate_not_paused(deps.storage, env)?;
+
+    ensure!(
+        state.selection.is_jackpot(),
+        ContractError::BulkBuyUnsupported {}
+    );
+
+    let recipients = recipients
+        .iter()
+        .map(|recipient| deps.api.addr_validate(recipient))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let amount = validate_buy(deps.as_ref(), &state, info, denom, env, &recipients)?;
+
+    // The payment is shared by the tickets, the first one takes the remainder
+    let count = recipients.len() as u128;
+    let share = amount.u128() / count;
+    let remainder = amount.u128() % count;
+
+    let pot = deps.querier.query_balance(&env.contract.address, denom)?;
+
+    let mut resp = Response::new();
+    let mut hooks = vec![];
+    for (i, recipient) in recipients.iter().enumerate() {
+        validate_double_buy(deps.as_ref(), PLAYERS, recipient)?;
+
+        for token_id in validate_nft_gate(deps.as_ref(), recipient)? {
+            USED_GATE_TOKENS.save(deps.storage, &token_id, recipient)?;
+        }
+
+        let paid = coin(if i == 0 { share + remainder } else { share }, denom);
+        update_state_with_buy(
+            deps.branch(),
+            env,
+            &mut state,
+            recipient,
+            memo.clone(),
+            None,
+            paid.clone(),
+        )?;
+
+        let token_id = &state.player_count.to_string();
+        let minted = mint_nft(env, token_id, recipient, None, Default::default())?;
+        resp = resp
+            .add_submessages(minted.messages)
+            .add_attributes(minted.attributes);
+
+        hooks.extend(platform_hook(
+            deps.storage,
+            LotteryHookMsg::TicketBought {
+                player: recipient.to_string(),
+                ticket_id: token_id.to_owned(),
+                player_count: state.player_count,
+                pot: pot.clone(),
+                referrer: None,
+                referral_reward: None,
+                paid: Some(paid),
+            },
+        )?);
+    }
+
+    let attributes = vec![
+        attr("action", "buy_tickets"),
+        attr("sender", info.sender.as_str()),
+        attr("denom", denom),
+        attr("quantity", recipients.len().to_string()),
+        attr("paid", amount.to_string()),
+    ];
 
-    Ok(resp.add_attributes(attributes))
+    Ok(resp.add_messages(hooks).add_attributes(attributes))
 }
 
 pub fn draw_lottery(
@@ -112,9 +313,16 @@ pub fn draw_lottery(
 
     let mut state = STATE.load(deps.storage)?;
 
-    let owner = OWNER.load(deps.storage)?;
+    let owner = load_owner(deps.storage)?;
     let player_counter = PLAYER_COUNTER.load(deps.storage)?;
 
+    ensure!(
+        !state.selection.is_scratch(),
+        ContractError::ScratchNotDrawn {}
+    );
+
+    validate_not_paused(deps.storage, env)?;
+
     validate_draw(&state, &owner, info, env, player_counter)?;
 
     let current_height = env.block.height;
@@ -123,92 +331,604 @@ pub fn draw_lottery(
     // Change status to `Closed`
     state.status = GameStatus::Closed;
 
+    market::clear_listings(deps.storage, &env.contract.address)?;
+
     state.seed = hash::finalize(&state.seed, sender, env.block.height, &transaction);
 
-    let winners = choose_winner_infos(deps.storage, PLAYERS, IDX_2_ADDR, &state, player_counter)?;
+    // A number pick draws the winning numbers, a jackpot draws a single winner
+    let (random_numbers, winning_numbers) = match &state.selection {
+        WinnerSelection::NumberPick {
+            pick, max_number, ..
+        } => {
+            let random_numbers = hash::random_numbers(&state.seed, *pick as usize)?;
+            let winning_numbers = hash::pick_numbers(&random_numbers, *max_number);
+            (random_numbers, Some(winning_numbers))
+        }
+        _ if player_counter == 0 => (vec![], None),
+        _ => (hash::random_numbers(&state.seed, 1)?, None),
+    };
+    let winner_indices = match &winning_numbers {
+        Some(winning_numbers) => {
+            tier_winner_indices(deps.storage, &state.selection, winning_numbers)?
+        }
+        None => hash::winner_indices(&random_numbers, player_counter),
+    };
+
+    let winners = choose_winner_infos(deps.storage, PLAYERS, IDX_2_ADDR, &winner_indices)?;
+
+    // The secondary draw of the progressive jackpot, only among the tickets of this round
+    let jackpot = PROGRESSIVE_JACKPOT
+        .may_load(deps.storage)?
+        .filter(|_| player_counter > 0);
+    let jackpot_index = match &jackpot {
+        Some(jackpot) => {
+            let count = random_numbers.len();
+            let numbers = hash::random_numbers(&state.seed, count + 2)?;
+            let won = matches!(state.period, LotteryPeriod::Year {})
+                || numbers[count] % u64::from(jackpot.odds) == 0;
+            won.then(|| hash::winner_indices(&numbers[count + 1..], player_counter)[0])
+        }
+        None => None,
+    };
 
-    if winners.is_empty() {
-        state.winner = vec![];
-    } else {
-        let balances = deps
-            .querier
-            .query_balance(&env.contract.address, &state.unit_price.denom)?;
-        let winner_player = winners.first().unwrap();
-        let winner_info = WinnerInfo {
-            address: winner_player.player_addr.clone(),
-            prize: vec![balances],
-            ticket_id: winner_player.ticket_id.clone(),
-        };
-        state.winner.push(winner_info);
+    DRAW_RECORD.save(
+        deps.storage,
+        &DrawRecord {
+            sender: sender.clone(),
+            height: current_height,
+            lucky_phrase: transaction,
+            seed: state.seed.clone(),
+            random_numbers,
+            winner_indices,
+            winning_numbers: winning_numbers.clone(),
+            jackpot_index,
+        },
+    )?;
+
+    let mut pot = deps
+        .querier
+        .query_balance(&env.contract.address, &state.unit_price.denom)?;
+
+    // Unclaimed referral rewards are paid before the pot is shared
+    let payouts = take_referral_rewards(deps.storage, None)?;
+    for payout in &payouts {
+        pot.amount -= payout.reward.amount;
+    }
+    let (referral_msgs, referral_hook) = pay_referral_rewards(deps.storage, payouts)?;
+
+    // The progressive jackpot takes its share before the commission
+    let jackpot_contribution = jackpot
+        .map(|jackpot| {
+            let amount = pot
+                .amount
+                .multiply_ratio(jackpot.contribution_bps, 10_000u128);
+            coin(amount.u128(), &pot.denom)
+        })
+        .filter(|contribution| !contribution.amount.is_zero());
+    if let Some(contribution) = &jackpot_contribution {
+        pot.amount -= contribution.amount;
+    }
+    let jackpot_winner = match jackpot_index {
+        Some(idx) => {
+            let player = PLAYERS.load(deps.storage, &IDX_2_ADDR.load(deps.storage, idx)?)?;
+            let holder = ticket_holder(deps.storage, &player.ticket_id, &player.player_addr)?;
+            Some(JackpotWinner {
+                address: holder.into_string(),
+                ticket_id: player.ticket_id,
+            })
+        }
+        None => None,
+    };
+
+    // The creator is only paid when someone wins the pot
+    let commission = match COMMISSION.may_load(deps.storage)? {
+        Some(commission) if !winners.is_empty() => Some(commission),
+        _ => None,
+    };
+    let commission_paid = commission.as_ref().map(|commission| commission.of(&pot));
+    let commission_msg = commission
+        .zip(commission_paid.clone())
+        .filter(|(_, paid)| !paid.amount.is_zero())
+        .map(|(commission, paid)| BankMsg::Send {
+            to_address: commission.recipient.into_string(),
+            amount: vec![paid],
+        });
+
+    let mut prize_pot = pot.clone();
+    if let Some(paid) = &commission_paid {
+        prize_pot.amount -= paid.amount;
+    }
+
+    match &winning_numbers {
+        Some(winning_numbers) => {
+            state.winner = tier_prizes(&state.selection, &winners, winning_numbers, &prize_pot);
+        }
+        None if winners.is_empty() => state.winner = vec![],
+        None => {
+            let winner_player = winners.first().unwrap();
+            let winner_info = WinnerInfo {
+                address: winner_player.player_addr.clone(),
+                prize: vec![prize_pot.clone()],
+                ticket_id: winner_player.ticket_id.clone(),
+                nft_prizes: vec![],
+            };
+            state.winner.push(winner_info);
+        }
+    }
+
+    let mut prize_msgs = assign_nft_prizes(deps.storage, &mut state.winner)?;
+    let mut rollover = None;
+    if winning_numbers.is_some() {
+        // Number-pick prizes are paid at once, to the current holders of the tickets
+        for winner in &state.winner {
+            let holder = ticket_holder(deps.storage, &winner.ticket_id, &winner.address)?;
+            prize_msgs.extend(
+                winner
+                    .prize
+                    .iter()
+                    .filter(|prize| !prize.amount.is_zero())
+                    .map(|prize| {
+                        BankMsg::Send {
+                            to_address: holder.to_string(),
+                            amount: vec![prize.clone()],
+                        }
+                        .into()
+                    }),
+            );
+            prize_msgs.extend(send_nft_prizes(
+                deps.storage,
+                &winner.nft_prizes,
+                holder.as_str(),
+            )?);
+            prize_pot.amount -= winner.prize[0].amount;
+        }
+
+        // The share of the tiers without winners is left to the next round
+        rollover = Some(prize_pot).filter(|rollover| !rollover.amount.is_zero());
     }
 
     STATE.save(deps.storage, &state)?;
 
-    let attributes = vec![
+    // The platform funds its next round with the rollover, a standalone lottery keeps it
+    let platform = PLATFORM.may_load(deps.storage)?;
+    let rollover = rollover.filter(|_| platform.is_some());
+    let rollover_msg = platform
+        .clone()
+        .zip(rollover.clone())
+        .map(|(platform, rollover)| BankMsg::Send {
+            to_address: platform.into_string(),
+            amount: vec![rollover],
+        });
+    let jackpot_msg = platform
+        .zip(jackpot_contribution.clone())
+        .map(|(platform, contribution)| BankMsg::Send {
+            to_address: platform.into_string(),
+            amount: vec![contribution],
+        });
+
+    let mut attributes = vec![
         attr("action", "draw_lottery"),
         attr("sender", sender.as_str()),
         attr("height", current_height.to_string()),
     ];
+    if let Some(winning_numbers) = &winning_numbers {
+        let winning_numbers: Vec<_> = winning_numbers.iter().map(u8::to_string).collect();
+        attributes.push(attr("winning_numbers", winning_numbers.join(",")));
+    }
+    if let Some(contribution) = &jackpot_contribution {
+        attributes.push(attr("jackpot_contribution", contribution.to_string()));
+    }
+    if let Some(winner) = &jackpot_winner {
+        attributes.push(attr("jackpot_ticket", &winner.ticket_id));
+    }
 
-    Ok(Response::new().add_attributes(attributes))
+    let hook = platform_hook(
+        deps.storage,
+        LotteryHookMsg::Drawn {
+            winner: state.winner,
+            pot,
+            commission: commission_paid,
+            rollover,
+            jackpot_contribution,
+            jackpot_winner,
+        },
+    )?;
+
+    Ok(Response::new()
+        .add_messages(referral_msgs)
+        .add_messages(referral_hook)
+        .add_messages(commission_msg)
+        .add_messages(prize_msgs)
+        .add_messages(rollover_msg)
+        .add_messages(jackpot_msg)
+        .add_messages(hook)
+        .add_attributes(attributes))
 }
 
 pub fn claim_lottery(
+    deps: DepsMut,
+    _env: &Env,
+    info: &MessageInfo,
+) -> Result<Response, ContractError> {
+    let sender = &info.sender;
+    let state = STATE.load(deps.storage)?;
+
+    // Number-pick prizes are paid at draw
+    ensure!(
+        state.selection.is_jackpot(),
+        ContractError::NothingToClaim {}
+    );
+
+    let winner = state
+        .winner
+        .first()
+        .ok_or(ContractError::NothingToClaim {})?;
+
+    // The holder of the winning ticket claims, the owner keeps the lottery
+    ensure!(
+        is_prize_winner(deps.storage, &state, sender)?,
+        ContractError::Unauthorized {}
+    );
+
+    // Only the first claim sends the NFT prizes and tells the platform
+    let (nft_msgs, hook) = if CLAIMED.exists(deps.storage) {
+        (vec![], None)
+    } else {
+        CLAIMED.save(deps.storage, &Empty {})?;
+        let nft_msgs = send_nft_prizes(deps.storage, &winner.nft_prizes, sender.as_str())?;
+        let hook = platform_hook(
+            deps.storage,
+            LotteryHookMsg::Claimed {
+                claimer: sender.to_string(),
+            },
+        )?;
+        (nft_msgs, hook)
+    };
+
+    let attributes = vec![
+        attr("action", "claim_lottery"),
+        attr("sender", sender.as_str()),
+    ];
+
+    Ok(Response::new()
+        .add_messages(nft_msgs)
+        .add_messages(hook)
+        .add_attributes(attributes))
+}
+
+pub fn cancel_lottery(
+    deps: DepsMut,
+    env: &Env,
+    info: &MessageInfo,
+) -> Result<Response, ContractError> {
+    let sender = &info.sender;
+    let mut state = STATE.load(deps.storage)?;
+    let owner = load_owner(deps.storage)?;
+
+    validate_cancel(&state, &owner, info)?;
+
+    ensure!(
+        !state.selection.is_scratch(),
+        ContractError::ScratchNotDrawn {}
+    );
+
+    // Players get their tickets paid back by `Refund`, the depositors their NFT prizes at once
+    state.status = GameStatus::Cancelled;
+
+    STATE.save(deps.storage, &state)?;
+
+    market::clear_listings(deps.storage, &env.contract.address)?;
+
+    let nft_msgs = return_nft_prizes(deps.storage)?;
+
+    let hook = platform_hook(deps.storage, LotteryHookMsg::Cancelled {})?;
+
+    let attributes = vec![
+        attr("action", "cancel_lottery"),
+        attr("sender", sender.as_str()),
+        attr("height", env.block.height.to_string()),
+    ];
+
+    Ok(Response::new()
+        .add_messages(nft_msgs)
+        .add_messages(hook)
+        .add_attributes(attributes))
+}
+
+pub fn refund(deps: DepsMut, _env: &Env, info: &MessageInfo) -> Result<Response, ContractError> {
+    let sender = &info.sender;
+    let state = STATE.load(deps.storage)?;
+    let refunded = REFUNDS.may_load(deps.storage, sender)?;
+
+    // A sponsor gets the sponsored amount back once, the holders of the tickets what they cost
+    let sponsorship = SPONSORS
+        .may_load(deps.storage, sender)?
+        .filter(|_| refunded.is_none());
+    let tickets = Cw721MetadataContract::default()
+        .tokens
+        .idx
+        .owner
+        .prefix(sender.clone())
+        .keys(deps.storage, None, None, Order::Ascending)
+        .filter(|token_id| {
+            token_id.as_ref().map_or(true, |token_id| {
+                !REFUNDED_TICKETS.has(deps.storage, token_id)
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let nothing_due = sponsorship.is_none() && tickets.is_empty();
+    validate_refund(&state, nothing_due && refunded.is_some(), sender)?;
+    ensure!(
+        !nothing_due,
+        ContractError::NothingToRefund {
+            player: sender.clone(),
+        }
+    );
+
+    let mut amount = coin(0, &state.unit_price.denom);
+    if let Some(sponsorship) = sponsorship {
+        amount.amount += sponsorship.amount.amount;
+    }
+    for token_id in &tickets {
+        amount.amount += ticket_refund(deps.storage, &state, token_id)?.amount;
+        REFUNDED_TICKETS.save(deps.storage, token_id, &Empty {})?;
+    }
+
+    let mut total = refunded.unwrap_or_else(|| coin(0, &state.unit_price.denom));
+    total.amount += amount.amount;
+    REFUNDS.save(deps.storage, sender, &total)?;
+    sub_pot_owed(deps.storage, amount.amount)?;
+
+    let bank_msg = BankMsg::Send {
+        to_address: sender.to_string(),
+        amount: vec![amount.clone()],
+    };
+
+    let attributes = vec![
+        attr("action", "refund"),
+        attr("sender", sender.as_str()),
+        attr("tickets", tickets.join(",")),
+        attr("amount", amount.to_string()),
+    ];
+
+    Ok(Response::new()
+        .add_message(bank_msg)
+        .add_attributes(attributes))
+}
+
+/// What the ticket `token_id` put into the pot
+pub fn ticket_refund(
+    storage: &dyn Storage,
+    state: &State,
+    token_id: &str,
+) -> Result<Coin, ContractError> {
+    let idx = token_id
+        .parse()
+        .map_err(|_| StdError::parse_err("u64", token_id))?;
+    let buyer = IDX_2_ADDR.load(storage, idx)?;
+    let player = PLAYERS.load(storage, &buyer)?;
+
+    Ok(paid_to_pot(storage, state, &buyer, &player)?)
+}
+
+/// What a ticket cost its buyer, less a reward already paid to the buyer's referrer,
+/// which isn't in the contract anymore
+fn paid_to_pot(
+    storage: &dyn Storage,
+    state: &State,
+    buyer: &Addr,
+    player: &PlayerInfo,
+) -> StdResult<Coin> {
+    let mut amount = player
+        .paid
+        .clone()
+        .unwrap_or_else(|| state.unit_price.clone());
+    if let Some(referrer) = REFERRERS.may_load(storage, buyer)? {
+        let referral = REFERRALS.load(storage, (&referrer, buyer))?;
+        if referral.paid {
+            amount.amount -= referral.reward.amount;
+        }
+    }
+
+    Ok(amount)
+}
+
+pub fn pause(
+    deps: DepsMut,
+    env: &Env,
+    info: &MessageInfo,
+    reason: String,
+    expires_at: Option<u64>,
+) -> Result<Response, ContractError> {
+    let sender = &info.sender;
+
+    validate_guardian(deps.storage, sender)?;
+
+    let pause = validate_pause(env, sender, reason, expires_at)?;
+    PAUSE.save(deps.storage, &pause)?;
+
+    let mut attributes = vec![
+        attr("action", "pause"),
+        attr("sender", sender.as_str()),
+        attr("reason", pause.reason),
+    ];
+    if let Some(expires_at) = pause.expires_at {
+        attributes.push(attr("expires_at", expires_at.seconds().to_string()));
+    }
+
+    Ok(Response::new().add_attributes(attributes))
+}
+
+/// Lift the pause, if any
+pub fn unpause(deps: DepsMut, _env: &Env, info: &MessageInfo) -> Result<Response, ContractError> {
+    let sender = &info.sender;
+
+    validate_guardian(deps.storage, sender)?;
+
+    PAUSE.remove(deps.storage);
+
+    let attributes = vec![attr("action", "unpause"), attr("sender", sender.as_str())];
+
+    Ok(Response::new().add_attributes(attributes))
+}
+
+/// Add the sent funds to the pot, recorded under the sender
+pub fn sponsor(
     deps: DepsMut,
     env: &Env,
     info: &MessageInfo,
+    name: Option<String>,
 ) -> Result<Response, ContractError> {
     let sender = &info.sender;
     let state = STATE.load(deps.storage)?;
 
-    // check the ticket' owner is sender
-    let ticket_id = state.winner.first().unwrap().ticket_id.clone();
+    let amount = validate_sponsor(&state, info, name.as_deref())?;
+    let denom = &state.unit_price.denom;
+
+    let mut sponsorship = SPONSORS
+        .may_load(deps.storage, sender)?
+        .unwrap_or_else(|| Sponsorship {
+            sponsor: sender.clone(),
+            name: None,
+            amount: coin(0, denom),
+        });
+    sponsorship.amount.amount += amount;
+    if name.is_some() {
+        sponsorship.name = name;
+    }
+    SPONSORS.save(deps.storage, sender, &sponsorship)?;
+    let sponsored = SPONSORED.may_load(deps.storage)?.unwrap_or_default();
+    SPONSORED.save(deps.storage, &(sponsored + amount))?;
+    add_pot_owed(deps.storage, amount)?;
 
-    let ticket: cw721::OwnerOfResponse = deps.querier.query_wasm_smart(
-        env.contract.address.as_str(),
-        &QueryMsg::OwnerOf {
-            token_id: ticket_id,
-            include_expired: Some(true),
+    let pot = deps.querier.query_balance(&env.contract.address, denom)?;
+    let hook = platform_hook(
+        deps.storage,
+        LotteryHookMsg::Sponsored {
+            sponsor: sender.to_string(),
+            amount: coin(amount.u128(), denom),
+            pot,
         },
     )?;
 
-    // check lottery is closed and sender is winner
-    if state.is_closed() && ticket.owner == *sender {
-        OWNER.save(deps.storage, sender)?;
+    let mut attributes = vec![
+        attr("action", "sponsor"),
+        attr("sender", sender.as_str()),
+        attr("amount", coin(amount.u128(), denom).to_string()),
+    ];
+    if let Some(name) = &sponsorship.name {
+        attributes.push(attr("name", name));
+    }
 
-        let attributes = vec![
-            attr("action", "claim_lottery"),
-            attr("sender", sender.as_str()),
-            attr("owner", sender),
-        ];
+    Ok(Response::new()
+        .add_messages(hook)
+        .add_attributes(attributes))
+}
 
-        Ok(Response::new().add_attributes(attributes))
-    } else {
-        Err(ContractError::Unauthorized {})
-    }
+pub fn claim_referral_rewards(
+    deps: DepsMut,
+    _env: &Env,
+    info: &MessageInfo,
+) -> Result<Response, ContractError> {
+    let sender = &info.sender;
+    let state = STATE.load(deps.storage)?;
+
+    // Unclaimed rewards of a cancelled lottery are voided, the players are refunded instead
+    ensure!(!state.is_cancelled(), ContractError::LotteryCancelled {});
+
+    let payouts = take_referral_rewards(deps.storage, Some(sender))?;
+    let Some(payout) = payouts.first() else {
+        return Err(ContractError::NoReferralRewards {
+            referrer: sender.clone(),
+        });
+    };
+    let amount = payout.reward.to_string();
+
+    let (msgs, hook) = pay_referral_rewards(deps.storage, payouts)?;
+
+    let attributes = vec![
+        attr("action", "claim_referral_rewards"),
+        attr("sender", sender.as_str()),
+        attr("amount", amount),
+    ];
+
+    Ok(Response::new()
+        .add_messages(msgs)
+        .add_messages(hook)
+        .add_attributes(attributes))
 }
 
-pub fn transfer(
+/// Deposit a cw721 token sent by the owner as a prize of the lottery
+pub fn receive_nft(
     deps: DepsMut,
     env: &Env,
     info: &MessageInfo,
-    recipient: String,
+    msg: Cw721ReceiveMsg,
+) -> Result<Response, ContractError> {
+    let collection = &info.sender;
+    let depositor = deps.api.addr_validate(&msg.sender)?;
+    let state = STATE.load(deps.storage)?;
+    let owner = load_owner(deps.storage)?;
+
+    // The lottery's own tickets are not prizes
+    ensure!(
+        depositor == owner && *collection != env.contract.address,
+        ContractError::Unauthorized {}
+    );
+    ensure!(
+        !state.selection.is_scratch(),
+        ContractError::NftPrizesUnsupported {}
+    );
+    validate_status(&state)?;
+
+    NFT_PRIZES.save(deps.storage, (collection, &msg.token_id), &depositor)?;
+
+    let attributes = vec![
+        attr("action", "deposit_nft_prize"),
+        attr("sender", depositor.as_str()),
+        attr("collection", collection.as_str()),
+        attr("token_id", msg.token_id),
+    ];
+
+    Ok(Response::new().add_attributes(attributes))
+}
+
+pub fn update_allowlist(
+    deps: DepsMut,
+    _env: &Env,
+    info: &MessageInfo,
+    add: Vec<String>,
+    remove: Vec<String>,
 ) -> Result<Response, ContractError> {
     let sender = &info.sender;
-    let owner = OWNER.load(deps.storage)?;
+    let owner = load_owner(deps.storage)?;
 
     validate_owner(&owner, info)?;
 
-    let recipient: Addr = deps.api.addr_validate(&recipient)?;
-    let height = env.block.height;
+    ensure!(
+        ALLOWLIST.may_load(deps.storage)? == Some(Allowlist::Addresses {}),
+        ContractError::AllowlistNotInline {}
+    );
 
-    OWNER.save(deps.storage, &recipient)?;
+    // Players who bought a ticket must stay eligible
+    let player_counter = PLAYER_COUNTER.load(deps.storage)?;
+    ensure!(player_counter == 0, ContractError::AllowlistLocked {});
+
+    for address in &add {
+        let address = deps.api.addr_validate(address)?;
+        ALLOWLIST_MEMBERS.save(deps.storage, &address, &Empty {})?;
+    }
+    for address in &remove {
+        let address = deps.api.addr_validate(address)?;
+        ALLOWLIST_MEMBERS.remove(deps.storage, &address);
+    }
 
     let attributes = vec![
-        attr("action", "transfer_lottery"),
+        attr("action", "update_allowlist"),
         attr("sender", sender.as_str()),
-        attr("recipient", recipient.as_str()),
-        attr("height", height.to_string()),
+        attr("added", add.len().to_string()),
+        attr("removed", remove.len().to_string()),
     ];
 
     Ok(Response::new().add_attributes(attributes))
@@ -224,13 +944,25 @@ pub fn withdraw(
 ) -> Result<Response, ContractError> {
     let sender = &info.sender;
 
-    let owner = OWNER.load(deps.storage)?;
+    let state = STATE.load(deps.storage)?;
+    let is_winner = is_prize_winner(deps.storage, &state, sender)?;
 
-    validate_owner(&owner, info)?;
+    // The winner withdraws the prize, the owner what the lottery doesn't owe
+    if !is_winner {
+        let owner = load_owner(deps.storage)?;
+        validate_owner(&owner, info)?;
+    }
 
     let balance = deps.querier.query_balance(&env.contract.address, denom)?;
+    let prize = prize_left(deps.storage, &state)?;
+    let locked = locked_in_pot(deps.storage, env, &state, &prize)?;
 
-    validate_balance(&balance, amount)?;
+    validate_withdraw(&state, &balance, amount, &locked, &prize, is_winner)?;
+
+    if is_winner && denom == state.unit_price.denom {
+        let withdrawn = PRIZE_WITHDRAWN.may_load(deps.storage)?.unwrap_or_default();
+        PRIZE_WITHDRAWN.save(deps.storage, &(withdrawn + Uint128::from(amount)))?;
+    }
 
     let recipient = recipient.unwrap_or(sender.to_string());
 
@@ -252,34 +984,319 @@ pub fn withdraw(
         .add_attributes(attributes))
 }
 
-// Choose winner use random number in players
+/// What the lottery still owes in the pot denom: the ticket payments and sponsorships not
+/// refunded yet, the prize left to the winner or the scratch prizes still to win
+fn locked_in_pot(
+    storage: &dyn Storage,
+    env: &Env,
+    state: &State,
+    prize: &Coin,
+) -> Result<Coin, ContractError> {
+    let mut locked = coin(0, &state.unit_price.denom);
+
+    if state.selection.is_scratch() {
+        locked.amount = scratch::reserved_prizes(storage, env, state)?;
+        return Ok(locked);
+    }
+
+    match state.status {
+        // Nothing is refunded before the lottery is cancelled
+        GameStatus::Activing | GameStatus::Cancelled => {
+            locked.amount = POT_OWED.may_load(storage)?.unwrap_or_default();
+        }
+        GameStatus::Closed => locked.amount = prize.amount,
+    }
+
+    Ok(locked)
+}
+
+/// The part of a drawn pot still held for the winner, number-pick and scratch prizes
+/// are paid as soon as they're won
+fn prize_left(storage: &dyn Storage, state: &State) -> StdResult<Coin> {
+    let mut prize = coin(0, &state.unit_price.denom);
+    if let Some(winner) = state.winner.first().filter(|_| state.is_closed()) {
+        if !state.selection.is_number_pick() && !state.selection.is_scratch() {
+            prize.amount = winner
+                .prize
+                .iter()
+                .filter(|coin| coin.denom == prize.denom)
+                .map(|coin| coin.amount)
+                .sum();
+        }
+    }
+    let withdrawn = PRIZE_WITHDRAWN.may_load(storage)?.unwrap_or_default();
+    prize.amount = prize.amount.saturating_sub(withdrawn);
+
+    Ok(prize)
+}
+
+/// Whether `sender` holds the winning ticket of a drawn jackpot
+fn is_prize_winner(storage: &dyn Storage, state: &State, sender: &Addr) -> StdResult<bool> {
+    let paid_at_once = state.selection.is_number_pick() || state.selection.is_scratch();
+    match state.winner.first() {
+        Some(winner) if state.is_closed() && !paid_at_once => {
+            // Nobody holds a burned ticket
+            let ticket = Cw721MetadataContract::default()
+                .tokens
+                .may_load(storage, &winner.ticket_id)?;
+            Ok(ticket.is_some_and(|ticket| ticket.owner == sender))
+        }
+        _ => Ok(false),
+    }
+}
+
+// Choose winners by their ticket indices
 pub fn choose_winner_infos(
     storage: &dyn Storage,
     players: Map<&Addr, PlayerInfo>,
     idx_addr: Map<u64, Addr>,
-    state: &State,
-    player_counter: u64,
+    winner_indices: &[u64],
 ) -> Result<Vec<PlayerInfo>, ContractError> {
-    // validate_winner_selection(state)?;
+    winner_indices
+        .iter()
+        .map(|idx| {
+            let address = idx_addr.load(storage, *idx)?;
+            Ok(players.load(storage, &address)?)
+        })
+        .collect()
+}
 
-    if state.player_count == 0 {
-        Ok(vec![])
-    } else if state.player_count == 1 {
-        let winner = players.first(storage)?;
-        Ok(winner.into_iter().map(|(_, player)| player).collect())
-    } else {
-        let seed = state.seed.as_str();
-        let seed_num = hash::hash_to_u64(seed);
+/// Ticket indices matching enough winning numbers for a prize tier
+fn tier_winner_indices(
+    storage: &dyn Storage,
+    selection: &WinnerSelection,
+    winning_numbers: &[u8],
+) -> StdResult<Vec<u64>> {
+    let mut indices = vec![];
+    for item in IDX_2_ADDR.range(storage, None, None, Order::Ascending) {
+        let (idx, address) = item?;
+        let player = PLAYERS.load(storage, &address)?;
+        if selection.tier_of(player.matches(winning_numbers)).is_some() {
+            indices.push(idx);
+        }
+    }
+
+    Ok(indices)
+}
 
-        println!("The seed num is {:?}", seed_num);
+/// Share the `pot` between the tiers, then between the winners of each tier
+fn tier_prizes(
+    selection: &WinnerSelection,
+    winners: &[PlayerInfo],
+    winning_numbers: &[u8],
+    pot: &Coin,
+) -> Vec<WinnerInfo> {
+    let tiers: Vec<_> = winners
+        .iter()
+        .filter_map(|winner| selection.tier_of(winner.matches(winning_numbers)))
+        .collect();
+
+    winners
+        .iter()
+        .zip(&tiers)
+        .map(|(winner, tier)| {
+            let tier_winners = tiers.iter().filter(|t| t.matches == tier.matches).count();
+            let amount = pot
+                .amount
+                .multiply_ratio(u128::from(tier.pct), 100 * tier_winners as u128);
+            WinnerInfo {
+                address: winner.player_addr.clone(),
+                prize: vec![coin(amount.u128(), &pot.denom)],
+                ticket_id: winner.ticket_id.clone(),
+                nft_prizes: vec![],
+            }
+        })
+        .collect()
+}
 
-        let idx = seed_num % player_counter + 1;
-        println!("The idx is: {:?}", idx);
+/// Hand the NFT prizes out to the winners in turn, or back to their depositors without winners
+fn assign_nft_prizes(
+    storage: &mut dyn Storage,
+    winners: &mut [WinnerInfo],
+) -> StdResult<Vec<CosmosMsg>> {
+    if winners.is_empty() {
+        return return_nft_prizes(storage);
+    }
 
-        let address = idx_addr.may_load(storage, idx)?.unwrap();
-        let player_info = players.load(storage, &address)?;
-        Ok(vec![player_info])
+    let prizes = NFT_PRIZES
+        .keys(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    let count = winners.len();
+    for (i, (collection, token_id)) in prizes.into_iter().enumerate() {
+        winners[i % count].nft_prizes.push(NftPrize {
+            collection,
+            token_id,
+        });
     }
+
+    Ok(vec![])
+}
+
+/// Transfer the NFT prizes still held by the lottery to `recipient`
+fn send_nft_prizes(
+    storage: &mut dyn Storage,
+    prizes: &[NftPrize],
+    recipient: &str,
+) -> StdResult<Vec<CosmosMsg>> {
+    let mut msgs = vec![];
+    for prize in prizes {
+        let key = (&prize.collection, prize.token_id.as_str());
+        if NFT_PRIZES.has(storage, key) {
+            NFT_PRIZES.remove(storage, key);
+            msgs.push(transfer_nft_msg(prize, recipient)?);
+        }
+    }
+
+    Ok(msgs)
+}
+
+fn return_nft_prizes(storage: &mut dyn Storage) -> StdResult<Vec<CosmosMsg>> {
+    let deposits = NFT_PRIZES
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let mut msgs = vec![];
+    for ((collection, token_id), depositor) in deposits {
+        NFT_PRIZES.remove(storage, (&collection, &token_id));
+        let prize = NftPrize {
+            collection,
+            token_id,
+        };
+        msgs.push(transfer_nft_msg(&prize, depositor.as_str())?);
+    }
+
+    Ok(msgs)
+}
+
+fn transfer_nft_msg(prize: &NftPrize, recipient: &str) -> StdResult<CosmosMsg> {
+    let msg = WasmMsg::Execute {
+        contract_addr: prize.collection.to_string(),
+        msg: to_json_binary(&cw721::Cw721ExecuteMsg::TransferNft {
+            recipient: recipient.to_string(),
+            token_id: prize.token_id.clone(),
+        })?,
+        funds: vec![],
+    };
+
+    Ok(msg.into())
+}
+
+/// Record the `referrer` of the `sender`, credited a share of the ticket price if any
+fn record_referral(
+    deps: DepsMut,
+    paid: &Coin,
+    sender: &Addr,
+    referrer: &str,
+) -> Result<(Addr, Option<Coin>), ContractError> {
+    let referrer = deps.api.addr_validate(referrer)?;
+
+    validate_referral(deps.storage, sender, &referrer)?;
+
+    let bps = REFERRAL_BPS.may_load(deps.storage)?.unwrap_or_default();
+    let amount = paid.amount.multiply_ratio(u128::from(bps), 10_000u128);
+    let reward = coin(amount.u128(), &paid.denom);
+
+    REFERRERS.save(deps.storage, sender, &referrer)?;
+    REFERRALS.save(
+        deps.storage,
+        (&referrer, sender),
+        &Referral {
+            reward: reward.clone(),
+            paid: false,
+        },
+    )?;
+
+    let reward = (!reward.amount.is_zero()).then_some(reward);
+
+    Ok((referrer, reward))
+}
+
+/// Mark the unpaid referral rewards as paid, summed by referrer
+fn take_referral_rewards(
+    storage: &mut dyn Storage,
+    referrer: Option<&Addr>,
+) -> StdResult<Vec<ReferralPayout>> {
+    let unpaid = match referrer {
+        Some(referrer) => REFERRALS
+            .prefix(referrer)
+            .range(storage, None, None, Order::Ascending)
+            .map(|item| item.map(|(player, referral)| ((referrer.clone(), player), referral)))
+            .collect::<StdResult<Vec<_>>>()?,
+        None => REFERRALS
+            .range(storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()?,
+    };
+
+    let mut payouts: Vec<ReferralPayout> = vec![];
+    for ((referrer, player), mut referral) in unpaid {
+        if referral.paid || referral.reward.amount.is_zero() {
+            continue;
+        }
+
+        referral.paid = true;
+        REFERRALS.save(storage, (&referrer, &player), &referral)?;
+        // The reward leaves the pot, it's not refunded with the ticket
+        sub_pot_owed(storage, referral.reward.amount)?;
+
+        // Referrals are ordered by referrer, so the same referrer is the last payout
+        match payouts.last_mut() {
+            Some(payout) if payout.referrer == referrer.as_str() => {
+                payout.reward.amount += referral.reward.amount;
+            }
+            _ => payouts.push(ReferralPayout {
+                referrer: referrer.into_string(),
+                reward: referral.reward,
+            }),
+        }
+    }
+
+    Ok(payouts)
+}
+
+fn pay_referral_rewards(
+    storage: &dyn Storage,
+    payouts: Vec<ReferralPayout>,
+) -> StdResult<(Vec<BankMsg>, Option<CosmosMsg>)> {
+    if payouts.is_empty() {
+        return Ok((vec![], None));
+    }
+
+    let msgs = payouts
+        .iter()
+        .map(|payout| BankMsg::Send {
+            to_address: payout.referrer.clone(),
+            amount: vec![payout.reward.clone()],
+        })
+        .collect();
+    let hook = platform_hook(storage, LotteryHookMsg::ReferralsPaid { payouts })?;
+
+    Ok((msgs, hook))
+}
+
+fn add_pot_owed(storage: &mut dyn Storage, amount: Uint128) -> StdResult<()> {
+    let owed = POT_OWED.may_load(storage)?.unwrap_or_default();
+    POT_OWED.save(storage, &(owed + amount))
+}
+
+fn sub_pot_owed(storage: &mut dyn Storage, amount: Uint128) -> StdResult<()> {
+    let owed = POT_OWED.may_load(storage)?.unwrap_or_default();
+    POT_OWED.save(storage, &owed.checked_sub(amount)?)
+}
+
+/// The current holder of a ticket, or its `buyer` once it's burned
+pub fn ticket_holder(storage: &dyn Storage, ticket_id: &str, buyer: &Addr) -> StdResult<Addr> {
+    let ticket = Cw721MetadataContract::default()
+        .tokens
+        .may_load(storage, ticket_id)?;
+    Ok(ticket.map_or_else(|| buyer.clone(), |ticket| ticket.owner))
+}
+
+/// Notify the platform which created the lottery, if any
+fn platform_hook(storage: &dyn Storage, hook: LotteryHookMsg) -> StdResult<Option<CosmosMsg>> {
+    PLATFORM
+        .may_load(storage)?
+        .map(|platform| hook.into_cosmos_msg(&platform))
+        .transpose()
 }
 
 fn update_state_with_buy(
@@ -288,6 +1305,8 @@ fn update_state_with_buy(
     state: &mut State,
     sender: &Addr,
     memo: Option<String>,
+    numbers: Option<Vec<u8>>,
+    paid: Coin,
 ) -> Result<(), ContractError> {
     let current_height = env.block.height;
     let lottery_addr = &env.contract.address;
@@ -296,6 +1315,19 @@ fn update_state_with_buy(
 
     state.seed = hash::update(&state.seed, sender, player_counter, current_height, &memo);
 
+    SEED_CONTRIBUTIONS.save(
+        deps.storage,
+        player_counter,
+        &SeedContribution {
+            player: sender.clone(),
+            counter: player_counter,
+            height: current_height,
+            memo_digest: hash::memo_digest(&memo),
+        },
+    )?;
+
+    add_pot_owed(deps.storage, paid.amount)?;
+
     state.player_count += 1;
 
     STATE.save(deps.storage, state)?;
@@ -310,6 +1342,8 @@ fn update_state_with_buy(
             buy_at: current_height,
             ticket_id: player_counter.to_string(),
             memo,
+            numbers,
+            paid: Some(paid),
         },
     )?;
 
@@ -336,7 +1370,7 @@ pub fn mint_nft(
 
     let msg = WasmMsg::Execute {
         contract_addr: env.contract.address.to_string(),
-        msg: to_binary(&mint_msg)?,
+        msg: to_json_binary(&mint_msg)?,
         funds: vec![],
     };
 
@@ -1,10 +1,20 @@
-use cosmwasm_std::{attr, coin, ensure, DepsMut, Env, MessageInfo, Response, Timestamp};
+use cosmwasm_std::{
+    attr, coin, ensure, Decimal, DepsMut, Empty, Env, MessageInfo, Response, Timestamp,
+};
 use cw2::set_contract_version;
 
 use crate::{
+    auth::exec::{
+        validate_number_pick, validate_pricing, validate_progressive_jackpot,
+        validate_scratch_table,
+    },
     hash,
-    msg::InstantiateMsg,
-    state::{GameStatus, LotteryPeriod, State, OWNER, PLAYER_COUNTER, STATE},
+    msg::{AllowlistMsg, InstantiateMsg},
+    state::{
+        Allowlist, Commission, GameStatus, LotteryPeriod, NftGate, State, WinnerSelection,
+        ALLOWLIST, ALLOWLIST_MEMBERS, COMMISSION, NFT_GATE, OWNER, PLATFORM, PLAYER_COUNTER,
+        PROGRESSIVE_JACKPOT, REFERRAL_BPS, RESALE_ROYALTY_BPS, SCRATCH_REMAINING, STATE,
+    },
     ContractError, Cw721InstantiateMsg, Cw721MetadataContract,
 };
 
@@ -24,11 +34,22 @@ pub fn instantiate(
         });
     }
 
-    // Only support OnlyOnce now
-    if !msg.selection.is_jackpot() {
-        return Err(ContractError::UnSupportedWinnerSelection {
-            selection: msg.selection,
-        });
+    // Only support OnlyOnce and number picks now
+    match &msg.selection {
+        WinnerSelection::Jackpot {} => {}
+        WinnerSelection::NumberPick {
+            pick,
+            max_number,
+            tiers,
+        } => validate_number_pick(*pick, *max_number, tiers)?,
+        WinnerSelection::Scratch { prizes } => {
+            validate_scratch_table(&info, prizes, msg.max_players, &msg.unit_price_denom)?
+        }
+        WinnerSelection::Fixed { .. } => {
+            return Err(ContractError::UnSupportedWinnerSelection {
+                selection: msg.selection,
+            })
+        }
     }
 
     let sender = &info.sender;
@@ -51,7 +72,7 @@ pub fn instantiate(
         symbol: msg.symobl.clone(),
         height: env.block.height,
         created_at,
-        expiratoin: expiration,
+        expiration,
         unit_price: coin(msg.unit_price_amount, msg.unit_price_denom),
         period,
         selection: msg.selection,
@@ -61,12 +82,87 @@ pub fn instantiate(
         seed: hash::init(env.contract.address.as_str(), env.block.height),
         winner: vec![],
         extension: Default::default(),
+        pricing: msg.pricing.unwrap_or_default(),
     };
 
+    validate_pricing(&config.pricing)?;
+
     STATE.save(deps.storage, &config)?;
     OWNER.save(deps.storage, sender)?;
     PLAYER_COUNTER.save(deps.storage, &0)?;
 
+    if let WinnerSelection::Scratch { prizes } = &config.selection {
+        let remaining = prizes.iter().map(|prize| prize.count).collect();
+        SCRATCH_REMAINING.save(deps.storage, &remaining)?;
+    }
+
+    if let Some(platform) = msg.platform {
+        PLATFORM.save(deps.storage, &deps.api.addr_validate(&platform)?)?;
+    }
+
+    if let Some(commission) = msg.commission {
+        ensure!(
+            commission.rate <= Decimal::one(),
+            ContractError::InvalidCommission {
+                rate: commission.rate
+            }
+        );
+
+        let commission = Commission {
+            recipient: deps.api.addr_validate(&commission.recipient)?,
+            rate: commission.rate,
+        };
+        COMMISSION.save(deps.storage, &commission)?;
+    }
+
+    match msg.allowlist {
+        Some(AllowlistMsg::Addresses { addresses }) => {
+            ALLOWLIST.save(deps.storage, &Allowlist::Addresses {})?;
+            for address in addresses {
+                let address = deps.api.addr_validate(&address)?;
+                ALLOWLIST_MEMBERS.save(deps.storage, &address, &Empty {})?;
+            }
+        }
+        Some(AllowlistMsg::Group { group }) => {
+            let group = deps.api.addr_validate(&group)?;
+            ALLOWLIST.save(deps.storage, &Allowlist::Group { group })?;
+        }
+        None => {}
+    }
+
+    if let Some(bps) = msg.referral_bps {
+        ensure!(bps <= 10_000, ContractError::InvalidReferralBps { bps });
+        REFERRAL_BPS.save(deps.storage, &bps)?;
+    }
+
+    if let Some(jackpot) = msg.progressive_jackpot {
+        validate_progressive_jackpot(&jackpot)?;
+        ensure!(
+            PLATFORM.exists(deps.storage),
+            ContractError::InvalidProgressiveJackpot {}
+        );
+        PROGRESSIVE_JACKPOT.save(deps.storage, &jackpot)?;
+    }
+
+    if let Some(bps) = msg.resale_royalty_bps {
+        ensure!(
+            bps <= 10_000 && PLATFORM.exists(deps.storage),
+            ContractError::InvalidResaleRoyalty { bps }
+        );
+        RESALE_ROYALTY_BPS.save(deps.storage, &bps)?;
+    }
+
+    if let Some(gate) = msg.nft_gate {
+        ensure!(gate.min_tokens > 0, ContractError::InvalidNftGate {});
+
+        let gate = NftGate {
+            collection: deps.api.addr_validate(&gate.collection)?,
+            min_tokens: gate.min_tokens,
+            once_per_token: gate.once_per_token,
+        };
+        NFT_GATE.save(deps.storage, &gate)?;
+    }
+
     let init_msg = Cw721InstantiateMsg {
         name: msg.name,
         symbol: msg.symobl,
@@ -0,0 +1,210 @@
+//! Ticket resale: a listed ticket is escrowed by the lottery's own cw721 approval,
+//! so the token and the payment change hands in the buying transaction.
+use cosmwasm_std::{
+    attr, coin, ensure, Addr, BankMsg, Coin, DepsMut, Env, MessageInfo, Order, Response, StdResult,
+    Storage,
+};
+use cw_utils::{must_pay, Expiration};
+
+use crate::{
+    auth::exec::{validate_not_paused, validate_status},
+    state::{Listing, LISTINGS, PLATFORM, RESALE_ROYALTY_BPS, STATE},
+    ContractError, Cw721MetadataContract,
+};
+
+pub fn list_ticket(
+    mut deps: DepsMut,
+    env: &Env,
+    info: &MessageInfo,
+    token_id: String,
+    price: Coin,
+) -> Result<Response, ContractError> {
+    let sender = &info.sender;
+    let state = STATE.load(deps.storage)?;
+
+    validate_not_paused(deps.storage, env)?;
+
+    validate_status(&state)?;
+
+    ensure!(
+        !price.amount.is_zero(),
+        ContractError::InvalidListingPrice {}
+    );
+
+    let contract = Cw721MetadataContract::default();
+    let ticket = contract.tokens.load(deps.storage, &token_id)?;
+    ensure!(ticket.owner == *sender, ContractError::Unauthorized {});
+
+    contract
+        ._update_approvals(
+            deps.branch(),
+            env,
+            info,
+            env.contract.address.as_str(),
+            &token_id,
+            true,
+            Some(Expiration::Never {}),
+        )
+        .or_else(|err| Err(ContractError::try_from(err)?))?;
+
+    let listing = Listing {
+        seller: sender.clone(),
+        price,
+        listed_at: env.block.time,
+    };
+    LISTINGS.save(deps.storage, &token_id, &listing)?;
+
+    let attributes = vec![
+        attr("action", "list_ticket"),
+        attr("sender", sender.as_str()),
+        attr("token_id", token_id),
+        attr("price", listing.price.to_string()),
+    ];
+
+    Ok(Response::new().add_attributes(attributes))
+}
+
+pub fn cancel_listing(
+    deps: DepsMut,
+    env: &Env,
+    info: &MessageInfo,
+    token_id: String,
+) -> Result<Response, ContractError> {
+    let sender = &info.sender;
+
+    let listing = load_listing(deps.storage, &token_id)?;
+    ensure!(listing.seller == *sender, ContractError::Unauthorized {});
+
+    revoke_escrow(deps.storage, &env.contract.address, &token_id)?;
+    LISTINGS.remove(deps.storage, &token_id);
+
+    let attributes = vec![
+        attr("action", "cancel_listing"),
+        attr("sender", sender.as_str()),
+        attr("token_id", token_id),
+    ];
+
+    Ok(Response::new().add_attributes(attributes))
+}
+
+pub fn buy_listed_ticket(
+    mut deps: DepsMut,
+    env: &Env,
+    info: &MessageInfo,
+    token_id: String,
+) -> Result<Response, ContractError> {
+    let buyer = &info.sender;
+    let state = STATE.load(deps.storage)?;
+
+    validate_not_paused(deps.storage, env)?;
+
+    validate_status(&state)?;
+
+    let listing = load_listing(deps.storage, &token_id)?;
+
+    let paid = must_pay(info, &listing.price.denom)?;
+    ensure!(
+        paid == listing.price.amount,
+        ContractError::ListingPriceMismatch {
+            price: listing.price
+        }
+    );
+
+    // A ticket moved or revoked since it was listed can't be sold anymore
+    let contract = Cw721MetadataContract::default();
+    let ticket = contract.tokens.load(deps.storage, &token_id)?;
+    let escrowed = ticket.approvals.iter().any(|approval| {
+        approval.spender == env.contract.address && !approval.is_expired(&env.block)
+    });
+    ensure!(
+        ticket.owner == listing.seller && escrowed,
+        ContractError::ListingInvalid { token_id }
+    );
+
+    // The lottery spends its own approval
+    let escrow = MessageInfo {
+        sender: env.contract.address.clone(),
+        funds: vec![],
+    };
+    contract
+        ._transfer_nft(deps.branch(), env, &escrow, buyer.as_str(), &token_id)
+        .or_else(|err| Err(ContractError::try_from(err)?))?;
+    LISTINGS.remove(deps.storage, &token_id);
+
+    let platform = PLATFORM.may_load(deps.storage)?;
+    let bps = RESALE_ROYALTY_BPS
+        .may_load(deps.storage)?
+        .unwrap_or_default();
+    let royalty = coin(
+        paid.multiply_ratio(u128::from(bps), 10_000u128).u128(),
+        &listing.price.denom,
+    );
+    let proceeds = coin((paid - royalty.amount).u128(), &listing.price.denom);
+
+    let mut msgs = vec![];
+    if !proceeds.amount.is_zero() {
+        msgs.push(BankMsg::Send {
+            to_address: listing.seller.to_string(),
+            amount: vec![proceeds.clone()],
+        });
+    }
+    if let Some(platform) = platform.filter(|_| !royalty.amount.is_zero()) {
+        msgs.push(BankMsg::Send {
+            to_address: platform.into_string(),
+            amount: vec![royalty.clone()],
+        });
+    }
+
+    let attributes = vec![
+        attr("action", "buy_listed_ticket"),
+        attr("sender", buyer.as_str()),
+        attr("seller", listing.seller.as_str()),
+        attr("token_id", token_id),
+        attr("price", listing.price.to_string()),
+        attr("royalty", royalty.to_string()),
+    ];
+
+    Ok(Response::new()
+        .add_messages(msgs)
+        .add_attributes(attributes))
+}
+
+/// Drop every listing with its escrow approval, once the lottery is drawn or cancelled
+pub fn clear_listings(storage: &mut dyn Storage, lottery: &Addr) -> StdResult<()> {
+    let token_ids = LISTINGS
+        .keys(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    for token_id in token_ids {
+        revoke_escrow(storage, lottery, &token_id)?;
+        LISTINGS.remove(storage, &token_id);
+    }
+
+    Ok(())
+}
+
+/// Forget the listing of a ticket moved or burned by its holder, its escrow went with it
+pub fn drop_listing(storage: &mut dyn Storage, token_id: &str) {
+    LISTINGS.remove(storage, token_id);
+}
+
+fn load_listing(storage: &dyn Storage, token_id: &str) -> Result<Listing, ContractError> {
+    LISTINGS
+        .may_load(storage, token_id)?
+        .ok_or_else(|| ContractError::ListingNotFound {
+            token_id: token_id.to_owned(),
+        })
+}
+
+/// Remove the lottery's approval on the ticket, whoever holds it now
+fn revoke_escrow(storage: &mut dyn Storage, lottery: &Addr, token_id: &str) -> StdResult<()> {
+    let tokens = Cw721MetadataContract::default().tokens;
+    let Some(mut ticket) = tokens.may_load(storage, token_id)? else {
+        return Ok(());
+    };
+    ticket
+        .approvals
+        .retain(|approval| approval.spender != *lottery);
+
+    tokens.save(storage, token_id, &ticket)
+}
@@ -0,0 +1,176 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{attr, ensure, Coin, DepsMut, Env, Response, Storage, Timestamp, Uint128};
+use cw2::{get_contract_version, set_contract_version};
+use cw_storage_plus::Item;
+use semver::Version;
+
+use crate::{
+    msg::MigrateMsg,
+    state::{
+        GameStatus, LotteryPeriod, State, WinnerInfo, WinnerSelection, PLATFORM, POT_OWED, STATE,
+    },
+    ContractError, Extension,
+};
+
+use super::{CONTRACT_NAME, CONTRACT_VERSION};
+
+/// `State` as stored by 1.0.x, before `expiratoin` was renamed to `expiration`
+#[cw_serde]
+struct StateV1 {
+    pub name: String,
+    pub symbol: String,
+    pub height: u64,
+    pub created_at: Timestamp,
+    pub expiratoin: Timestamp,
+    pub unit_price: Coin,
+    pub period: LotteryPeriod,
+    pub selection: WinnerSelection,
+    pub player_count: u64,
+    pub max_players: u64,
+    pub status: GameStatus,
+    pub seed: String,
+    pub winner: Vec<WinnerInfo>,
+    pub extension: Extension,
+}
+
+const STATE_V1: Item<StateV1> = Item::new("state");
+
+pub fn migrate(deps: DepsMut, _env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+    let stored = get_contract_version(deps.storage)?;
+
+    ensure!(
+        stored.contract == CONTRACT_NAME,
+        ContractError::MigrateContractMismatch {
+            contract: stored.contract
+        }
+    );
+
+    let stored_version: Version = stored.version.parse()?;
+    let current_version: Version = CONTRACT_VERSION.parse()?;
+
+    ensure!(
+        stored_version <= current_version,
+        ContractError::MigrateVersionDowngrade {
+            stored: stored.version,
+            current: CONTRACT_VERSION.to_string(),
+        }
+    );
+
+    if stored_version < Version::new(1, 1, 0) {
+        migrate_state_from_v1(deps.storage)?;
+    }
+
+    if let Some(platform) = msg.platform {
+        PLATFORM.save(deps.storage, &deps.api.addr_validate(&platform)?)?;
+    }
+
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    let attrs = vec![
+        attr("action", "migrate"),
+        attr("from_version", stored_version.to_string()),
+        attr("to_version", CONTRACT_VERSION),
+    ];
+
+    Ok(Response::new().add_attributes(attrs))
+}
+
+fn migrate_state_from_v1(storage: &mut dyn Storage) -> Result<(), ContractError> {
+    let old = STATE_V1.load(storage)?;
+
+    // 1.0.x sold every ticket at the unit price, and had no sponsors nor refunds
+    let owed = old.unit_price.amount * Uint128::from(old.player_count);
+    POT_OWED.save(storage, &owed)?;
+
+    let state = State {
+        name: old.name,
+        symbol: old.symbol,
+        height: old.height,
+        created_at: old.created_at,
+        expiration: old.expiratoin,
+        unit_price: old.unit_price,
+        period: old.period,
+        selection: old.selection,
+        player_count: old.player_count,
+        max_players: old.max_players,
+        status: old.status,
+        seed: old.seed,
+        winner: old.winner,
+        extension: old.extension,
+        pricing: Default::default(),
+    };
+
+    STATE.save(storage, &state)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::{
+        coin,
+        testing::{mock_dependencies, mock_env},
+    };
+
+    use super::*;
+
+    fn state_v1() -> StateV1 {
+        StateV1 {
+            name: "LOTTERY".to_string(),
+            symbol: "LOTTER".to_string(),
+            height: 12_345,
+            created_at: Timestamp::from_seconds(1_000),
+            expiratoin: Timestamp::from_seconds(2_000),
+            unit_price: coin(100, "aconst"),
+            period: LotteryPeriod::Hour {},
+            selection: WinnerSelection::Jackpot {},
+            player_count: 0,
+            max_players: 3,
+            status: GameStatus::Activing,
+            seed: "seed".to_string(),
+            winner: vec![],
+            extension: Default::default(),
+        }
+    }
+
+    #[test]
+    fn migrate_from_v1_should_works() {
+        let mut deps = mock_dependencies();
+        set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "1.0.0").unwrap();
+        STATE_V1.save(deps.as_mut().storage, &state_v1()).unwrap();
+
+        migrate(deps.as_mut(), mock_env(), MigrateMsg { platform: None }).unwrap();
+
+        let state = STATE.load(deps.as_ref().storage).unwrap();
+        assert_eq!(state.expiration, Timestamp::from_seconds(2_000));
+        assert_eq!(state.name, "LOTTERY");
+
+        let version = get_contract_version(deps.as_ref().storage).unwrap();
+        assert_eq!(version.version, CONTRACT_VERSION);
+    }
+
+    #[test]
+    fn migrate_should_fail() {
+        let mut deps = mock_dependencies();
+        set_contract_version(deps.as_mut().storage, "crates.io:other", "1.0.0").unwrap();
+
+        let err = migrate(deps.as_mut(), mock_env(), MigrateMsg { platform: None }).unwrap_err();
+        assert_eq!(
+            ContractError::MigrateContractMismatch {
+                contract: "crates.io:other".to_string()
+            },
+            err
+        );
+
+        set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "99.0.0").unwrap();
+
+        let err = migrate(deps.as_mut(), mock_env(), MigrateMsg { platform: None }).unwrap_err();
+        assert_eq!(
+            ContractError::MigrateVersionDowngrade {
+                stored: "99.0.0".to_string(),
+                current: CONTRACT_VERSION.to_string(),
+            },
+            err
+        );
+    }
+}
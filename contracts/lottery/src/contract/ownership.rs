@@ -0,0 +1,108 @@
+//! Two-step ownership transfer: the owner proposes, the new owner accepts before the expiry.
+use cosmwasm_std::{attr, ensure, DepsMut, Env, MessageInfo, Response};
+use cw_utils::Expiration;
+
+use crate::{
+    auth::exec::{load_owner, validate_acceptance, validate_owner, validate_owner_proposal},
+    state::{GameStatus, OWNER, PENDING_OWNER, STATE},
+    ContractError,
+};
+
+pub fn propose_owner(
+    deps: DepsMut,
+    env: &Env,
+    info: &MessageInfo,
+    new_owner: String,
+    expiry: Option<Expiration>,
+) -> Result<Response, ContractError> {
+    let sender = &info.sender;
+    let owner = load_owner(deps.storage)?;
+
+    validate_owner(&owner, info)?;
+
+    let new_owner = deps.api.addr_validate(&new_owner)?;
+    let pending = validate_owner_proposal(env, new_owner, expiry)?;
+    PENDING_OWNER.save(deps.storage, &pending)?;
+
+    let attributes = vec![
+        attr("action", "propose_owner"),
+        attr("sender", sender.as_str()),
+        attr("new_owner", pending.address.as_str()),
+        attr("expiry", pending.expiry.to_string()),
+    ];
+
+    Ok(Response::new().add_attributes(attributes))
+}
+
+pub fn accept_ownership(
+    deps: DepsMut,
+    env: &Env,
+    info: &MessageInfo,
+) -> Result<Response, ContractError> {
+    let sender = &info.sender;
+
+    validate_acceptance(env, PENDING_OWNER.may_load(deps.storage)?, sender)?;
+
+    OWNER.save(deps.storage, sender)?;
+    PENDING_OWNER.remove(deps.storage);
+
+    let attributes = vec![
+        attr("action", "accept_ownership"),
+        attr("sender", sender.as_str()),
+    ];
+
+    Ok(Response::new().add_attributes(attributes))
+}
+
+pub fn cancel_ownership_transfer(
+    deps: DepsMut,
+    _env: &Env,
+    info: &MessageInfo,
+) -> Result<Response, ContractError> {
+    let sender = &info.sender;
+    let owner = load_owner(deps.storage)?;
+
+    validate_owner(&owner, info)?;
+
+    ensure!(
+        PENDING_OWNER.exists(deps.storage),
+        ContractError::NoPendingOwner {}
+    );
+    PENDING_OWNER.remove(deps.storage);
+
+    let attributes = vec![
+        attr("action", "cancel_ownership_transfer"),
+        attr("sender", sender.as_str()),
+    ];
+
+    Ok(Response::new().add_attributes(attributes))
+}
+
+/// The owner powers are gone for good, and the pending transfer with them.
+/// A running lottery still needs its owner to be drawn or cancelled.
+pub fn renounce_ownership(
+    deps: DepsMut,
+    _env: &Env,
+    info: &MessageInfo,
+) -> Result<Response, ContractError> {
+    let sender = &info.sender;
+    let owner = load_owner(deps.storage)?;
+
+    validate_owner(&owner, info)?;
+
+    let state = STATE.load(deps.storage)?;
+    ensure!(
+        matches!(state.status, GameStatus::Closed | GameStatus::Cancelled),
+        ContractError::RenounceWhileActive {}
+    );
+
+    OWNER.remove(deps.storage);
+    PENDING_OWNER.remove(deps.storage);
+
+    let attributes = vec![
+        attr("action", "renounce_ownership"),
+        attr("sender", sender.as_str()),
+    ];
+
+    Ok(Response::new().add_attributes(attributes))
+}
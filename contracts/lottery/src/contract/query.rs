@@ -1,20 +1,70 @@
-use cosmwasm_std::{to_binary, Binary, Coin, Deps, Env, StdResult};
+use cosmwasm_std::{to_json_binary, Binary, Coin, Deps, Env, Order, StdResult, Timestamp};
 
 use cw721_base::entry::query as cw721_query;
+use cw_storage_plus::Bound;
 
 use crate::{
-    msg::{CurrentStateResp, OwnerResp, PlayInfoResp, QueryMsg, WinnerResp},
-    state::{OWNER, PLAYERS, STATE},
+    hash,
+    msg::{
+        AllowlistResp, CurrentStateResp, DrawProofResp, ListedTicket, ListingsResp, NftGateResp,
+        NftPrizeDeposit, NftPrizesResp, OwnerResp, OwnershipResp, PauseResp, PlayInfoResp,
+        PlayersResp, QueryMsg, QuoteResp, ReferrerResp, ScratchResp, SponsorsResp, UsedGateToken,
+        WinnerResp,
+    },
+    state::{
+        GameStatus, ALLOWLIST, ALLOWLIST_MEMBERS, DRAW_RECORD, IDX_2_ADDR, LISTINGS, NFT_GATE,
+        NFT_PRIZES, OWNER, PAUSE, PENDING_OWNER, PLAYERS, PLAYER_COUNTER, REFERRALS,
+        SCRATCH_PENDING, SCRATCH_REMAINING, SCRATCH_RESULTS, SEED_CONTRIBUTIONS, SPONSORED,
+        SPONSORS, STATE, USED_GATE_TOKENS,
+    },
+    Cw721MetadataContract,
 };
 
+use super::{DEFAULT_LIMIT, MAX_LIMIT};
+
 pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
-        QueryMsg::Owner {} => owner(deps).and_then(|resp| to_binary(&resp)),
-        QueryMsg::Winner {} => winner(deps).and_then(|resp| to_binary(&resp)),
-        QueryMsg::CurrentState {} => current_state(deps).and_then(|resp| to_binary(&resp)),
-        QueryMsg::Balances {} => balances(deps, &env).and_then(|cs| to_binary(&cs)),
+        QueryMsg::Owner {} => owner(deps).and_then(|resp| to_json_binary(&resp)),
+        QueryMsg::Ownership {} => ownership(deps).and_then(|resp| to_json_binary(&resp)),
+        QueryMsg::Winner {} => winner(deps).and_then(|resp| to_json_binary(&resp)),
+        QueryMsg::CurrentState {} => current_state(deps).and_then(|resp| to_json_binary(&resp)),
+        QueryMsg::Balances {} => balances(deps, &env).and_then(|cs| to_json_binary(&cs)),
         QueryMsg::PlayInfo { address } => {
-            play_info(deps, &address).and_then(|info| to_binary(&info))
+            play_info(deps, &address).and_then(|info| to_json_binary(&info))
+        }
+        QueryMsg::Players { start_after, limit } => {
+            players(deps, start_after, limit).and_then(|resp| to_json_binary(&resp))
+        }
+        QueryMsg::TicketsByIndex { start_after, limit } => {
+            tickets_by_index(deps, start_after, limit).and_then(|resp| to_json_binary(&resp))
+        }
+        QueryMsg::Allowlist { start_after, limit } => {
+            allowlist(deps, start_after, limit).and_then(|resp| to_json_binary(&resp))
+        }
+        QueryMsg::NftGate { start_after, limit } => {
+            nft_gate(deps, start_after, limit).and_then(|resp| to_json_binary(&resp))
+        }
+        QueryMsg::Scratch { start_after, limit } => {
+            scratch(deps, start_after, limit).and_then(|resp| to_json_binary(&resp))
+        }
+        QueryMsg::NftPrizes { start_after, limit } => {
+            nft_prizes(deps, start_after, limit).and_then(|resp| to_json_binary(&resp))
+        }
+        QueryMsg::Sponsors { start_after, limit } => {
+            sponsors(deps, start_after, limit).and_then(|resp| to_json_binary(&resp))
+        }
+        QueryMsg::Listings { start_after, limit } => {
+            listings(deps, start_after, limit).and_then(|resp| to_json_binary(&resp))
+        }
+        QueryMsg::Pause {} => pause(deps, &env).and_then(|resp| to_json_binary(&resp)),
+        QueryMsg::Quote { quantity, at } => {
+            quote(deps, &env, quantity, at).and_then(|resp| to_json_binary(&resp))
+        }
+        QueryMsg::Referrer { address } => {
+            referrer(deps, address).and_then(|resp| to_json_binary(&resp))
+        }
+        QueryMsg::DrawProof { start_after, limit } => {
+            draw_proof(deps, &env, start_after, limit).and_then(|resp| to_json_binary(&resp))
         }
 
         _ => {
@@ -29,6 +79,13 @@ pub fn owner(deps: Deps) -> StdResult<OwnerResp> {
     Ok(OwnerResp { owner })
 }
 
+pub fn ownership(deps: Deps) -> StdResult<OwnershipResp> {
+    Ok(OwnershipResp {
+        owner: OWNER.may_load(deps.storage)?,
+        pending_owner: PENDING_OWNER.may_load(deps.storage)?,
+    })
+}
+
 pub fn winner(deps: Deps) -> StdResult<WinnerResp> {
     let state = STATE.load(deps.storage)?;
     Ok(WinnerResp {
@@ -50,3 +107,259 @@ pub fn play_info(deps: Deps, address: &str) -> StdResult<PlayInfoResp> {
     let player = PLAYERS.may_load(deps.storage, &address)?;
     Ok(PlayInfoResp { info: player })
 }
+
+pub fn players(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<PlayersResp> {
+    let start_after = match start_after {
+        Some(address) => {
+            let address = deps.api.addr_validate(&address)?;
+            let player = PLAYERS.load(deps.storage, &address)?;
+            Some(player.ticket_index()?)
+        }
+        None => None,
+    };
+
+    tickets_by_index(deps, start_after, limit)
+}
+
+pub fn tickets_by_index(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<PlayersResp> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    let players: StdResult<Vec<_>> = IDX_2_ADDR
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (_, address) = item?;
+            PLAYERS.load(deps.storage, &address)
+        })
+        .collect();
+    let total = PLAYER_COUNTER.load(deps.storage)?;
+
+    Ok(PlayersResp {
+        players: players?,
+        total,
+    })
+}
+
+pub fn draw_proof(
+    deps: Deps,
+    env: &Env,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<DrawProofResp> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    let state = STATE.load(deps.storage)?;
+    // The seed is initialised by the contract address and the instantiate height
+    let initial_seed = hash::init(env.contract.address.as_str(), state.height);
+
+    let contributions: StdResult<Vec<_>> = SEED_CONTRIBUTIONS
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(_, contribution)| contribution))
+        .collect();
+    let total = PLAYER_COUNTER.load(deps.storage)?;
+    let draw = DRAW_RECORD.may_load(deps.storage)?;
+
+    Ok(DrawProofResp {
+        initial_seed,
+        contributions: contributions?,
+        total,
+        draw,
+    })
+}
+
+pub fn allowlist(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<AllowlistResp> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start_after = start_after
+        .map(|address| deps.api.addr_validate(&address))
+        .transpose()?;
+    let start = start_after.as_ref().map(Bound::exclusive);
+
+    let members = ALLOWLIST_MEMBERS
+        .keys(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(AllowlistResp {
+        allowlist: ALLOWLIST.may_load(deps.storage)?,
+        members,
+    })
+}
+
+pub fn nft_gate(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<NftGateResp> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.as_deref().map(Bound::exclusive);
+
+    let used_tokens = USED_GATE_TOKENS
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(token_id, player)| UsedGateToken { token_id, player }))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(NftGateResp {
+        gate: NFT_GATE.may_load(deps.storage)?,
+        used_tokens,
+    })
+}
+
+pub fn scratch(deps: Deps, start_after: Option<u64>, limit: Option<u32>) -> StdResult<ScratchResp> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    let results = SCRATCH_RESULTS
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(_, result)| result))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(ScratchResp {
+        remaining: SCRATCH_REMAINING
+            .may_load(deps.storage)?
+            .unwrap_or_default(),
+        pending: SCRATCH_PENDING.may_load(deps.storage)?,
+        results,
+    })
+}
+
+pub fn nft_prizes(
+    deps: Deps,
+    start_after: Option<(String, String)>,
+    limit: Option<u32>,
+) -> StdResult<NftPrizesResp> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start_after = start_after
+        .map(|(collection, token_id)| {
+            deps.api
+                .addr_validate(&collection)
+                .map(|collection| (collection, token_id))
+        })
+        .transpose()?;
+    let start = start_after
+        .as_ref()
+        .map(|(collection, token_id)| Bound::exclusive((collection, token_id.as_str())));
+
+    let prizes = NFT_PRIZES
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            item.map(|((collection, token_id), depositor)| NftPrizeDeposit {
+                collection,
+                token_id,
+                depositor,
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(NftPrizesResp { prizes })
+}
+
+pub fn sponsors(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<SponsorsResp> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start_after = start_after
+        .map(|address| deps.api.addr_validate(&address))
+        .transpose()?;
+    let start = start_after.as_ref().map(Bound::exclusive);
+
+    let sponsors = SPONSORS
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(_, sponsorship)| sponsorship))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let total = Coin {
+        denom: STATE.load(deps.storage)?.unit_price.denom,
+        amount: SPONSORED.may_load(deps.storage)?.unwrap_or_default(),
+    };
+
+    Ok(SponsorsResp { sponsors, total })
+}
+
+/// The listings of tickets still held by their sellers, none once the lottery is over
+pub fn listings(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<ListingsResp> {
+    if STATE.load(deps.storage)?.status != GameStatus::Activing {
+        return Ok(ListingsResp { listings: vec![] });
+    }
+
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.as_deref().map(Bound::exclusive);
+    let tickets = Cw721MetadataContract::default().tokens;
+
+    let mut listings = vec![];
+    for item in LISTINGS.range(deps.storage, start, None, Order::Ascending) {
+        let (token_id, listing) = item?;
+        let held = tickets.may_load(deps.storage, &token_id)?;
+        if held.is_some_and(|ticket| ticket.owner == listing.seller) {
+            listings.push(ListedTicket { token_id, listing });
+        }
+        if listings.len() == limit {
+            break;
+        }
+    }
+
+    Ok(ListingsResp { listings })
+}
+
+pub fn pause(deps: Deps, env: &Env) -> StdResult<PauseResp> {
+    let pause = PAUSE
+        .may_load(deps.storage)?
+        .filter(|pause| pause.is_active(env.block.time));
+
+    Ok(PauseResp { pause })
+}
+
+pub fn quote(deps: Deps, env: &Env, quantity: u32, at: Option<u64>) -> StdResult<QuoteResp> {
+    let at = at.map_or(env.block.time, Timestamp::from_seconds);
+    let price = STATE.load(deps.storage)?.quote(quantity, at);
+
+    Ok(QuoteResp { quantity, price })
+}
+
+pub fn referrer(deps: Deps, address: String) -> StdResult<ReferrerResp> {
+    let referrer = deps.api.addr_validate(&address)?;
+    let denom = STATE.load(deps.storage)?.unit_price.denom;
+
+    let mut resp = ReferrerResp {
+        referrals: 0,
+        earned: Coin::new(0, &denom),
+        paid: Coin::new(0, &denom),
+    };
+    for item in REFERRALS
+        .prefix(&referrer)
+        .range(deps.storage, None, None, Order::Ascending)
+    {
+        let (_, referral) = item?;
+        resp.referrals += 1;
+        resp.earned.amount += referral.reward.amount;
+        if referral.paid {
+            resp.paid.amount += referral.reward.amount;
+        }
+    }
+
+    Ok(resp)
+}
@@ -0,0 +1,244 @@
+//! Instant-win tickets: a ticket is settled from a later block, by the next purchase or
+//! `settle_ticket`, and the prize is paid at once. The outcome only depends on the seed chain
+//! as it stands at the end of the block the ticket is bought in, so it's fixed before anyone
+//! can settle it, and settling then reverting on a loss changes nothing.
+use std::{cmp::Reverse, collections::BTreeMap};
+
+use cosmwasm_std::{
+    attr, coin, ensure, BankMsg, DepsMut, Env, MessageInfo, Order, Response, StdResult, Storage,
+    Uint128,
+};
+use cw_storage_plus::Bound;
+
+use crate::{
+    auth::exec::validate_not_paused,
+    hash,
+    state::{
+        GameStatus, ScratchResult, State, WinnerInfo, WinnerSelection, IDX_2_ADDR, PLAYERS,
+        SCRATCH_BLOCK_SEEDS, SCRATCH_PENDING, SCRATCH_REMAINING, SCRATCH_RESULTS, STATE,
+    },
+    ContractError,
+};
+
+use super::exec::ticket_holder;
+
+/// Settle, in purchase order, the pending tickets bought before the current block.
+/// The lottery closes itself once the prize table is exhausted or every ticket is settled.
+pub fn settle_pending(
+    deps: DepsMut,
+    env: &Env,
+    state: &mut State,
+) -> Result<Response, ContractError> {
+    let Some(first) = SCRATCH_PENDING.may_load(deps.storage)? else {
+        return Ok(Response::new());
+    };
+    let WinnerSelection::Scratch { prizes } = state.selection.clone() else {
+        return Ok(Response::new());
+    };
+
+    let mut tickets = vec![];
+    for idx in first..=state.player_count {
+        let player = IDX_2_ADDR.load(deps.storage, idx)?;
+        let height = PLAYERS.load(deps.storage, &player)?.height;
+        if height >= env.block.height {
+            break;
+        }
+        tickets.push((idx, player, height));
+    }
+    if tickets.is_empty() {
+        return Ok(Response::new());
+    }
+
+    let next = first + tickets.len() as u64;
+    if next > state.player_count {
+        SCRATCH_PENDING.remove(deps.storage);
+    } else {
+        SCRATCH_PENDING.save(deps.storage, &next)?;
+    }
+
+    let settled_blocks = SCRATCH_BLOCK_SEEDS
+        .range(
+            deps.storage,
+            None,
+            Some(Bound::exclusive(env.block.height)),
+            Order::Ascending,
+        )
+        .collect::<StdResult<BTreeMap<_, _>>>()?;
+
+    let mut remaining = SCRATCH_REMAINING.load(deps.storage)?;
+    let mut attributes = vec![];
+    let mut msgs = vec![];
+    for (idx, player, height) in tickets {
+        // Nothing of the settlement goes into the seed, only the ticket and its block
+        let block_seed = &settled_blocks[&height];
+        let seed = hash::finalize(
+            block_seed,
+            &env.contract.address,
+            height,
+            &Some(idx.to_string()),
+        );
+        // Like a deck of scratch cards: the tickets are settled in order, so `idx - 1` are gone
+        let tickets_left = state.max_players - idx + 1;
+        let draw = hash::random_numbers(&seed, 1)?[0] % tickets_left;
+
+        let mut level = None;
+        let mut upper = 0;
+        for (i, count) in remaining.iter().enumerate() {
+            upper += u64::from(*count);
+            if draw < upper {
+                level = Some(i);
+                break;
+            }
+        }
+        let prize = level.map(|i| {
+            remaining[i] -= 1;
+            coin(prizes[i].amount.u128(), &state.unit_price.denom)
+        });
+
+        let ticket_id = idx.to_string();
+        SCRATCH_RESULTS.save(
+            deps.storage,
+            idx,
+            &ScratchResult {
+                ticket_id: ticket_id.clone(),
+                player: player.clone(),
+                prize: prize.clone(),
+            },
+        )?;
+
+        attributes.push(attr("settled_ticket", &ticket_id));
+        if let Some(prize) = prize {
+            // The prize goes to the current holder of the ticket
+            let holder = ticket_holder(deps.storage, &ticket_id, &player)?;
+            msgs.push(BankMsg::Send {
+                to_address: holder.to_string(),
+                amount: vec![prize.clone()],
+            });
+
+            attributes.push(attr("scratch_prize", prize.to_string()));
+            state.winner.push(WinnerInfo {
+                address: player,
+                prize: vec![prize],
+                ticket_id,
+                nft_prizes: vec![],
+            });
+        }
+
+        if idx == state.max_players {
+            state.status = GameStatus::Closed;
+        }
+    }
+    SCRATCH_REMAINING.save(deps.storage, &remaining)?;
+
+    // Every ticket of the blocks before the current one is settled now
+    for (height, _) in settled_blocks {
+        SCRATCH_BLOCK_SEEDS.remove(deps.storage, height);
+    }
+
+    if remaining.iter().all(|count| *count == 0) {
+        state.status = GameStatus::Closed;
+    }
+    if state.is_closed() {
+        attributes.push(attr("closed", "true"));
+    }
+
+    Ok(Response::new()
+        .add_messages(msgs)
+        .add_attributes(attributes))
+}
+
+/// The prizes the lottery may still pay: the whole table left while tickets are sold, then
+/// only the best prizes left for the tickets waiting to be settled
+pub fn reserved_prizes(storage: &dyn Storage, env: &Env, state: &State) -> StdResult<Uint128> {
+    let WinnerSelection::Scratch { prizes } = &state.selection else {
+        return Ok(Uint128::zero());
+    };
+    let remaining = SCRATCH_REMAINING.may_load(storage)?.unwrap_or_default();
+    let mut levels: Vec<_> = prizes
+        .iter()
+        .zip(remaining)
+        .map(|(prize, count)| (prize.amount, u64::from(count)))
+        .collect();
+
+    let selling = state.status == GameStatus::Activing
+        && env.block.time <= state.expiration
+        && state.player_count < state.max_players;
+    if selling {
+        return Ok(levels
+            .iter()
+            .map(|(amount, count)| amount * Uint128::from(*count))
+            .sum());
+    }
+
+    let mut pending = match SCRATCH_PENDING.may_load(storage)? {
+        Some(first) => state.player_count + 1 - first,
+        None => 0,
+    };
+    levels.sort_by_key(|(amount, _)| Reverse(*amount));
+
+    let mut reserved = Uint128::zero();
+    for (amount, count) in levels {
+        let won = count.min(pending);
+        reserved += amount * Uint128::from(won);
+        pending -= won;
+    }
+
+    Ok(reserved)
+}
+
+/// Keep the seed chain after each purchase, the last one of a block seeds all its tickets
+pub fn record_block_seed(storage: &mut dyn Storage, env: &Env, state: &State) -> StdResult<()> {
+    SCRATCH_BLOCK_SEEDS.save(storage, env.block.height, &state.seed)
+}
+
+/// The ticket bought when the prize table runs out is paid back
+pub fn sold_out(settled: Response, info: &MessageInfo) -> Response {
+    let attributes = vec![
+        attr("action", "buy_ticket"),
+        attr("sender", info.sender.as_str()),
+        attr("refunded", "true"),
+    ];
+
+    settled
+        .add_message(BankMsg::Send {
+            to_address: info.sender.to_string(),
+            amount: info.funds.clone(),
+        })
+        .add_attributes(attributes)
+}
+
+pub fn settle_ticket(
+    mut deps: DepsMut,
+    env: &Env,
+    info: &MessageInfo,
+) -> Result<Response, ContractError> {
+    let sender = &info.sender;
+    let mut state = STATE.load(deps.storage)?;
+
+    validate_not_paused(deps.storage, env)?;
+
+    let idx = SCRATCH_PENDING
+        .may_load(deps.storage)?
+        .ok_or(ContractError::NothingToSettle {})?;
+
+    // A ticket is never settled in the block it's bought in
+    let player = PLAYERS.load(deps.storage, &IDX_2_ADDR.load(deps.storage, idx)?)?;
+    ensure!(
+        env.block.height > player.height,
+        ContractError::SettleTooEarly {
+            height: player.height
+        }
+    );
+
+    let resp = settle_pending(deps.branch(), env, &mut state)?;
+
+    STATE.save(deps.storage, &state)?;
+
+    let attributes = vec![
+        attr("action", "settle_ticket"),
+        attr("sender", sender.as_str()),
+        attr("height", env.block.height.to_string()),
+    ];
+
+    Ok(resp.add_attributes(attributes))
+}
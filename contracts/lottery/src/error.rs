@@ -1,5 +1,6 @@
-use cosmwasm_std::{Addr, Coin, StdError, Uint128};
-use cw_utils::PaymentError;
+use common::error::CommonError;
+use cosmwasm_std::{Addr, Coin, Decimal, StdError, Uint128};
+use cw_utils::{Expiration, PaymentError};
 use thiserror::Error;
 
 use crate::{msg::QueryMsg, state::WinnerSelection};
@@ -39,15 +40,77 @@ pub enum ContractError {
     #[error("The contract balance is too small")]
     BalanceTooSmall { balance: Coin },
 
+    #[error("The ticket pot is locked until the draw, only {excess} can be withdrawn")]
+    PotLocked { excess: Coin },
+
+    #[error(
+        "The pot of a cancelled lottery is kept for the refunds, only {excess} can be withdrawn"
+    )]
+    RefundsPending { excess: Coin },
+
+    #[error("The scratch prizes still to win are locked, only {excess} can be withdrawn")]
+    ScratchPrizesLocked { excess: Coin },
+
+    #[error("The withdrawal exceeds the prize left: {prize}")]
+    PrizeExceeded { prize: Coin },
+
+    #[error("The withdrawal exceeds the balance outside the prize: {excess}")]
+    ExcessExceeded { excess: Coin },
+
     #[error("Not support denom: {denom}")]
     UnSupportedDenom { denom: String },
 
     #[error("Unsupport selection: {:?}", selection)]
     UnSupportedWinnerSelection { selection: WinnerSelection },
 
+    #[error("No ownership transfer is pending")]
+    NoPendingOwner {},
+
+    #[error("Ownership transfer expired")]
+    OwnershipTransferExpired {},
+
+    #[error("Ownership transfer expiry {expiry} is already reached")]
+    InvalidOwnershipExpiry { expiry: Expiration },
+
+    #[error("Ownership can't be renounced before the lottery is closed or cancelled")]
+    RenounceWhileActive {},
+
+    #[error("Paused: {reason}")]
+    Paused { reason: String },
+
+    #[error("A pause needs a reason")]
+    PauseReasonMissing {},
+
+    #[error("Pause expiry {expires_at} must be in the future")]
+    InvalidPauseExpiry { expires_at: u64 },
+
     #[error("{player} Only can buy a lottery once")]
     LotteryCanBuyOnce { player: Addr },
 
+    #[error("Invalid pricing schedule")]
+    InvalidPricing {},
+
+    #[error("Tickets can only be bought in bulk in a jackpot lottery")]
+    BulkBuyUnsupported {},
+
+    #[error("At least a ticket must be bought")]
+    NoTicketToBuy {},
+
+    #[error("A listing needs a price")]
+    InvalidListingPrice {},
+
+    #[error("Ticket {token_id} isn't listed")]
+    ListingNotFound { token_id: String },
+
+    #[error("Ticket {token_id} was moved since it was listed")]
+    ListingInvalid { token_id: String },
+
+    #[error("The listing price is {price}")]
+    ListingPriceMismatch { price: Coin },
+
+    #[error("Invalid resale royalty: {bps} basis points, a platform is required")]
+    InvalidResaleRoyalty { bps: u16 },
+
     #[error("The payment funds is not enough")]
     PaymentNotEnough { amount: Uint128 },
 
@@ -63,9 +126,91 @@ pub enum ContractError {
         created_at: u64,
     },
 
+    #[error("Commission rate: {rate} must not exceed 1")]
+    InvalidCommission { rate: Decimal },
+
+    #[error("{player} is not allowlisted")]
+    NotAllowlisted { player: Addr },
+
+    #[error("The allowlist can't be updated after tickets are sold")]
+    AllowlistLocked {},
+
+    #[error("Only an inline allowlist can be updated")]
+    AllowlistNotInline {},
+
+    #[error("The NFT gate must require at least one token")]
+    InvalidNftGate {},
+
+    #[error("{player} holds {held} unused gate token(s), {required} required")]
+    NotEnoughGateTokens {
+        player: Addr,
+        required: u32,
+        held: u32,
+    },
+
+    #[error("Referral share: {bps} basis points must not exceed 10000")]
+    InvalidReferralBps { bps: u16 },
+
+    #[error("Progressive jackpot needs a platform, a share of at most 10000 basis points and odds of at least 1")]
+    InvalidProgressiveJackpot {},
+
+    #[error("{player} can't refer themselves")]
+    SelfReferral { player: Addr },
+
+    #[error("{referrer} was referred by {player}, directly or not")]
+    CircularReferral { player: Addr, referrer: Addr },
+
+    #[error("{referrer} has no referral reward to claim")]
+    NoReferralRewards { referrer: Addr },
+
+    #[error("Can't pick {pick} numbers from 1 to {max_number}")]
+    InvalidNumberPick { pick: u8, max_number: u8 },
+
+    #[error("Prize tiers must each match a distinct count of 1 to {pick} numbers, and split 100 percent")]
+    InvalidPrizeTiers { pick: u8 },
+
+    #[error("{numbers:?} are not {pick} distinct numbers from 1 to {max_number}")]
+    InvalidNumbers {
+        numbers: Vec<u8>,
+        pick: u8,
+        max_number: u8,
+    },
+
+    #[error("Only a number-pick lottery takes picked numbers")]
+    NumbersNotAllowed {},
+
+    #[error(
+        "The prize table needs positive prize amounts and counts, {max_players} prizes at most"
+    )]
+    InvalidScratchTable { max_players: u64 },
+
+    #[error("The prize table must be funded with {expected}")]
+    ScratchNotFunded { expected: Coin },
+
+    #[error("A scratch lottery settles its tickets when bought, it can't be drawn or cancelled")]
+    ScratchNotDrawn {},
+
+    #[error("No scratch ticket is waiting to be settled")]
+    NothingToSettle {},
+
+    #[error("The pending ticket can be settled after height {height}")]
+    SettleTooEarly { height: u64 },
+
+    #[error("A scratch lottery can't raffle NFT prizes")]
+    NftPrizesUnsupported {},
+
+    #[error("A scratch lottery can't be sponsored")]
+    SponsorshipUnsupported {},
+
+    #[error("Sponsor name must be 1 to {max_len} characters")]
+    InvalidSponsorName { max_len: usize },
+
     #[error("error(0)")]
     PaymentError(#[from] PaymentError),
 
+    #[error("{0}")]
+    Common(#[from] CommonError),
+
     #[error(
         "Current height: {current_height} must greater than lottery start height: {lottery_height}"
     )]
@@ -77,6 +222,18 @@ pub enum ContractError {
     #[error("Lottery is already closed")]
     LotteryAlreadyClosed {},
 
+    #[error("Lottery is cancelled")]
+    LotteryCancelled {},
+
+    #[error("Lottery is not cancelled, nothing to refund")]
+    LotteryNotCancelled {},
+
+    #[error("{player} is already refunded")]
+    AlreadyRefunded { player: Addr },
+
+    #[error("{player} has no ticket to refund")]
+    NothingToRefund { player: Addr },
+
     #[error("The contract has nothing to claim")]
     NothingToClaim {},
 
@@ -114,6 +271,22 @@ pub enum ContractError {
 
     #[error("Unsupported query message: {:?}", msg)]
     QueryMsgNotSupported { msg: QueryMsg },
+
+    // for migration
+    #[error("Can't migrate from contract: {contract}")]
+    MigrateContractMismatch { contract: String },
+
+    #[error("Can't migrate from version {stored} to older version {current}")]
+    MigrateVersionDowngrade { stored: String, current: String },
+
+    #[error("Invalid contract version: {0}")]
+    InvalidContractVersion(String),
+}
+
+impl From<semver::Error> for ContractError {
+    fn from(err: semver::Error) -> Self {
+        ContractError::InvalidContractVersion(err.to_string())
+    }
 }
 
 impl From<cw20_base::ContractError> for ContractError {
@@ -1,8 +1,9 @@
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::Binary;
+use cosmwasm_std::{to_json_binary, Addr, Binary, Coin, CosmosMsg, StdResult, WasmMsg};
+use cw721::Cw721ReceiveMsg;
 use cw_utils::Expiration;
 
-use crate::{ContractError, Cw721ExecuteMsg, Extension};
+use crate::{state::WinnerInfo, ContractError, Cw721ExecuteMsg, Extension};
 
 #[allow(clippy::large_enum_variant)]
 #[cw_serde]
@@ -10,18 +11,72 @@ pub enum ExecuteMsg {
     BuyTicket {
         denom: String,
         memo: Option<String>,
+        /// Credited a share of the ticket price, if the lottery pays referrals
+        referrer: Option<String>,
+        /// The numbers picked for a number-pick lottery
+        numbers: Option<Vec<u8>>,
+    },
+    /// One ticket for each of the `recipients`, paid at the bulk price of a jackpot lottery
+    BuyTickets {
+        denom: String,
+        recipients: Vec<String>,
+        memo: Option<String>,
     },
     DrawLottery {
         // lottery: String,
     },
     ClaimLottery {},
+    CancelLottery {},
+    /// Get the payment of the tickets held by the sender back from a cancelled lottery
+    Refund {},
+    /// Pay out the referral rewards credited to the sender so far
+    ClaimReferralRewards {},
+    /// Add the sent funds to the pot without a ticket, refunded if the lottery is cancelled
+    Sponsor {
+        name: Option<String>,
+    },
+    /// Stop buying and drawing, by the owner or the platform. Refunds and claims keep working.
+    Pause {
+        reason: String,
+        /// Seconds since epoch, the pause lasts until `Unpause` without it
+        expires_at: Option<u64>,
+    },
+    Unpause {},
+    /// Settle the pending scratch tickets bought before this block, when no one buys the next one
+    SettleTicket {},
+    /// A cw721 token sent by the owner with `SendNft`, raffled along the pot
+    ReceiveNft(Cw721ReceiveMsg),
+    /// Put the sender's ticket on sale, escrowed by the lottery until sold, cancelled or drawn
+    ListTicket {
+        token_id: String,
+        price: Coin,
+    },
+    CancelListing {
+        token_id: String,
+    },
+    /// Pay exactly the listing price, less the platform royalty to the seller
+    BuyListedTicket {
+        token_id: String,
+    },
     WithdrawFunds {
         amount: u128,
         denom: String,
         recipient: Option<String>,
     },
-    Transfer {
-        recipient: String,
+    /// Offer the ownership to `new_owner`, who takes it over with `AcceptOwnership`
+    /// until `expiry`. A new proposal replaces the pending one.
+    ProposeOwner {
+        new_owner: String,
+        expiry: Option<Expiration>,
+    },
+    AcceptOwnership {},
+    CancelOwnershipTransfer {},
+    /// Give up the owner powers for good
+    RenounceOwnership {},
+    /// Edit an inline allowlist, until the first ticket is sold
+    UpdateAllowlist {
+        add: Vec<String>,
+        remove: Vec<String>,
     },
 
     // NFT msg
@@ -64,6 +119,78 @@ pub enum ExecuteMsg {
     },
 }
 
+/// Notifications sent to the platform which created the lottery
+#[cw_serde]
+pub enum LotteryHookMsg {
+    TicketBought {
+        player: String,
+        ticket_id: String,
+        player_count: u64,
+        pot: Coin,
+        referrer: Option<String>,
+        referral_reward: Option<Coin>,
+        /// The share of the payment for the ticket
+        paid: Option<Coin>,
+    },
+    Drawn {
+        winner: Vec<WinnerInfo>,
+        pot: Coin,
+        /// Paid out of the pot to the lottery's creator
+        commission: Option<Coin>,
+        /// The share of the prize tiers without winners, sent to the platform
+        rollover: Option<Coin>,
+        /// Sent to the platform, taken from the pot before the commission
+        jackpot_contribution: Option<Coin>,
+        /// The ticket which won the progressive jackpot in the secondary draw
+        jackpot_winner: Option<JackpotWinner>,
+    },
+    Claimed {
+        claimer: String,
+    },
+    Cancelled {},
+    Sponsored {
+        sponsor: String,
+        amount: Coin,
+        pot: Coin,
+    },
+    /// Referral rewards paid out, on claim or at draw
+    ReferralsPaid {
+        payouts: Vec<ReferralPayout>,
+    },
+}
+
+#[cw_serde]
+pub struct JackpotWinner {
+    /// The holder of the ticket at draw
+    pub address: String,
+    pub ticket_id: String,
+}
+
+#[cw_serde]
+pub struct ReferralPayout {
+    pub referrer: String,
+    pub reward: Coin,
+}
+
+impl LotteryHookMsg {
+    /// Wrap the hook as `{"lottery_hook": ...}`, to be handled by the platform's `ExecuteMsg`
+    pub fn into_cosmos_msg(self, platform: &Addr) -> StdResult<CosmosMsg> {
+        let msg = PlatformExecuteMsg::LotteryHook(self);
+        let msg = WasmMsg::Execute {
+            contract_addr: platform.to_string(),
+            msg: to_json_binary(&msg)?,
+            funds: vec![],
+        };
+
+        Ok(msg.into())
+    }
+}
+
+#[cw_serde]
+enum PlatformExecuteMsg {
+    LotteryHook(LotteryHookMsg),
+}
+
 impl TryFrom<ExecuteMsg> for Cw721ExecuteMsg {
     type Error = ContractError;
 
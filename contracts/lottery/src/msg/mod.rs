@@ -5,8 +5,9 @@ pub use exec::*;
 pub use query::*;
 
 use cosmwasm_schema::cw_serde;
+use cosmwasm_std::Decimal;
 
-use crate::state::WinnerSelection;
+use crate::state::{Pricing, ProgressiveJackpot, WinnerSelection};
 
 #[cw_serde]
 pub struct InstantiateMsg {
@@ -18,6 +19,45 @@ pub struct InstantiateMsg {
     pub expiration: u64,
     pub selection: WinnerSelection,
     pub max_players: u64,
+    /// The platform notified of the lottery's lifecycle, see `LotteryHookMsg`
+    pub platform: Option<String>,
+    /// Share of the pot paid to the lottery's creator when it's drawn
+    pub commission: Option<CommissionMsg>,
+    /// Only the members can buy tickets
+    pub allowlist: Option<AllowlistMsg>,
+    /// Only the holders of a cw721 collection can buy tickets
+    pub nft_gate: Option<NftGateMsg>,
+    /// Share of the ticket price credited to the referrer, in basis points
+    pub referral_bps: Option<u16>,
+    /// Contribution to the progressive jackpot of the platform
+    pub progressive_jackpot: Option<ProgressiveJackpot>,
+    /// Early bird, last call and bulk prices, the `unit_price` otherwise
+    pub pricing: Option<Pricing>,
+    /// Share of the ticket resales paid to the platform, in basis points
+    pub resale_royalty_bps: Option<u16>,
+}
+
+#[cw_serde]
+pub struct NftGateMsg {
+    pub collection: String,
+    /// Tokens the buyer must hold, at least 1
+    pub min_tokens: u32,
+    /// A token can only admit one ticket of the lottery
+    pub once_per_token: bool,
+}
+
+#[cw_serde]
+pub enum AllowlistMsg {
+    /// Updatable by the owner until the first ticket is sold
+    Addresses { addresses: Vec<String> },
+    /// Members of a cw4 group contract
+    Group { group: String },
+}
+
+#[cw_serde]
+pub struct CommissionMsg {
+    pub recipient: String,
+    pub rate: Decimal,
 }
 
 impl InstantiateMsg {
@@ -31,6 +71,7 @@ impl InstantiateMsg {
         expiration: u64,
         selection: WinnerSelection,
         max_players: u64,
+        platform: Option<String>,
     ) -> Self {
         Self {
             name: name.into(),
@@ -41,6 +82,58 @@ impl InstantiateMsg {
             selection,
             expiration,
             max_players,
+            platform,
+            commission: None,
+            allowlist: None,
+            nft_gate: None,
+            referral_bps: None,
+            progressive_jackpot: None,
+            pricing: None,
+            resale_royalty_bps: None,
         }
     }
+
+    pub fn with_commission(mut self, recipient: impl Into<String>, rate: Decimal) -> Self {
+        self.commission = Some(CommissionMsg {
+            recipient: recipient.into(),
+            rate,
+        });
+        self
+    }
+
+    pub fn with_allowlist(mut self, allowlist: AllowlistMsg) -> Self {
+        self.allowlist = Some(allowlist);
+        self
+    }
+
+    pub fn with_nft_gate(mut self, nft_gate: NftGateMsg) -> Self {
+        self.nft_gate = Some(nft_gate);
+        self
+    }
+
+    pub fn with_referral_bps(mut self, referral_bps: u16) -> Self {
+        self.referral_bps = Some(referral_bps);
+        self
+    }
+
+    pub fn with_progressive_jackpot(mut self, jackpot: ProgressiveJackpot) -> Self {
+        self.progressive_jackpot = Some(jackpot);
+        self
+    }
+
+    pub fn with_resale_royalty_bps(mut self, resale_royalty_bps: u16) -> Self {
+        self.resale_royalty_bps = Some(resale_royalty_bps);
+        self
+    }
+
+    pub fn with_pricing(mut self, pricing: Pricing) -> Self {
+        self.pricing = Some(pricing);
+        self
+    }
+}
+
+#[cw_serde]
+pub struct MigrateMsg {
+    /// Register the platform to notify, for lotteries created before `LotteryHookMsg`
+    pub platform: Option<String>,
 }
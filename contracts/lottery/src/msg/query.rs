@@ -2,7 +2,10 @@ use cosmwasm_schema::{cw_serde, QueryResponses};
 use cosmwasm_std::{Addr, Coin, Empty};
 
 use crate::{
-    state::{PlayerInfo, State, WinnerInfo},
+    state::{
+        Allowlist, DrawRecord, Listing, NftGate, PauseInfo, PendingOwner, PlayerInfo,
+        ScratchResult, SeedContribution, Sponsorship, State, WinnerInfo,
+    },
     Cw721QueryMsg,
 };
 
@@ -15,10 +18,76 @@ pub enum QueryMsg {
     Winner {},
     #[returns(OwnerResp)]
     Owner {},
+    /// The owner, `None` once renounced, and the proposed one
+    #[returns(OwnershipResp)]
+    Ownership {},
     #[returns(CurrentStateResp)]
     CurrentState {},
     #[returns(PlayInfoResp)]
     PlayInfo { address: String },
+    /// Players in purchase order, `start_after` is a player address
+    #[returns(PlayersResp)]
+    Players {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Players in purchase order, `start_after` is a ticket index
+    #[returns(PlayersResp)]
+    TicketsByIndex {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// `None` for a public lottery, `start_after` is a member of an inline allowlist
+    #[returns(AllowlistResp)]
+    Allowlist {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// `None` for an ungated lottery, `start_after` is a consumed token id
+    #[returns(NftGateResp)]
+    NftGate {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// The prizes left and the settled tickets of a scratch lottery, `start_after` is a ticket index
+    #[returns(ScratchResp)]
+    Scratch {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// The cw721 tokens deposited as prizes and not sent out yet, `start_after` is a (collection, token id)
+    #[returns(NftPrizesResp)]
+    NftPrizes {
+        start_after: Option<(String, String)>,
+        limit: Option<u32>,
+    },
+    /// Contributions to the pot without a ticket, `start_after` is a sponsor address
+    #[returns(SponsorsResp)]
+    Sponsors {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// The tickets on sale until the draw, `start_after` is a token id
+    #[returns(ListingsResp)]
+    Listings {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// The pause in effect, if any
+    #[returns(PauseResp)]
+    Pause {},
+    /// The price of `quantity` tickets bought at `at` seconds, now by default
+    #[returns(QuoteResp)]
+    Quote { quantity: u32, at: Option<u64> },
+    /// The referrals of an address and its rewards, earned and paid out
+    #[returns(ReferrerResp)]
+    Referrer { address: String },
+    /// The seed chain and draw inputs, `start_after` is a ticket index
+    #[returns(DrawProofResp)]
+    DrawProof {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
 
     /// Return the owner of the given token, error if token does not exist
     #[returns(cw721::OwnerOfResponse)]
@@ -162,3 +231,101 @@ pub struct CurrentStateResp {
 pub struct PlayInfoResp {
     pub info: Option<PlayerInfo>,
 }
+
+#[cw_serde]
+pub struct PlayersResp {
+    pub players: Vec<PlayerInfo>,
+    pub total: u64,
+}
+
+#[cw_serde]
+pub struct DrawProofResp {
+    pub initial_seed: String,
+    pub contributions: Vec<SeedContribution>,
+    pub total: u64,
+    /// `None` until the lottery is drawn
+    pub draw: Option<DrawRecord>,
+}
+
+#[cw_serde]
+pub struct AllowlistResp {
+    pub allowlist: Option<Allowlist>,
+    /// Members of an inline allowlist, the group lists its own
+    pub members: Vec<Addr>,
+}
+
+#[cw_serde]
+pub struct NftGateResp {
+    pub gate: Option<NftGate>,
+    /// Token ids which already admitted a ticket
+    pub used_tokens: Vec<UsedGateToken>,
+}
+
+#[cw_serde]
+pub struct UsedGateToken {
+    pub token_id: String,
+    pub player: Addr,
+}
+
+#[cw_serde]
+pub struct NftPrizesResp {
+    pub prizes: Vec<NftPrizeDeposit>,
+}
+
+#[cw_serde]
+pub struct NftPrizeDeposit {
+    pub collection: Addr,
+    pub token_id: String,
+    pub depositor: Addr,
+}
+
+#[cw_serde]
+pub struct SponsorsResp {
+    pub sponsors: Vec<Sponsorship>,
+    /// Sum of all the contributions, not only of this page
+    pub total: Coin,
+}
+
+#[cw_serde]
+pub struct ListingsResp {
+    pub listings: Vec<ListedTicket>,
+}
+
+#[cw_serde]
+pub struct ListedTicket {
+    pub token_id: String,
+    pub listing: Listing,
+}
+
+#[cw_serde]
+pub struct OwnershipResp {
+    pub owner: Option<Addr>,
+    pub pending_owner: Option<PendingOwner>,
+}
+
+#[cw_serde]
+pub struct PauseResp {
+    pub pause: Option<PauseInfo>,
+}
+
+#[cw_serde]
+pub struct QuoteResp {
+    pub quantity: u32,
+    pub price: Coin,
+}
+
+#[cw_serde]
+pub struct ReferrerResp {
+    pub referrals: u64,
+    pub earned: Coin,
+    pub paid: Coin,
+}
+
+#[cw_serde]
+pub struct ScratchResp {
+    /// Prizes left by level of the prize table
+    pub remaining: Vec<u32>,
+    /// The first ticket waiting to be settled, the later ones follow in order
+    pub pending: Option<u64>,
+    pub results: Vec<ScratchResult>,
+}
@@ -3,11 +3,19 @@ mod tests;
 
 use anyhow::Result as AnyResult;
 
-use cosmwasm_std::{Addr, Coin, StdResult};
-use cw_multi_test::{App, AppResponse, ContractWrapper, Executor};
+use cosmwasm_std::{
+    instantiate2_address, testing::MockApi, Addr, Api, CanonicalAddr, Coin, Empty, HexBinary,
+    RecoverPubkeyError, StdResult, Storage, VerificationError,
+};
+use cw_multi_test::{
+    AddressGenerator, AppBuilder, AppResponse, BankKeeper, ContractWrapper, DistributionKeeper,
+    Executor, FailingModule, GovFailingModule, IbcFailingModule, Router, StakeKeeper,
+    StargateFailing, WasmKeeper,
+};
+use cw_utils::Expiration;
 
 use crate::{
-    contract::{execute, instantiate, query, reply},
+    contract::{execute, instantiate, migrate, query, reply},
     msg::*,
     state::WinnerSelection,
 };
@@ -15,12 +23,121 @@ use crate::{
 pub const ARCH_DEMON: &str = "aconst";
 pub const ARCH_DECIMALS: u8 = 18;
 
+pub type App = cw_multi_test::App<BankKeeper, TestApi>;
+pub type AppRouter = Router<
+    BankKeeper,
+    FailingModule<Empty, Empty, Empty>,
+    WasmKeeper<Empty, Empty>,
+    StakeKeeper,
+    DistributionKeeper,
+    IbcFailingModule,
+    GovFailingModule,
+    StargateFailing,
+>;
+
+/// An `App` which places `Instantiate2` contracts at their predicted addresses
+pub fn mock_app<F>(init_fn: F) -> App
+where
+    F: FnOnce(&mut AppRouter, &dyn Api, &mut dyn Storage),
+{
+    AppBuilder::new()
+        .with_api(TestApi::default())
+        .with_wasm(WasmKeeper::new().with_address_generator(Instantiate2AddressGenerator))
+        .build(init_fn)
+}
+
+/// `MockApi` which can also humanize the 32 bytes addresses derived by `instantiate2_address`
+#[derive(Default)]
+pub struct TestApi(MockApi);
+
+impl Api for TestApi {
+    fn addr_validate(&self, human: &str) -> StdResult<Addr> {
+        self.0.addr_validate(human)
+    }
+
+    fn addr_canonicalize(&self, human: &str) -> StdResult<CanonicalAddr> {
+        self.0.addr_canonicalize(human)
+    }
+
+    fn addr_humanize(&self, canonical: &CanonicalAddr) -> StdResult<Addr> {
+        match canonical.len() {
+            32 => Ok(Addr::unchecked(format!(
+                "contract{}",
+                HexBinary::from(canonical.as_slice()).to_hex()
+            ))),
+            _ => self.0.addr_humanize(canonical),
+        }
+    }
+
+    fn secp256k1_verify(
+        &self,
+        message_hash: &[u8],
+        signature: &[u8],
+        public_key: &[u8],
+    ) -> Result<bool, VerificationError> {
+        self.0.secp256k1_verify(message_hash, signature, public_key)
+    }
+
+    fn secp256k1_recover_pubkey(
+        &self,
+        message_hash: &[u8],
+        signature: &[u8],
+        recovery_param: u8,
+    ) -> Result<Vec<u8>, RecoverPubkeyError> {
+        self.0
+            .secp256k1_recover_pubkey(message_hash, signature, recovery_param)
+    }
+
+    fn ed25519_verify(
+        &self,
+        message: &[u8],
+        signature: &[u8],
+        public_key: &[u8],
+    ) -> Result<bool, VerificationError> {
+        self.0.ed25519_verify(message, signature, public_key)
+    }
+
+    fn ed25519_batch_verify(
+        &self,
+        messages: &[&[u8]],
+        signatures: &[&[u8]],
+        public_keys: &[&[u8]],
+    ) -> Result<bool, VerificationError> {
+        self.0
+            .ed25519_batch_verify(messages, signatures, public_keys)
+    }
+
+    fn debug(&self, message: &str) {
+        self.0.debug(message)
+    }
+}
+
+/// Derive `Instantiate2` addresses the same way as the chain does
+struct Instantiate2AddressGenerator;
+
+impl AddressGenerator for Instantiate2AddressGenerator {
+    fn predictable_contract_address(
+        &self,
+        api: &dyn Api,
+        _storage: &mut dyn Storage,
+        _code_id: u64,
+        _instance_id: u64,
+        checksum: &[u8],
+        creator: &CanonicalAddr,
+        salt: &[u8],
+    ) -> AnyResult<Addr> {
+        Ok(api.addr_humanize(&instantiate2_address(checksum, creator, salt)?)?)
+    }
+}
+
 #[derive(Clone, Debug, Copy)]
 pub struct LotteryCodeId(u64);
 
 impl LotteryCodeId {
     pub fn store_code(app: &mut App) -> Self {
-        let contract = ContractWrapper::new(execute, instantiate, query).with_reply(reply);
+        let contract = ContractWrapper::new(execute, instantiate, query)
+            .with_reply(reply)
+            .with_migrate(migrate);
         let code_id = app.store_code(Box::new(contract));
         Self(code_id)
     }
@@ -97,6 +214,7 @@ impl LotteryContract {
             expiration,
             selection,
             max_players,
+            None,
         );
 
         app.instantiate_contract(
@@ -125,11 +243,174 @@ impl LotteryContract {
             &ExecuteMsg::BuyTicket {
                 denom: denom.into(),
                 memo,
+                referrer: None,
+                numbers: None,
             },
             funds,
         )
     }
 
+    #[track_caller]
+    pub fn buy_tickets(
+        &self,
+        app: &mut App,
+        sender: Addr,
+        denom: &str,
+        recipients: &[Addr],
+        funds: &[Coin],
+    ) -> AnyResult<AppResponse> {
+        app.execute_contract(
+            sender,
+            self.addr(),
+            &ExecuteMsg::BuyTickets {
+                denom: denom.into(),
+                recipients: recipients.iter().map(Addr::to_string).collect(),
+                memo: None,
+            },
+            funds,
+        )
+    }
+
+    #[track_caller]
+    pub fn buy_ticket_with_referrer(
+        &self,
+        app: &mut App,
+        sender: Addr,
+        denom: &str,
+        referrer: &Addr,
+        funds: &[Coin],
+    ) -> AnyResult<AppResponse> {
+        app.execute_contract(
+            sender,
+            self.addr(),
+            &ExecuteMsg::BuyTicket {
+                denom: denom.into(),
+                memo: None,
+                referrer: Some(referrer.to_string()),
+                numbers: None,
+            },
+            funds,
+        )
+    }
+
+    #[track_caller]
+    pub fn buy_ticket_with_numbers(
+        &self,
+        app: &mut App,
+        sender: Addr,
+        denom: &str,
+        numbers: Vec<u8>,
+        funds: &[Coin],
+    ) -> AnyResult<AppResponse> {
+        app.execute_contract(
+            sender,
+            self.addr(),
+            &ExecuteMsg::BuyTicket {
+                denom: denom.into(),
+                memo: None,
+                referrer: None,
+                numbers: Some(numbers),
+            },
+            funds,
+        )
+    }
+
+    #[track_caller]
+    pub fn sponsor(
+        &self,
+        app: &mut App,
+        sender: Addr,
+        name: Option<&str>,
+        funds: &[Coin],
+    ) -> AnyResult<AppResponse> {
+        app.execute_contract(
+            sender,
+            self.addr(),
+            &ExecuteMsg::Sponsor {
+                name: name.map(Into::into),
+            },
+            funds,
+        )
+    }
+
+    #[track_caller]
+    pub fn propose_owner(
+        &self,
+        app: &mut App,
+        sender: Addr,
+        new_owner: &str,
+        expiry: Option<Expiration>,
+    ) -> AnyResult<AppResponse> {
+        app.execute_contract(
+            sender,
+            self.addr(),
+            &ExecuteMsg::ProposeOwner {
+                new_owner: new_owner.into(),
+                expiry,
+            },
+            &[],
+        )
+    }
+
+    #[track_caller]
+    pub fn accept_ownership(&self, app: &mut App, sender: Addr) -> AnyResult<AppResponse> {
+        app.execute_contract(sender, self.addr(), &ExecuteMsg::AcceptOwnership {}, &[])
+    }
+
+    #[track_caller]
+    pub fn cancel_ownership_transfer(&self, app: &mut App, sender: Addr) -> AnyResult<AppResponse> {
+        app.execute_contract(
+            sender,
+            self.addr(),
+            &ExecuteMsg::CancelOwnershipTransfer {},
+            &[],
+        )
+    }
+
+    #[track_caller]
+    pub fn renounce_ownership(&self, app: &mut App, sender: Addr) -> AnyResult<AppResponse> {
+        app.execute_contract(sender, self.addr(), &ExecuteMsg::RenounceOwnership {}, &[])
+    }
+
+    #[track_caller]
+    pub fn pause(
+        &self,
+        app: &mut App,
+        sender: Addr,
+        reason: &str,
+        expires_at: Option<u64>,
+    ) -> AnyResult<AppResponse> {
+        app.execute_contract(
+            sender,
+            self.addr(),
+            &ExecuteMsg::Pause {
+                reason: reason.into(),
+                expires_at,
+            },
+            &[],
+        )
+    }
+
+    #[track_caller]
+    pub fn unpause(&self, app: &mut App, sender: Addr) -> AnyResult<AppResponse> {
+        app.execute_contract(sender, self.addr(), &ExecuteMsg::Unpause {}, &[])
+    }
+
+    #[track_caller]
+    pub fn settle_ticket(&self, app: &mut App, sender: Addr) -> AnyResult<AppResponse> {
+        app.execute_contract(sender, self.addr(), &ExecuteMsg::SettleTicket {}, &[])
+    }
+
+    #[track_caller]
+    pub fn claim_referral_rewards(&self, app: &mut App, sender: Addr) -> AnyResult<AppResponse> {
+        app.execute_contract(
+            sender,
+            self.addr(),
+            &ExecuteMsg::ClaimReferralRewards {},
+            &[],
+        )
+    }
+
     #[track_caller]
     pub fn draw_lottery(&self, app: &mut App, sender: Addr) -> AnyResult<AppResponse> {
         app.execute_contract(sender, self.addr(), &ExecuteMsg::DrawLottery {}, &[])
@@ -140,6 +421,70 @@ impl LotteryContract {
         app.execute_contract(sender, self.addr(), &ExecuteMsg::ClaimLottery {}, &[])
     }
 
+    #[track_caller]
+    pub fn cancel_lottery(&self, app: &mut App, sender: Addr) -> AnyResult<AppResponse> {
+        app.execute_contract(sender, self.addr(), &ExecuteMsg::CancelLottery {}, &[])
+    }
+
+    #[track_caller]
+    pub fn refund(&self, app: &mut App, sender: Addr) -> AnyResult<AppResponse> {
+        app.execute_contract(sender, self.addr(), &ExecuteMsg::Refund {}, &[])
+    }
+
+    #[track_caller]
+    pub fn list_ticket(
+        &self,
+        app: &mut App,
+        sender: Addr,
+        token_id: &str,
+        price: Coin,
+    ) -> AnyResult<AppResponse> {
+        app.execute_contract(
+            sender,
+            self.addr(),
+            &ExecuteMsg::ListTicket {
+                token_id: token_id.into(),
+                price,
+            },
+            &[],
+        )
+    }
+
+    #[track_caller]
+    pub fn cancel_listing(
+        &self,
+        app: &mut App,
+        sender: Addr,
+        token_id: &str,
+    ) -> AnyResult<AppResponse> {
+        app.execute_contract(
+            sender,
+            self.addr(),
+            &ExecuteMsg::CancelListing {
+                token_id: token_id.into(),
+            },
+            &[],
+        )
+    }
+
+    #[track_caller]
+    pub fn buy_listed_ticket(
+        &self,
+        app: &mut App,
+        sender: Addr,
+        token_id: &str,
+        funds: &[Coin],
+    ) -> AnyResult<AppResponse> {
+        app.execute_contract(
+            sender,
+            self.addr(),
+            &ExecuteMsg::BuyListedTicket {
+                token_id: token_id.into(),
+            },
+            funds,
+        )
+    }
+
     #[track_caller]
     pub fn withdraw(
         &self,
@@ -180,6 +525,116 @@ impl LotteryContract {
         )
     }
 
+    #[track_caller]
+    pub fn burn_ticket(
+        &self,
+        app: &mut App,
+        sender: Addr,
+        token_id: String,
+    ) -> AnyResult<AppResponse> {
+        app.execute_contract(sender, self.addr(), &ExecuteMsg::Burn { token_id }, &[])
+    }
+
+    #[track_caller]
+    pub fn update_allowlist(
+        &self,
+        app: &mut App,
+        sender: Addr,
+        add: Vec<String>,
+        remove: Vec<String>,
+    ) -> AnyResult<AppResponse> {
+        app.execute_contract(
+            sender,
+            self.addr(),
+            &ExecuteMsg::UpdateAllowlist { add, remove },
+            &[],
+        )
+    }
+
+    pub fn allowlist(&self, app: &App) -> StdResult<AllowlistResp> {
+        app.wrap().query_wasm_smart(
+            self.addr(),
+            &QueryMsg::Allowlist {
+                start_after: None,
+                limit: None,
+            },
+        )
+    }
+
+    pub fn nft_gate(&self, app: &App) -> StdResult<NftGateResp> {
+        app.wrap().query_wasm_smart(
+            self.addr(),
+            &QueryMsg::NftGate {
+                start_after: None,
+                limit: None,
+            },
+        )
+    }
+
+    pub fn scratch(&self, app: &App) -> StdResult<ScratchResp> {
+        app.wrap().query_wasm_smart(
+            self.addr(),
+            &QueryMsg::Scratch {
+                start_after: None,
+                limit: None,
+            },
+        )
+    }
+
+    pub fn nft_prizes(&self, app: &App) -> StdResult<NftPrizesResp> {
+        app.wrap().query_wasm_smart(
+            self.addr(),
+            &QueryMsg::NftPrizes {
+                start_after: None,
+                limit: None,
+            },
+        )
+    }
+
+    pub fn listings(&self, app: &App) -> StdResult<ListingsResp> {
+        app.wrap().query_wasm_smart(
+            self.addr(),
+            &QueryMsg::Listings {
+                start_after: None,
+                limit: None,
+            },
+        )
+    }
+
+    pub fn quote(&self, app: &App, quantity: u32, at: Option<u64>) -> StdResult<QuoteResp> {
+        app.wrap()
+            .query_wasm_smart(self.addr(), &QueryMsg::Quote { quantity, at })
+    }
+
+    pub fn ownership(&self, app: &App) -> StdResult<OwnershipResp> {
+        app.wrap()
+            .query_wasm_smart(self.addr(), &QueryMsg::Ownership {})
+    }
+
+    pub fn pause_info(&self, app: &App) -> StdResult<PauseResp> {
+        app.wrap()
+            .query_wasm_smart(self.addr(), &QueryMsg::Pause {})
+    }
+
+    pub fn sponsors(&self, app: &App) -> StdResult<SponsorsResp> {
+        app.wrap().query_wasm_smart(
+            self.addr(),
+            &QueryMsg::Sponsors {
+                start_after: None,
+                limit: None,
+            },
+        )
+    }
+
+    pub fn referrer(&self, app: &App, address: &Addr) -> StdResult<ReferrerResp> {
+        app.wrap().query_wasm_smart(
+            self.addr(),
+            &QueryMsg::Referrer {
+                address: address.to_string(),
+            },
+        )
+    }
+
     pub fn winner(&self, app: &App) -> StdResult<WinnerResp> {
         app.wrap()
             .query_wasm_smart(self.addr(), &QueryMsg::Winner {})
@@ -199,6 +654,38 @@ impl LotteryContract {
             .query_wasm_smart(self.addr(), &QueryMsg::CurrentState {})
     }
 
+    pub fn players(
+        &self,
+        app: &App,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<PlayersResp> {
+        app.wrap()
+            .query_wasm_smart(self.addr(), &QueryMsg::Players { start_after, limit })
+    }
+
+    pub fn tickets_by_index(
+        &self,
+        app: &App,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> StdResult<PlayersResp> {
+        app.wrap().query_wasm_smart(
+            self.addr(),
+            &QueryMsg::TicketsByIndex { start_after, limit },
+        )
+    }
+
+    pub fn draw_proof(
+        &self,
+        app: &App,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> StdResult<DrawProofResp> {
+        app.wrap()
+            .query_wasm_smart(self.addr(), &QueryMsg::DrawProof { start_after, limit })
+    }
+
     pub fn player_info(&self, app: &App, address: &str) -> StdResult<PlayInfoResp> {
         app.wrap().query_wasm_smart(
             self.addr(),
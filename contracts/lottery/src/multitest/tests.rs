@@ -3,12 +3,20 @@ mod test {
     use std::marker::PhantomData;
 
     use chrono::Utc;
-    use cosmwasm_std::{coin, coins, Empty};
-    use cw_multi_test::App;
+    use cosmwasm_std::{coin, coins, Addr, Coin, Decimal, Empty, Uint128};
+    use cw_multi_test::{next_block, ContractWrapper, Executor};
+    use cw_utils::Expiration;
 
     use crate::{
-        multitest::{alice, bob, owner, parent, LotteryCodeId, LotteryContract, ARCH_DEMON},
-        state::{GameStatus, WinnerSelection},
+        hash,
+        msg::{AllowlistMsg, InstantiateMsg, NftGateMsg},
+        multitest::{
+            alice, bob, mock_app, owner, parent, App, LotteryCodeId, LotteryContract, ARCH_DEMON,
+        },
+        state::{
+            Allowlist, GameStatus, PricePhase, Pricing, PrizeTier, QuantityTier, ScratchPrize,
+            WinnerSelection,
+        },
         ContractError,
     };
 
@@ -18,7 +26,7 @@ mod test {
 
     #[test]
     fn instantiate_should_works() {
-        let mut app = App::default();
+        let mut app = mock_app(|_, _, _| {});
         let code_id = LotteryCodeId::store_code(&mut app);
         let name = "LOTTERY";
         let symbol = "LOTTER";
@@ -69,7 +77,7 @@ mod test {
 
     #[test]
     fn lottery_full_flows_should_works() {
-        let mut app = App::new(|router, _api, storage| {
+        let mut app = mock_app(|router, _api, storage| {
             router
                 .bank
                 .init_balance(storage, &alice(), coins(300, ARCH_DEMON))
@@ -156,13 +164,13 @@ mod test {
 
         // transfer ticket
         contract
-            .transfer_ticket(&mut app, alice(), parent().to_string(), "1".to_string())
+            .transfer_ticket(&mut app, bob(), parent().to_string(), "2".to_string())
             .unwrap();
 
         // draw lottery
         contract.draw_lottery(&mut app, owner()).unwrap();
 
-        let claim_err = contract.claim_lottery(&mut app, alice()).unwrap_err();
+        let claim_err = contract.claim_lottery(&mut app, bob()).unwrap_err();
         assert_eq!(
             ContractError::Unauthorized {},
             claim_err.downcast().unwrap()
@@ -170,15 +178,16 @@ mod test {
 
         contract.claim_lottery(&mut app, parent()).unwrap();
 
-        let owner = contract.owner(&app).unwrap();
-        assert_eq!(owner.owner, parent());
+        // the winner claims the prize, not the lottery
+        let lottery_owner = contract.owner(&app).unwrap();
+        assert_eq!(lottery_owner.owner, owner());
 
         let state = contract.query_state(&app).unwrap();
         assert_eq!(state.state.player_count, 2);
         assert_eq!(state.state.winner.len(), 1);
 
         let winner = state.state.winner.first().unwrap();
-        assert_eq!(winner.address, alice());
+        assert_eq!(winner.address, bob());
         assert_eq!(winner.prize, coins(200, ARCH_DEMON));
 
         // withdraw funds
@@ -204,7 +213,7 @@ mod test {
 
     #[test]
     fn draw_lottery_should_fail() {
-        let mut app = App::new(|router, _api, storage| {
+        let mut app = mock_app(|router, _api, storage| {
             router
                 .bank
                 .init_balance(storage, &alice(), coins(300, ARCH_DEMON))
@@ -255,4 +264,2263 @@ mod test {
         let err = contract.draw_lottery(&mut app, alice()).unwrap_err();
         assert_eq!(ContractError::Unauthorized {}, err.downcast().unwrap())
     }
+
+    #[test]
+    fn cancel_and_refund_should_works() {
+        let mut app = mock_app(|router, _api, storage| {
+            router
+                .bank
+                .init_balance(storage, &alice(), coins(300, ARCH_DEMON))
+                .unwrap();
+            router
+                .bank
+                .init_balance(storage, &bob(), coins(500, ARCH_DEMON))
+                .unwrap();
+        });
+
+        let code_id = LotteryCodeId::store_code(&mut app);
+        let expiration = Utc::now().timestamp() as u64;
+        let contract = code_id
+            .instantiate(
+                &mut app,
+                owner(),
+                "LOTTERY",
+                "LOTTER",
+                100,
+                ARCH_DEMON,
+                "hour",
+                expiration,
+                WinnerSelection::Jackpot {},
+                3,
+                "Lottery label",
+            )
+            .unwrap();
+
+        contract
+            .buy_ticket(&mut app, alice(), ARCH_DEMON, None, &coins(100, ARCH_DEMON))
+            .unwrap();
+
+        let err = contract.refund(&mut app, alice()).unwrap_err();
+        assert_eq!(
+            ContractError::LotteryNotCancelled {},
+            err.downcast().unwrap()
+        );
+
+        let err = contract.cancel_lottery(&mut app, alice()).unwrap_err();
+        assert_eq!(ContractError::Unauthorized {}, err.downcast().unwrap());
+
+        contract.cancel_lottery(&mut app, owner()).unwrap();
+
+        let state = contract.query_state(&app).unwrap().state;
+        assert_eq!(state.status, GameStatus::Cancelled);
+
+        let err = contract
+            .buy_ticket(&mut app, bob(), ARCH_DEMON, None, &coins(100, ARCH_DEMON))
+            .unwrap_err();
+        assert_eq!(ContractError::LotteryCancelled {}, err.downcast().unwrap());
+
+        let err = contract.draw_lottery(&mut app, owner()).unwrap_err();
+        assert_eq!(ContractError::LotteryCancelled {}, err.downcast().unwrap());
+
+        let err = contract.refund(&mut app, bob()).unwrap_err();
+        assert_eq!(
+            ContractError::NothingToRefund { player: bob() },
+            err.downcast().unwrap()
+        );
+
+        contract.refund(&mut app, alice()).unwrap();
+        let alice_balances = LotteryContract::query_balances(&app, alice()).unwrap();
+        assert_eq!(alice_balances, coins(300, ARCH_DEMON));
+
+        let err = contract.refund(&mut app, alice()).unwrap_err();
+        assert_eq!(
+            ContractError::AlreadyRefunded { player: alice() },
+            err.downcast().unwrap()
+        );
+    }
+
+    #[test]
+    fn players_query_should_works() {
+        let mut app = mock_app(|router, _api, storage| {
+            for player in [alice(), bob(), parent()] {
+                router
+                    .bank
+                    .init_balance(storage, &player, coins(100, ARCH_DEMON))
+                    .unwrap();
+            }
+        });
+
+        let code_id = LotteryCodeId::store_code(&mut app);
+        let expiration = Utc::now().timestamp() as u64;
+        let contract = code_id
+            .instantiate(
+                &mut app,
+                owner(),
+                "LOTTERY",
+                "LOTTER",
+                100,
+                ARCH_DEMON,
+                "hour",
+                expiration,
+                WinnerSelection::Jackpot {},
+                3,
+                "Lottery label",
+            )
+            .unwrap();
+
+        for (player, memo) in [(bob(), "first"), (alice(), "second"), (parent(), "third")] {
+            contract
+                .buy_ticket(
+                    &mut app,
+                    player,
+                    ARCH_DEMON,
+                    Some(memo.to_string()),
+                    &coins(100, ARCH_DEMON),
+                )
+                .unwrap();
+        }
+
+        let resp = contract.players(&app, None, Some(2)).unwrap();
+        assert_eq!(resp.total, 3);
+        assert_eq!(
+            resp.players
+                .iter()
+                .map(|p| p.player_addr.clone())
+                .collect::<Vec<_>>(),
+            vec![bob(), alice()]
+        );
+        assert_eq!(resp.players[1].memo, Some("second".to_string()));
+
+        let resp = contract
+            .players(&app, Some(alice().to_string()), None)
+            .unwrap();
+        assert_eq!(resp.players.len(), 1);
+        assert_eq!(resp.players[0].player_addr, parent());
+
+        let resp = contract.tickets_by_index(&app, Some(1), Some(1)).unwrap();
+        assert_eq!(resp.total, 3);
+        assert_eq!(resp.players.len(), 1);
+        assert_eq!(resp.players[0].ticket_id, "2");
+        assert_eq!(resp.players[0].player_addr, alice());
+    }
+
+    #[test]
+    fn draw_proof_should_works() {
+        let mut app = mock_app(|router, _api, storage| {
+            for player in [alice(), bob(), parent()] {
+                router
+                    .bank
+                    .init_balance(storage, &player, coins(100, ARCH_DEMON))
+                    .unwrap();
+            }
+        });
+
+        let code_id = LotteryCodeId::store_code(&mut app);
+        let expiration = Utc::now().timestamp() as u64;
+        let contract = code_id
+            .instantiate(
+                &mut app,
+                owner(),
+                "LOTTERY",
+                "LOTTER",
+                100,
+                ARCH_DEMON,
+                "hour",
+                expiration,
+                WinnerSelection::Jackpot {},
+                3,
+                "Lottery label",
+            )
+            .unwrap();
+
+        let memos = [Some("first".to_string()), None, Some("third".to_string())];
+        for (player, memo) in [alice(), bob(), parent()].into_iter().zip(memos.clone()) {
+            contract
+                .buy_ticket(&mut app, player, ARCH_DEMON, memo, &coins(100, ARCH_DEMON))
+                .unwrap();
+        }
+
+        let proof = contract.draw_proof(&app, None, None).unwrap();
+        assert_eq!(proof.total, 3);
+        assert_eq!(proof.contributions.len(), 3);
+        assert_eq!(proof.contributions[1].player, bob());
+        assert_eq!(proof.contributions[1].memo_digest, None);
+        assert!(proof.draw.is_none());
+
+        contract.draw_lottery(&mut app, owner()).unwrap();
+
+        // Replay the seed chain from the proof and the players' memos
+        let proof = contract.draw_proof(&app, None, None).unwrap();
+        let mut seed = proof.initial_seed;
+        for (contribution, memo) in proof.contributions.iter().zip(memos) {
+            assert_eq!(contribution.memo_digest, hash::memo_digest(&memo));
+            seed = hash::update(
+                &seed,
+                &contribution.player,
+                contribution.counter,
+                contribution.height,
+                &memo,
+            );
+        }
+
+        let draw = proof.draw.unwrap();
+        let seed = hash::finalize(&seed, &draw.sender, draw.height, &draw.lucky_phrase);
+        assert_eq!(seed, draw.seed);
+
+        let random_numbers = hash::random_numbers(&seed, 1).unwrap();
+        assert_eq!(random_numbers, draw.random_numbers);
+        assert_eq!(
+            hash::winner_indices(&random_numbers, proof.total),
+            draw.winner_indices
+        );
+
+        let winner = contract.winner(&app).unwrap().winner;
+        assert_eq!(winner[0].ticket_id, draw.winner_indices[0].to_string());
+
+        let resp = contract.draw_proof(&app, Some(2), None).unwrap();
+        assert_eq!(resp.contributions.len(), 1);
+        assert_eq!(resp.contributions[0].player, parent());
+    }
+
+    #[test]
+    fn draw_with_commission_should_works() {
+        let mut app = mock_app(|router, _api, storage| {
+            for player in [alice(), bob()] {
+                router
+                    .bank
+                    .init_balance(storage, &player, coins(100, ARCH_DEMON))
+                    .unwrap();
+            }
+        });
+
+        let code_id = LotteryCodeId::store_code(&mut app);
+        let expiration = Utc::now().timestamp() as u64;
+        let new_msg = |rate| {
+            InstantiateMsg::new(
+                "LOTTERY",
+                "LOTTER",
+                100,
+                ARCH_DEMON,
+                "hour",
+                expiration,
+                WinnerSelection::Jackpot {},
+                2,
+                None,
+            )
+            .with_commission(parent(), rate)
+        };
+
+        let err = app
+            .instantiate_contract(
+                code_id.into(),
+                owner(),
+                &new_msg(Decimal::percent(101)),
+                &[],
+                "Lottery label",
+                None,
+            )
+            .unwrap_err();
+        assert_eq!(
+            ContractError::InvalidCommission {
+                rate: Decimal::percent(101)
+            },
+            err.downcast().unwrap()
+        );
+
+        let contract: LotteryContract = app
+            .instantiate_contract(
+                code_id.into(),
+                owner(),
+                &new_msg(Decimal::percent(10)),
+                &[],
+                "Lottery label",
+                None,
+            )
+            .unwrap()
+            .into();
+
+        for player in [alice(), bob()] {
+            contract
+                .buy_ticket(&mut app, player, ARCH_DEMON, None, &coins(100, ARCH_DEMON))
+                .unwrap();
+        }
+
+        contract.draw_lottery(&mut app, owner()).unwrap();
+
+        // the creator is paid 10% of the pot, the winner gets the rest
+        let creator_balances = LotteryContract::query_balances(&app, parent()).unwrap();
+        assert_eq!(creator_balances, coins(20, ARCH_DEMON));
+
+        let winner = contract.winner(&app).unwrap().winner;
+        assert_eq!(winner[0].prize, coins(180, ARCH_DEMON));
+
+        let lottery_balances = LotteryContract::query_balances(&app, contract.addr()).unwrap();
+        assert_eq!(lottery_balances, coins(180, ARCH_DEMON));
+    }
+
+    #[test]
+    fn referral_should_works() {
+        let mut app = mock_app(|router, _api, storage| {
+            for player in [alice(), bob()] {
+                router
+                    .bank
+                    .init_balance(storage, &player, coins(200, ARCH_DEMON))
+                    .unwrap();
+            }
+        });
+
+        let code_id = LotteryCodeId::store_code(&mut app);
+        let expiration = Utc::now().timestamp() as u64;
+        let new_msg = |bps| {
+            InstantiateMsg::new(
+                "LOTTERY",
+                "LOTTER",
+                100,
+                ARCH_DEMON,
+                "hour",
+                expiration,
+                WinnerSelection::Jackpot {},
+                2,
+                None,
+            )
+            .with_referral_bps(bps)
+        };
+        let instantiate = |app: &mut App, bps| {
+            app.instantiate_contract(
+                code_id.into(),
+                owner(),
+                &new_msg(bps),
+                &[],
+                "Lottery label",
+                None,
+            )
+        };
+
+        let err = instantiate(&mut app, 10_001).unwrap_err();
+        assert_eq!(
+            ContractError::InvalidReferralBps { bps: 10_001 },
+            err.downcast().unwrap()
+        );
+
+        let contract: LotteryContract = instantiate(&mut app, 1_000).unwrap().into();
+        let ticket = coins(100, ARCH_DEMON);
+
+        let err = contract
+            .buy_ticket_with_referrer(&mut app, bob(), ARCH_DEMON, &bob(), &ticket)
+            .unwrap_err();
+        assert_eq!(
+            ContractError::SelfReferral { player: bob() },
+            err.downcast().unwrap()
+        );
+
+        contract
+            .buy_ticket_with_referrer(&mut app, alice(), ARCH_DEMON, &bob(), &ticket)
+            .unwrap();
+
+        // bob referred alice, so alice can't refer bob
+        let err = contract
+            .buy_ticket_with_referrer(&mut app, bob(), ARCH_DEMON, &alice(), &ticket)
+            .unwrap_err();
+        assert_eq!(
+            ContractError::CircularReferral {
+                player: bob(),
+                referrer: alice()
+            },
+            err.downcast().unwrap()
+        );
+
+        contract
+            .buy_ticket_with_referrer(&mut app, bob(), ARCH_DEMON, &parent(), &ticket)
+            .unwrap();
+
+        // the reward is claimable before the draw
+        contract.claim_referral_rewards(&mut app, bob()).unwrap();
+        let bob_balances = LotteryContract::query_balances(&app, bob()).unwrap();
+        assert_eq!(bob_balances, coins(110, ARCH_DEMON));
+
+        let err = contract
+            .claim_referral_rewards(&mut app, bob())
+            .unwrap_err();
+        assert_eq!(
+            ContractError::NoReferralRewards { referrer: bob() },
+            err.downcast().unwrap()
+        );
+
+        let referrer = contract.referrer(&app, &bob()).unwrap();
+        assert_eq!(referrer.referrals, 1);
+        assert_eq!(referrer.earned, coin(10, ARCH_DEMON));
+        assert_eq!(referrer.paid, coin(10, ARCH_DEMON));
+
+        // the unclaimed rewards are paid at draw, out of the pot
+        contract.draw_lottery(&mut app, owner()).unwrap();
+
+        let parent_balances = LotteryContract::query_balances(&app, parent()).unwrap();
+        assert_eq!(parent_balances, coins(10, ARCH_DEMON));
+
+        let winner = contract.winner(&app).unwrap().winner;
+        assert_eq!(winner[0].prize, coins(180, ARCH_DEMON));
+
+        let referrer = contract.referrer(&app, &parent()).unwrap();
+        assert_eq!(referrer.paid, coin(10, ARCH_DEMON));
+
+        // a paid reward is deducted from the refund of a cancelled lottery
+        let contract: LotteryContract = instantiate(&mut app, 1_000).unwrap().into();
+        contract
+            .buy_ticket_with_referrer(&mut app, alice(), ARCH_DEMON, &bob(), &ticket)
+            .unwrap();
+        contract.claim_referral_rewards(&mut app, bob()).unwrap();
+
+        contract.cancel_lottery(&mut app, owner()).unwrap();
+
+        let err = contract
+            .claim_referral_rewards(&mut app, bob())
+            .unwrap_err();
+        assert_eq!(ContractError::LotteryCancelled {}, err.downcast().unwrap());
+
+        contract.refund(&mut app, alice()).unwrap();
+        let alice_balances = LotteryContract::query_balances(&app, alice()).unwrap();
+        assert_eq!(alice_balances, coins(90, ARCH_DEMON));
+    }
+
+    #[test]
+    fn number_pick_should_works() {
+        let mut app = mock_app(|router, _api, storage| {
+            for (player, amount) in [(alice(), 200), (bob(), 100)] {
+                router
+                    .bank
+                    .init_balance(storage, &player, coins(amount, ARCH_DEMON))
+                    .unwrap();
+            }
+        });
+
+        let code_id = LotteryCodeId::store_code(&mut app);
+        let expiration = Utc::now().timestamp() as u64;
+        let tier = |matches, pct| PrizeTier { matches, pct };
+        let instantiate = |app: &mut App, pick, max_number, tiers| {
+            let msg = InstantiateMsg::new(
+                "LOTTERY",
+                "LOTTER",
+                100,
+                ARCH_DEMON,
+                "hour",
+                expiration,
+                WinnerSelection::NumberPick {
+                    pick,
+                    max_number,
+                    tiers,
+                },
+                2,
+                None,
+            );
+            app.instantiate_contract(code_id.into(), owner(), &msg, &[], "Lottery label", None)
+        };
+
+        let err = instantiate(&mut app, 5, 4, vec![tier(5, 100)]).unwrap_err();
+        assert_eq!(
+            ContractError::InvalidNumberPick {
+                pick: 5,
+                max_number: 4
+            },
+            err.downcast().unwrap()
+        );
+
+        let err = instantiate(&mut app, 2, 4, vec![tier(2, 70), tier(1, 20)]).unwrap_err();
+        assert_eq!(
+            ContractError::InvalidPrizeTiers { pick: 2 },
+            err.downcast().unwrap()
+        );
+
+        let contract: LotteryContract = instantiate(&mut app, 2, 4, vec![tier(2, 70), tier(1, 30)])
+            .unwrap()
+            .into();
+        let ticket = coins(100, ARCH_DEMON);
+
+        // a number-pick ticket needs distinct numbers in range
+        let err = contract
+            .buy_ticket(&mut app, alice(), ARCH_DEMON, None, &ticket)
+            .unwrap_err();
+        assert_eq!(
+            ContractError::InvalidNumbers {
+                numbers: vec![],
+                pick: 2,
+                max_number: 4
+            },
+            err.downcast().unwrap()
+        );
+
+        for numbers in [vec![1, 1], vec![1, 5]] {
+            let err = contract
+                .buy_ticket_with_numbers(&mut app, alice(), ARCH_DEMON, numbers.clone(), &ticket)
+                .unwrap_err();
+            assert_eq!(
+                ContractError::InvalidNumbers {
+                    numbers,
+                    pick: 2,
+                    max_number: 4
+                },
+                err.downcast().unwrap()
+            );
+        }
+
+        contract
+            .buy_ticket_with_numbers(&mut app, alice(), ARCH_DEMON, vec![2, 1], &ticket)
+            .unwrap();
+        contract
+            .buy_ticket_with_numbers(&mut app, bob(), ARCH_DEMON, vec![3, 4], &ticket)
+            .unwrap();
+
+        let player = contract.player_info(&app, alice().as_str()).unwrap();
+        assert_eq!(player.info.unwrap().numbers, Some(vec![1, 2]));
+
+        contract.draw_lottery(&mut app, owner()).unwrap();
+
+        let draw = contract.draw_proof(&app, None, None).unwrap().draw.unwrap();
+        let winning_numbers = draw.winning_numbers.unwrap();
+        assert_eq!(winning_numbers.len(), 2);
+        assert!(winning_numbers[0] < winning_numbers[1] && winning_numbers[1] <= 4);
+
+        // a full match takes the 2-match tier, otherwise both share the 1-match tier
+        let (alice_prize, bob_prize) = match winning_numbers.as_slice() {
+            [1, 2] => (140, 0),
+            [3, 4] => (0, 140),
+            _ => (30, 30),
+        };
+        let balance = |app: &App, addr: Addr| {
+            app.wrap()
+                .query_balance(addr, ARCH_DEMON)
+                .unwrap()
+                .amount
+                .u128()
+        };
+        assert_eq!(balance(&app, alice()), 100 + alice_prize);
+        assert_eq!(balance(&app, bob()), bob_prize);
+
+        // the tier without winners stays in a standalone lottery
+        assert_eq!(
+            balance(&app, contract.addr()),
+            200 - alice_prize - bob_prize
+        );
+
+        let err = contract.claim_lottery(&mut app, alice()).unwrap_err();
+        assert_eq!(ContractError::NothingToClaim {}, err.downcast().unwrap());
+
+        // a jackpot ticket takes no numbers
+        let contract = code_id
+            .instantiate(
+                &mut app,
+                owner(),
+                "LOTTERY",
+                "LOTTER",
+                100,
+                ARCH_DEMON,
+                "hour",
+                expiration,
+                WinnerSelection::Jackpot {},
+                2,
+                "Lottery label",
+            )
+            .unwrap();
+        let err = contract
+            .buy_ticket_with_numbers(&mut app, alice(), ARCH_DEMON, vec![1, 2], &ticket)
+            .unwrap_err();
+        assert_eq!(ContractError::NumbersNotAllowed {}, err.downcast().unwrap());
+    }
+
+    #[test]
+    fn scratch_should_works() {
+        let mut app = mock_app(|router, _api, storage| {
+            for (addr, amount) in [
+                (alice(), 200),
+                (bob(), 200),
+                (parent(), 100),
+                (owner(), 400),
+            ] {
+                router
+                    .bank
+                    .init_balance(storage, &addr, coins(amount, ARCH_DEMON))
+                    .unwrap();
+            }
+        });
+
+        let code_id = LotteryCodeId::store_code(&mut app);
+        let expiration = Utc::now().timestamp() as u64 + 3600;
+        let prize = |amount: u128, count| ScratchPrize {
+            amount: amount.into(),
+            count,
+        };
+        let instantiate = |app: &mut App, prizes, max_players, funds: &[Coin]| {
+            let msg = InstantiateMsg::new(
+                "LOTTERY",
+                "LOTTER",
+                100,
+                ARCH_DEMON,
+                "hour",
+                expiration,
+                WinnerSelection::Scratch { prizes },
+                max_players,
+                None,
+            );
+            app.instantiate_contract(code_id.into(), owner(), &msg, funds, "Lottery label", None)
+        };
+        let balance = |app: &App, addr: Addr| {
+            app.wrap()
+                .query_balance(addr, ARCH_DEMON)
+                .unwrap()
+                .amount
+                .u128()
+        };
+
+        let err =
+            instantiate(&mut app, vec![prize(100, 3)], 2, &coins(300, ARCH_DEMON)).unwrap_err();
+        assert_eq!(
+            ContractError::InvalidScratchTable { max_players: 2 },
+            err.downcast().unwrap()
+        );
+
+        let err = instantiate(&mut app, vec![prize(150, 1), prize(50, 1)], 2, &[]).unwrap_err();
+        assert_eq!(
+            ContractError::ScratchNotFunded {
+                expected: coin(200, ARCH_DEMON)
+            },
+            err.downcast().unwrap()
+        );
+
+        // every ticket wins when there are as many prizes as tickets
+        let contract: LotteryContract = instantiate(
+            &mut app,
+            vec![prize(150, 1), prize(50, 1)],
+            2,
+            &coins(200, ARCH_DEMON),
+        )
+        .unwrap()
+        .into();
+        let ticket = coins(100, ARCH_DEMON);
+
+        contract
+            .buy_ticket(&mut app, alice(), ARCH_DEMON, None, &ticket)
+            .unwrap();
+        let scratch = contract.scratch(&app).unwrap();
+        assert_eq!(scratch.pending, Some(1));
+        assert!(scratch.results.is_empty());
+
+        let err = contract.draw_lottery(&mut app, owner()).unwrap_err();
+        assert_eq!(ContractError::ScratchNotDrawn {}, err.downcast().unwrap());
+
+        // a purchase in the same block leaves the pending ticket unsettled
+        contract
+            .buy_ticket(&mut app, bob(), ARCH_DEMON, None, &ticket)
+            .unwrap();
+        let scratch = contract.scratch(&app).unwrap();
+        assert_eq!(scratch.pending, Some(1));
+        assert!(scratch.results.is_empty());
+
+        // both tickets are settled from a later block
+        let err = contract.settle_ticket(&mut app, parent()).unwrap_err();
+        assert_eq!(
+            ContractError::SettleTooEarly {
+                height: app.block_info().height
+            },
+            err.downcast().unwrap()
+        );
+        app.update_block(next_block);
+        contract.settle_ticket(&mut app, parent()).unwrap();
+
+        let scratch = contract.scratch(&app).unwrap();
+        let alice_prize = scratch.results[0].prize.clone().unwrap().amount.u128();
+        assert!(alice_prize == 150 || alice_prize == 50);
+        assert_eq!(balance(&app, alice()), 100 + alice_prize);
+        assert_eq!(balance(&app, bob()), 100 + 200 - alice_prize);
+        assert_eq!(scratch.remaining, vec![0, 0]);
+        assert_eq!(scratch.pending, None);
+        assert_eq!(contract.winner(&app).unwrap().winner.len(), 2);
+
+        let state = contract.query_state(&app).unwrap().state;
+        assert_eq!(state.status, GameStatus::Closed);
+
+        let err = contract.settle_ticket(&mut app, parent()).unwrap_err();
+        assert_eq!(ContractError::NothingToSettle {}, err.downcast().unwrap());
+
+        // the lottery closes when the prize table runs out, the last buyer is paid back
+        let contract: LotteryContract =
+            instantiate(&mut app, vec![prize(100, 2)], 3, &coins(200, ARCH_DEMON))
+                .unwrap()
+                .into();
+        // each purchase settles the ticket bought in the block before
+        for player in [bob(), alice(), parent()] {
+            contract
+                .buy_ticket(&mut app, player, ARCH_DEMON, None, &ticket)
+                .unwrap();
+            app.update_block(next_block);
+        }
+
+        // both prizes are won by the first two tickets with these buyers, either way is checked
+        let scratch = contract.scratch(&app).unwrap();
+        let state = contract.query_state(&app).unwrap().state;
+        if scratch.remaining == vec![0] {
+            assert_eq!(scratch.results.len(), 2);
+            assert_eq!(scratch.pending, None);
+            assert_eq!(state.status, GameStatus::Closed);
+            assert_eq!(state.player_count, 2);
+            assert_eq!(balance(&app, parent()), 100);
+        } else {
+            assert_eq!(scratch.remaining, vec![1]);
+            assert_eq!(scratch.pending, Some(3));
+            assert_eq!(state.status, GameStatus::Activing);
+            assert_eq!(balance(&app, parent()), 0);
+        }
+    }
+
+    #[test]
+    fn allowlist_should_works() {
+        let mut app = mock_app(|router, _api, storage| {
+            for player in [alice(), bob(), parent()] {
+                router
+                    .bank
+                    .init_balance(storage, &player, coins(100, ARCH_DEMON))
+                    .unwrap();
+            }
+        });
+
+        let code_id = LotteryCodeId::store_code(&mut app);
+        let expiration = Utc::now().timestamp() as u64;
+        let msg = InstantiateMsg::new(
+            "LOTTERY",
+            "LOTTER",
+            100,
+            ARCH_DEMON,
+            "hour",
+            expiration,
+            WinnerSelection::Jackpot {},
+            3,
+            None,
+        );
+
+        // a public lottery has no list to update
+        let public = code_id
+            .instantiate(
+                &mut app,
+                owner(),
+                "LOTTERY",
+                "LOTTER",
+                100,
+                ARCH_DEMON,
+                "hour",
+                expiration,
+                WinnerSelection::Jackpot {},
+                3,
+                "Lottery label",
+            )
+            .unwrap();
+        assert_eq!(public.allowlist(&app).unwrap().allowlist, None);
+        let err = public
+            .update_allowlist(&mut app, owner(), vec![bob().to_string()], vec![])
+            .unwrap_err();
+        assert_eq!(
+            ContractError::AllowlistNotInline {},
+            err.downcast().unwrap()
+        );
+
+        let contract: LotteryContract = app
+            .instantiate_contract(
+                code_id.into(),
+                owner(),
+                &msg.with_allowlist(AllowlistMsg::Addresses {
+                    addresses: vec![alice().to_string()],
+                }),
+                &[],
+                "Lottery label",
+                None,
+            )
+            .unwrap()
+            .into();
+
+        let err = contract
+            .buy_ticket(&mut app, bob(), ARCH_DEMON, None, &coins(100, ARCH_DEMON))
+            .unwrap_err();
+        assert_eq!(
+            ContractError::NotAllowlisted { player: bob() },
+            err.downcast().unwrap()
+        );
+
+        let err = contract
+            .update_allowlist(&mut app, alice(), vec![bob().to_string()], vec![])
+            .unwrap_err();
+        assert_eq!(ContractError::Unauthorized {}, err.downcast().unwrap());
+
+        contract
+            .update_allowlist(
+                &mut app,
+                owner(),
+                vec![bob().to_string(), parent().to_string()],
+                vec![parent().to_string()],
+            )
+            .unwrap();
+
+        let allowlist = contract.allowlist(&app).unwrap();
+        assert_eq!(allowlist.allowlist, Some(Allowlist::Addresses {}));
+        assert_eq!(allowlist.members.len(), 2);
+        assert!(!allowlist.members.contains(&parent()));
+
+        contract
+            .buy_ticket(&mut app, alice(), ARCH_DEMON, None, &coins(100, ARCH_DEMON))
+            .unwrap();
+
+        // the list is locked once tickets are sold
+        let err = contract
+            .update_allowlist(&mut app, owner(), vec![parent().to_string()], vec![])
+            .unwrap_err();
+        assert_eq!(ContractError::AllowlistLocked {}, err.downcast().unwrap());
+
+        contract
+            .buy_ticket(&mut app, bob(), ARCH_DEMON, None, &coins(100, ARCH_DEMON))
+            .unwrap();
+        let err = contract
+            .buy_ticket(
+                &mut app,
+                parent(),
+                ARCH_DEMON,
+                None,
+                &coins(100, ARCH_DEMON),
+            )
+            .unwrap_err();
+        assert_eq!(
+            ContractError::NotAllowlisted { player: parent() },
+            err.downcast().unwrap()
+        );
+    }
+
+    #[test]
+    fn nft_gate_should_works() {
+        let mut app = mock_app(|router, _api, storage| {
+            for player in [alice(), bob(), parent()] {
+                router
+                    .bank
+                    .init_balance(storage, &player, coins(200, ARCH_DEMON))
+                    .unwrap();
+            }
+        });
+
+        // the membership collection, a plain cw721-base
+        let collection_code_id = app.store_code(Box::new(ContractWrapper::new(
+            cw721_base::entry::execute,
+            cw721_base::entry::instantiate,
+            cw721_base::entry::query,
+        )));
+        let collection = app
+            .instantiate_contract(
+                collection_code_id,
+                owner(),
+                &cw721_base::InstantiateMsg {
+                    name: "PASS".to_string(),
+                    symbol: "PASS".to_string(),
+                    minter: owner().to_string(),
+                },
+                &[],
+                "Membership pass",
+                None,
+            )
+            .unwrap();
+        let mint = |app: &mut App, token_id: &str, holder: &Addr| {
+            let msg: cw721_base::ExecuteMsg<cw721_base::Extension, Empty> =
+                cw721_base::ExecuteMsg::Mint {
+                    token_id: token_id.to_string(),
+                    owner: holder.to_string(),
+                    token_uri: None,
+                    extension: None,
+                };
+            app.execute_contract(owner(), collection.clone(), &msg, &[])
+                .unwrap();
+        };
+        for (token_id, holder) in [("1", alice()), ("2", alice()), ("3", bob())] {
+            mint(&mut app, token_id, &holder);
+        }
+
+        let code_id = LotteryCodeId::store_code(&mut app);
+        let expiration = Utc::now().timestamp() as u64;
+        let new_msg = |min_tokens| {
+            InstantiateMsg::new(
+                "LOTTERY",
+                "LOTTER",
+                100,
+                ARCH_DEMON,
+                "hour",
+                expiration,
+                WinnerSelection::Jackpot {},
+                3,
+                None,
+            )
+            .with_nft_gate(NftGateMsg {
+                collection: collection.to_string(),
+                min_tokens,
+                once_per_token: true,
+            })
+        };
+
+        let err = app
+            .instantiate_contract(code_id.into(), owner(), &new_msg(0), &[], "label", None)
+            .unwrap_err();
+        assert_eq!(ContractError::InvalidNftGate {}, err.downcast().unwrap());
+
+        let contract: LotteryContract = app
+            .instantiate_contract(code_id.into(), owner(), &new_msg(1), &[], "label", None)
+            .unwrap()
+            .into();
+
+        let err = contract
+            .buy_ticket(
+                &mut app,
+                parent(),
+                ARCH_DEMON,
+                None,
+                &coins(100, ARCH_DEMON),
+            )
+            .unwrap_err();
+        assert_eq!(
+            ContractError::NotEnoughGateTokens {
+                player: parent(),
+                required: 1,
+                held: 0,
+            },
+            err.downcast().unwrap()
+        );
+
+        contract
+            .buy_ticket(&mut app, alice(), ARCH_DEMON, None, &coins(100, ARCH_DEMON))
+            .unwrap();
+
+        // a consumed token can't admit another ticket once transferred
+        let msg: cw721_base::ExecuteMsg<cw721_base::Extension, Empty> =
+            cw721_base::ExecuteMsg::TransferNft {
+                recipient: parent().to_string(),
+                token_id: "1".to_string(),
+            };
+        app.execute_contract(alice(), collection.clone(), &msg, &[])
+            .unwrap();
+        let err = contract
+            .buy_ticket(
+                &mut app,
+                parent(),
+                ARCH_DEMON,
+                None,
+                &coins(100, ARCH_DEMON),
+            )
+            .unwrap_err();
+        assert_eq!(
+            ContractError::NotEnoughGateTokens {
+                player: parent(),
+                required: 1,
+                held: 0,
+            },
+            err.downcast().unwrap()
+        );
+
+        contract
+            .buy_ticket(&mut app, bob(), ARCH_DEMON, None, &coins(100, ARCH_DEMON))
+            .unwrap();
+
+        let gate = contract.nft_gate(&app).unwrap();
+        assert_eq!(gate.gate.unwrap().collection, collection);
+        let used: Vec<_> = gate
+            .used_tokens
+            .into_iter()
+            .map(|used| (used.token_id, used.player))
+            .collect();
+        assert_eq!(
+            used,
+            vec![("1".to_string(), alice()), ("3".to_string(), bob())]
+        );
+
+        // holding is enough when tokens can be reused
+        let reusable: LotteryContract = app
+            .instantiate_contract(
+                code_id.into(),
+                owner(),
+                &InstantiateMsg::new(
+                    "LOTTERY",
+                    "LOTTER",
+                    100,
+                    ARCH_DEMON,
+                    "hour",
+                    expiration,
+                    WinnerSelection::Jackpot {},
+                    3,
+                    None,
+                )
+                .with_nft_gate(NftGateMsg {
+                    collection: collection.to_string(),
+                    min_tokens: 1,
+                    once_per_token: false,
+                }),
+                &[],
+                "label",
+                None,
+            )
+            .unwrap()
+            .into();
+        reusable
+            .buy_ticket(
+                &mut app,
+                parent(),
+                ARCH_DEMON,
+                None,
+                &coins(100, ARCH_DEMON),
+            )
+            .unwrap();
+        assert!(reusable.nft_gate(&app).unwrap().used_tokens.is_empty());
+    }
+
+    #[test]
+    fn nft_prizes_should_works() {
+        let mut app = mock_app(|router, _api, storage| {
+            for player in [alice(), bob()] {
+                router
+                    .bank
+                    .init_balance(storage, &player, coins(100, ARCH_DEMON))
+                    .unwrap();
+            }
+        });
+
+        // the prize collection, a plain cw721-base
+        let collection_code_id = app.store_code(Box::new(ContractWrapper::new(
+            cw721_base::entry::execute,
+            cw721_base::entry::instantiate,
+            cw721_base::entry::query,
+        )));
+        let collection = app
+            .instantiate_contract(
+                collection_code_id,
+                owner(),
+                &cw721_base::InstantiateMsg {
+                    name: "ART".to_string(),
+                    symbol: "ART".to_string(),
+                    minter: owner().to_string(),
+                },
+                &[],
+                "Prize collection",
+                None,
+            )
+            .unwrap();
+        let prizes: Cw721Contract<Empty, Empty> =
+            Cw721Contract(collection.clone(), PhantomData, PhantomData);
+        for (token_id, holder) in [
+            ("1", owner()),
+            ("2", owner()),
+            ("3", alice()),
+            ("4", owner()),
+        ] {
+            let msg: cw721_base::ExecuteMsg<cw721_base::Extension, Empty> =
+                cw721_base::ExecuteMsg::Mint {
+                    token_id: token_id.to_string(),
+                    owner: holder.to_string(),
+                    token_uri: None,
+                    extension: None,
+                };
+            app.execute_contract(owner(), collection.clone(), &msg, &[])
+                .unwrap();
+        }
+        let deposit = |app: &mut App, sender: Addr, lottery: &LotteryContract, token_id: &str| {
+            let msg: cw721_base::ExecuteMsg<cw721_base::Extension, Empty> =
+                cw721_base::ExecuteMsg::SendNft {
+                    contract: lottery.addr().to_string(),
+                    token_id: token_id.to_string(),
+                    msg: Default::default(),
+                };
+            app.execute_contract(sender, collection.clone(), &msg, &[])
+        };
+
+        let code_id = LotteryCodeId::store_code(&mut app);
+        let expiration = Utc::now().timestamp() as u64;
+        let new_lottery = |app: &mut App| {
+            code_id
+                .instantiate(
+                    app,
+                    owner(),
+                    "LOTTERY",
+                    "LOTTER",
+                    100,
+                    ARCH_DEMON,
+                    "hour",
+                    expiration,
+                    WinnerSelection::Jackpot {},
+                    3,
+                    "Lottery label",
+                )
+                .unwrap()
+        };
+
+        let contract = new_lottery(&mut app);
+        deposit(&mut app, owner(), &contract, "1").unwrap();
+        deposit(&mut app, owner(), &contract, "2").unwrap();
+
+        // only the owner deposits prizes
+        let err = deposit(&mut app, alice(), &contract, "3").unwrap_err();
+        assert_eq!(ContractError::Unauthorized {}, err.downcast().unwrap());
+
+        let deposits = contract.nft_prizes(&app).unwrap().prizes;
+        assert_eq!(deposits.len(), 2);
+        assert_eq!(deposits[0].collection, collection);
+        assert_eq!(deposits[0].token_id, "1");
+        assert_eq!(deposits[0].depositor, owner());
+
+        for player in [alice(), bob()] {
+            contract
+                .buy_ticket(&mut app, player, ARCH_DEMON, None, &coins(100, ARCH_DEMON))
+                .unwrap();
+        }
+        contract.draw_lottery(&mut app, owner()).unwrap();
+
+        let winner = contract.query_state(&app).unwrap().state.winner[0].clone();
+        assert_eq!(winner.prize, coins(200, ARCH_DEMON));
+        assert_eq!(
+            winner
+                .nft_prizes
+                .iter()
+                .map(|prize| prize.token_id.as_str())
+                .collect::<Vec<_>>(),
+            vec!["1", "2"]
+        );
+
+        contract
+            .claim_lottery(&mut app, winner.address.clone())
+            .unwrap();
+        for token_id in ["1", "2"] {
+            let resp = prizes.owner_of(&app.wrap(), token_id, false).unwrap();
+            assert_eq!(resp.owner, winner.address);
+        }
+        assert!(contract.nft_prizes(&app).unwrap().prizes.is_empty());
+
+        // a cancelled lottery gives the prizes back
+        let cancelled = new_lottery(&mut app);
+        deposit(&mut app, owner(), &cancelled, "4").unwrap();
+        let resp = prizes.owner_of(&app.wrap(), "4", false).unwrap();
+        assert_eq!(resp.owner, cancelled.addr());
+
+        cancelled.cancel_lottery(&mut app, owner()).unwrap();
+        let resp = prizes.owner_of(&app.wrap(), "4", false).unwrap();
+        assert_eq!(resp.owner, owner());
+        assert!(cancelled.nft_prizes(&app).unwrap().prizes.is_empty());
+    }
+
+    #[test]
+    fn sponsor_should_works() {
+        let mut app = mock_app(|router, _api, storage| {
+            for player in [alice(), bob(), parent()] {
+                router
+                    .bank
+                    .init_balance(storage, &player, coins(200, ARCH_DEMON))
+                    .unwrap();
+            }
+        });
+
+        let code_id = LotteryCodeId::store_code(&mut app);
+        let expiration = Utc::now().timestamp() as u64;
+        let new_lottery = |app: &mut App| {
+            code_id
+                .instantiate(
+                    app,
+                    owner(),
+                    "LOTTERY",
+                    "LOTTER",
+                    100,
+                    ARCH_DEMON,
+                    "hour",
+                    expiration,
+                    WinnerSelection::Jackpot {},
+                    3,
+                    "Lottery label",
+                )
+                .unwrap()
+        };
+
+        let contract = new_lottery(&mut app);
+
+        let err = contract
+            .sponsor(&mut app, alice(), Some(" "), &coins(50, ARCH_DEMON))
+            .unwrap_err();
+        assert_eq!(
+            ContractError::InvalidSponsorName { max_len: 64 },
+            err.downcast().unwrap()
+        );
+
+        contract
+            .sponsor(&mut app, alice(), None, &coins(20, ARCH_DEMON))
+            .unwrap();
+        contract
+            .sponsor(
+                &mut app,
+                alice(),
+                Some("Alice's bakery"),
+                &coins(30, ARCH_DEMON),
+            )
+            .unwrap();
+        contract
+            .buy_ticket(&mut app, bob(), ARCH_DEMON, None, &coins(100, ARCH_DEMON))
+            .unwrap();
+
+        // sponsoring doesn't buy a ticket
+        let resp = contract.sponsors(&app).unwrap();
+        assert_eq!(resp.total, coin(50, ARCH_DEMON));
+        assert_eq!(resp.sponsors.len(), 1);
+        assert_eq!(resp.sponsors[0].sponsor, alice());
+        assert_eq!(resp.sponsors[0].name, Some("Alice's bakery".to_string()));
+        assert_eq!(resp.sponsors[0].amount, coin(50, ARCH_DEMON));
+        assert_eq!(contract.query_state(&app).unwrap().state.player_count, 1);
+
+        contract.draw_lottery(&mut app, owner()).unwrap();
+        let winner = contract.query_state(&app).unwrap().state.winner[0].clone();
+        assert_eq!(winner.address, bob());
+        assert_eq!(winner.prize, coins(150, ARCH_DEMON));
+
+        let err = contract
+            .sponsor(&mut app, alice(), None, &coins(10, ARCH_DEMON))
+            .unwrap_err();
+        assert_eq!(
+            ContractError::LotteryAlreadyClosed {},
+            err.downcast().unwrap()
+        );
+
+        // sponsors get their contribution back from a cancelled lottery
+        let cancelled = new_lottery(&mut app);
+        cancelled
+            .sponsor(&mut app, parent(), None, &coins(40, ARCH_DEMON))
+            .unwrap();
+        cancelled
+            .buy_ticket(
+                &mut app,
+                parent(),
+                ARCH_DEMON,
+                None,
+                &coins(100, ARCH_DEMON),
+            )
+            .unwrap();
+        cancelled.cancel_lottery(&mut app, owner()).unwrap();
+
+        cancelled.refund(&mut app, parent()).unwrap();
+        let balance = app.wrap().query_balance(parent(), ARCH_DEMON).unwrap();
+        assert_eq!(balance.amount.u128(), 200);
+
+        let err = cancelled.refund(&mut app, alice()).unwrap_err();
+        assert_eq!(
+            ContractError::NothingToRefund { player: alice() },
+            err.downcast().unwrap()
+        );
+    }
+
+    #[test]
+    fn pause_should_works() {
+        let mut app = mock_app(|router, _api, storage| {
+            for player in [alice(), bob()] {
+                router
+                    .bank
+                    .init_balance(storage, &player, coins(200, ARCH_DEMON))
+                    .unwrap();
+            }
+        });
+
+        let code_id = LotteryCodeId::store_code(&mut app);
+        let expiration = Utc::now().timestamp() as u64;
+        let contract = code_id
+            .instantiate(
+                &mut app,
+                owner(),
+                "LOTTERY",
+                "LOTTER",
+                100,
+                ARCH_DEMON,
+                "hour",
+                expiration,
+                WinnerSelection::Jackpot {},
+                3,
+                "Lottery label",
+            )
+            .unwrap();
+        let now = app.block_info().time.seconds();
+
+        let err = contract.pause(&mut app, alice(), "bug", None).unwrap_err();
+        assert_eq!(ContractError::Unauthorized {}, err.downcast().unwrap());
+
+        let err = contract.pause(&mut app, owner(), " ", None).unwrap_err();
+        assert_eq!(
+            ContractError::PauseReasonMissing {},
+            err.downcast().unwrap()
+        );
+
+        let err = contract
+            .pause(&mut app, owner(), "bug", Some(now))
+            .unwrap_err();
+        assert_eq!(
+            ContractError::InvalidPauseExpiry { expires_at: now },
+            err.downcast().unwrap()
+        );
+
+        contract.pause(&mut app, owner(), "bug", None).unwrap();
+        let pause = contract.pause_info(&app).unwrap().pause.unwrap();
+        assert_eq!(pause.reason, "bug");
+        assert_eq!(pause.paused_by, owner());
+
+        let paused = ContractError::Paused {
+            reason: "bug".to_string(),
+        };
+        let err = contract
+            .buy_ticket(&mut app, alice(), ARCH_DEMON, None, &coins(100, ARCH_DEMON))
+            .unwrap_err();
+        assert_eq!(paused, err.downcast().unwrap());
+        let err = contract.draw_lottery(&mut app, owner()).unwrap_err();
+        assert_eq!(paused, err.downcast().unwrap());
+
+        contract.unpause(&mut app, owner()).unwrap();
+        assert!(contract.pause_info(&app).unwrap().pause.is_none());
+        contract
+            .buy_ticket(&mut app, alice(), ARCH_DEMON, None, &coins(100, ARCH_DEMON))
+            .unwrap();
+
+        // a pause lifts itself once it expires
+        contract
+            .pause(&mut app, owner(), "maintenance", Some(now + 60))
+            .unwrap();
+        let err = contract
+            .buy_ticket(&mut app, bob(), ARCH_DEMON, None, &coins(100, ARCH_DEMON))
+            .unwrap_err();
+        assert_eq!(
+            ContractError::Paused {
+                reason: "maintenance".to_string()
+            },
+            err.downcast().unwrap()
+        );
+        app.update_block(|block| block.time = block.time.plus_seconds(60));
+        assert!(contract.pause_info(&app).unwrap().pause.is_none());
+        contract
+            .buy_ticket(&mut app, bob(), ARCH_DEMON, None, &coins(100, ARCH_DEMON))
+            .unwrap();
+
+        // refunds keep working while paused
+        contract.pause(&mut app, owner(), "bug", None).unwrap();
+        contract.cancel_lottery(&mut app, owner()).unwrap();
+        contract.refund(&mut app, alice()).unwrap();
+        let balance = app.wrap().query_balance(alice(), ARCH_DEMON).unwrap();
+        assert_eq!(balance.amount.u128(), 200);
+    }
+
+    #[test]
+    fn ownership_should_works() {
+        let mut app = mock_app(|_, _, _| {});
+        let code_id = LotteryCodeId::store_code(&mut app);
+        let expiration = Utc::now().timestamp() as u64;
+        let contract = code_id
+            .instantiate(
+                &mut app,
+                owner(),
+                "LOTTERY",
+                "LOTTER",
+                100,
+                ARCH_DEMON,
+                "hour",
+                expiration,
+                WinnerSelection::Jackpot {},
+                3,
+                "Lottery label",
+            )
+            .unwrap();
+        let height = app.block_info().height;
+
+        let err = contract
+            .propose_owner(&mut app, alice(), alice().as_str(), None)
+            .unwrap_err();
+        assert_eq!(ContractError::Unauthorized {}, err.downcast().unwrap());
+
+        let err = contract
+            .propose_owner(
+                &mut app,
+                owner(),
+                alice().as_str(),
+                Some(Expiration::AtHeight(height)),
+            )
+            .unwrap_err();
+        assert_eq!(
+            ContractError::InvalidOwnershipExpiry {
+                expiry: Expiration::AtHeight(height)
+            },
+            err.downcast().unwrap()
+        );
+
+        // an expired proposal can't be accepted
+        contract
+            .propose_owner(
+                &mut app,
+                owner(),
+                alice().as_str(),
+                Some(Expiration::AtHeight(height + 5)),
+            )
+            .unwrap();
+        let err = contract.accept_ownership(&mut app, bob()).unwrap_err();
+        assert_eq!(ContractError::Unauthorized {}, err.downcast().unwrap());
+        app.update_block(|block| block.height += 5);
+        let err = contract.accept_ownership(&mut app, alice()).unwrap_err();
+        assert_eq!(
+            ContractError::OwnershipTransferExpired {},
+            err.downcast().unwrap()
+        );
+
+        contract
+            .cancel_ownership_transfer(&mut app, owner())
+            .unwrap();
+        let err = contract.accept_ownership(&mut app, alice()).unwrap_err();
+        assert_eq!(ContractError::NoPendingOwner {}, err.downcast().unwrap());
+
+        contract
+            .propose_owner(&mut app, owner(), alice().as_str(), None)
+            .unwrap();
+        let resp = contract.ownership(&app).unwrap();
+        assert_eq!(resp.owner, Some(owner()));
+        assert_eq!(resp.pending_owner.unwrap().address, alice());
+
+        contract.accept_ownership(&mut app, alice()).unwrap();
+        let resp = contract.ownership(&app).unwrap();
+        assert_eq!(resp.owner, Some(alice()));
+        assert_eq!(resp.pending_owner, None);
+
+        let err = contract.renounce_ownership(&mut app, owner()).unwrap_err();
+        assert_eq!(ContractError::Unauthorized {}, err.downcast().unwrap());
+
+        // the lottery can't be left running without an owner
+        let err = contract.renounce_ownership(&mut app, alice()).unwrap_err();
+        assert_eq!(
+            ContractError::RenounceWhileActive {},
+            err.downcast().unwrap()
+        );
+        contract.cancel_lottery(&mut app, alice()).unwrap();
+        contract.renounce_ownership(&mut app, alice()).unwrap();
+        assert_eq!(contract.ownership(&app).unwrap().owner, None);
+
+        let err = contract.pause(&mut app, alice(), "bug", None).unwrap_err();
+        assert_eq!(ContractError::Unauthorized {}, err.downcast().unwrap());
+    }
+
+    #[test]
+    fn withdraw_should_works() {
+        let mut app = mock_app(|router, _api, storage| {
+            for player in [alice(), bob()] {
+                router
+                    .bank
+                    .init_balance(storage, &player, coins(200, ARCH_DEMON))
+                    .unwrap();
+            }
+            router
+                .bank
+                .init_balance(
+                    storage,
+                    &owner(),
+                    vec![coin(500, ARCH_DEMON), coin(50, "uatom")],
+                )
+                .unwrap();
+        });
+
+        let code_id = LotteryCodeId::store_code(&mut app);
+        let expiration = Utc::now().timestamp() as u64;
+        let contract = code_id
+            .instantiate(
+                &mut app,
+                owner(),
+                "LOTTERY",
+                "LOTTER",
+                100,
+                ARCH_DEMON,
+                "hour",
+                expiration,
+                WinnerSelection::Jackpot {},
+                2,
+                "Lottery label",
+            )
+            .unwrap();
+
+        for player in [alice(), bob()] {
+            contract
+                .buy_ticket(&mut app, player, ARCH_DEMON, None, &coins(100, ARCH_DEMON))
+                .unwrap();
+        }
+        app.send_tokens(owner(), contract.addr(), &coins(50, "uatom"))
+            .unwrap();
+
+        // only what's beyond the ticket pot can be withdrawn before the draw
+        app.send_tokens(owner(), contract.addr(), &coins(20, ARCH_DEMON))
+            .unwrap();
+        let err = contract
+            .withdraw(&mut app, owner(), 21, ARCH_DEMON, None)
+            .unwrap_err();
+        assert_eq!(
+            ContractError::PotLocked {
+                excess: coin(20, ARCH_DEMON)
+            },
+            err.downcast().unwrap()
+        );
+        contract
+            .withdraw(&mut app, owner(), 20, ARCH_DEMON, None)
+            .unwrap();
+        contract
+            .withdraw(&mut app, owner(), 50, "uatom", None)
+            .unwrap();
+
+        contract.draw_lottery(&mut app, owner()).unwrap();
+        let winner = contract.query_state(&app).unwrap().state.winner[0]
+            .address
+            .clone();
+
+        // the owner only withdraws what's beyond the prize
+        app.send_tokens(owner(), contract.addr(), &coins(30, ARCH_DEMON))
+            .unwrap();
+        let err = contract
+            .withdraw(&mut app, owner(), 31, ARCH_DEMON, None)
+            .unwrap_err();
+        assert_eq!(
+            ContractError::ExcessExceeded {
+                excess: coin(30, ARCH_DEMON)
+            },
+            err.downcast().unwrap()
+        );
+        contract
+            .withdraw(&mut app, owner(), 30, ARCH_DEMON, None)
+            .unwrap();
+
+        // the winner only withdraws the prize left
+        contract.claim_lottery(&mut app, winner.clone()).unwrap();
+        contract
+            .withdraw(&mut app, winner.clone(), 150, ARCH_DEMON, None)
+            .unwrap();
+        app.send_tokens(owner(), contract.addr(), &coins(20, ARCH_DEMON))
+            .unwrap();
+        let err = contract
+            .withdraw(&mut app, winner.clone(), 60, ARCH_DEMON, None)
+            .unwrap_err();
+        assert_eq!(
+            ContractError::PrizeExceeded {
+                prize: coin(50, ARCH_DEMON)
+            },
+            err.downcast().unwrap()
+        );
+        contract
+            .withdraw(&mut app, winner.clone(), 50, ARCH_DEMON, None)
+            .unwrap();
+
+        let balance = app.wrap().query_balance(&winner, ARCH_DEMON).unwrap();
+        assert_eq!(balance.amount.u128(), 300);
+        let balance = app
+            .wrap()
+            .query_balance(contract.addr(), ARCH_DEMON)
+            .unwrap();
+        assert_eq!(balance.amount.u128(), 20);
+
+        // the owner keeps the lottery and what's beyond the prize after the claim
+        assert_eq!(contract.owner(&app).unwrap().owner, owner());
+        contract
+            .withdraw(&mut app, owner(), 20, ARCH_DEMON, None)
+            .unwrap();
+
+        // a cancelled lottery keeps what it still has to refund
+        let contract = code_id
+            .instantiate(
+                &mut app,
+                owner(),
+                "LOTTERY",
+                "LOTTER",
+                100,
+                ARCH_DEMON,
+                "hour",
+                expiration,
+                WinnerSelection::Jackpot {},
+                2,
+                "Lottery label",
+            )
+            .unwrap();
+        for player in [alice(), bob()] {
+            contract
+                .buy_ticket(&mut app, player, ARCH_DEMON, None, &coins(100, ARCH_DEMON))
+                .unwrap();
+        }
+        contract.cancel_lottery(&mut app, owner()).unwrap();
+        app.send_tokens(owner(), contract.addr(), &coins(10, ARCH_DEMON))
+            .unwrap();
+        let err = contract
+            .withdraw(&mut app, owner(), 11, ARCH_DEMON, None)
+            .unwrap_err();
+        assert_eq!(
+            ContractError::RefundsPending {
+                excess: coin(10, ARCH_DEMON)
+            },
+            err.downcast().unwrap()
+        );
+        contract.refund(&mut app, alice()).unwrap();
+        contract
+            .withdraw(&mut app, owner(), 10, ARCH_DEMON, None)
+            .unwrap();
+        let err = contract
+            .withdraw(&mut app, owner(), 1, ARCH_DEMON, None)
+            .unwrap_err();
+        assert_eq!(
+            ContractError::RefundsPending {
+                excess: coin(0, ARCH_DEMON)
+            },
+            err.downcast().unwrap()
+        );
+        contract.refund(&mut app, bob()).unwrap();
+    }
+
+    #[test]
+    fn scratch_withdraw_should_works() {
+        let mut app = mock_app(|router, _api, storage| {
+            for (addr, amount) in [(alice(), 100), (owner(), 150)] {
+                router
+                    .bank
+                    .init_balance(storage, &addr, coins(amount, ARCH_DEMON))
+                    .unwrap();
+            }
+        });
+
+        let code_id = LotteryCodeId::store_code(&mut app);
+        let expiration = app.block_info().time.seconds() + 3600;
+        let prizes = vec![
+            ScratchPrize {
+                amount: 100u128.into(),
+                count: 1,
+            },
+            ScratchPrize {
+                amount: 50u128.into(),
+                count: 1,
+            },
+        ];
+        let msg = InstantiateMsg::new(
+            "LOTTERY",
+            "LOTTER",
+            100,
+            ARCH_DEMON,
+            "hour",
+            expiration,
+            WinnerSelection::Scratch { prizes },
+            3,
+            None,
+        );
+        let contract: LotteryContract = app
+            .instantiate_contract(
+                code_id.into(),
+                owner(),
+                &msg,
+                &coins(150, ARCH_DEMON),
+                "Lottery label",
+                None,
+            )
+            .unwrap()
+            .into();
+        contract
+            .buy_ticket(&mut app, alice(), ARCH_DEMON, None, &coins(100, ARCH_DEMON))
+            .unwrap();
+
+        // the whole prize table is kept while tickets are sold
+        let err = contract
+            .withdraw(&mut app, owner(), 101, ARCH_DEMON, None)
+            .unwrap_err();
+        assert_eq!(
+            ContractError::ScratchPrizesLocked {
+                excess: coin(100, ARCH_DEMON)
+            },
+            err.downcast().unwrap()
+        );
+        contract
+            .withdraw(&mut app, owner(), 100, ARCH_DEMON, None)
+            .unwrap();
+
+        // once expired, only the best prize left is kept for the pending ticket
+        app.update_block(|block| {
+            block.height += 1;
+            block.time = block.time.plus_seconds(3601);
+        });
+        let state = contract.query_state(&app).unwrap().state;
+        assert_eq!(state.status, GameStatus::Activing);
+        let err = contract
+            .withdraw(&mut app, owner(), 51, ARCH_DEMON, None)
+            .unwrap_err();
+        assert_eq!(
+            ContractError::ScratchPrizesLocked {
+                excess: coin(50, ARCH_DEMON)
+            },
+            err.downcast().unwrap()
+        );
+        contract
+            .withdraw(&mut app, owner(), 50, ARCH_DEMON, None)
+            .unwrap();
+
+        // nothing is kept after the last ticket is settled
+        contract.settle_ticket(&mut app, owner()).unwrap();
+        let balance = app
+            .wrap()
+            .query_balance(contract.addr(), ARCH_DEMON)
+            .unwrap()
+            .amount
+            .u128();
+        // nothing is left when the pending ticket won the best prize
+        if balance > 0 {
+            contract
+                .withdraw(&mut app, owner(), balance, ARCH_DEMON, None)
+                .unwrap();
+        }
+    }
+
+    #[test]
+    fn dynamic_pricing_should_works() {
+        let mut app = mock_app(|router, _api, storage| {
+            router
+                .bank
+                .init_balance(storage, &alice(), coins(1000, ARCH_DEMON))
+                .unwrap();
+        });
+
+        let code_id = LotteryCodeId::store_code(&mut app);
+        let expiration = Utc::now().timestamp() as u64;
+        let now = app.block_info().time.seconds();
+        let new_msg = |tiers| {
+            InstantiateMsg::new(
+                "LOTTERY",
+                "LOTTER",
+                80,
+                ARCH_DEMON,
+                "hour",
+                expiration,
+                WinnerSelection::Jackpot {},
+                5,
+                None,
+            )
+            .with_pricing(Pricing {
+                phases: vec![
+                    PricePhase {
+                        starts_at: now + 100,
+                        amount: Uint128::new(100),
+                    },
+                    PricePhase {
+                        starts_at: now + 200,
+                        amount: Uint128::new(120),
+                    },
+                ],
+                tiers,
+            })
+        };
+        let instantiate = |app: &mut App, tiers| {
+            app.instantiate_contract(
+                code_id.into(),
+                owner(),
+                &new_msg(tiers),
+                &[],
+                "Lottery label",
+                None,
+            )
+        };
+
+        let err = instantiate(
+            &mut app,
+            vec![QuantityTier {
+                quantity: 2,
+                paid: 2,
+            }],
+        )
+        .unwrap_err();
+        assert_eq!(ContractError::InvalidPricing {}, err.downcast().unwrap());
+
+        let tiers = vec![QuantityTier {
+            quantity: 3,
+            paid: 2,
+        }];
+        let contract: LotteryContract = instantiate(&mut app, tiers).unwrap().into();
+
+        // early bird, then standard and last call, 3 tickets for the price of 2
+        let quote = |quantity, at| contract.quote(&app, quantity, at).unwrap().price;
+        assert_eq!(quote(1, None), coin(80, ARCH_DEMON));
+        assert_eq!(quote(4, None), coin(240, ARCH_DEMON));
+        assert_eq!(quote(1, Some(now + 150)), coin(100, ARCH_DEMON));
+        assert_eq!(quote(3, Some(now + 250)), coin(240, ARCH_DEMON));
+
+        let recipients = [bob(), parent(), owner()];
+        let err = contract
+            .buy_tickets(
+                &mut app,
+                alice(),
+                ARCH_DEMON,
+                &recipients,
+                &coins(159, ARCH_DEMON),
+            )
+            .unwrap_err();
+        assert_eq!(
+            ContractError::PaymentNotEnough {
+                amount: Uint128::new(159)
+            },
+            err.downcast().unwrap()
+        );
+        contract
+            .buy_tickets(
+                &mut app,
+                alice(),
+                ARCH_DEMON,
+                &recipients,
+                &coins(160, ARCH_DEMON),
+            )
+            .unwrap();
+
+        // the last call price applies once started
+        app.update_block(|block| block.time = block.time.plus_seconds(250));
+        let err = contract
+            .buy_ticket(&mut app, alice(), ARCH_DEMON, None, &coins(100, ARCH_DEMON))
+            .unwrap_err();
+        assert_eq!(
+            ContractError::PaymentNotEnough {
+                amount: Uint128::new(100)
+            },
+            err.downcast().unwrap()
+        );
+        contract
+            .buy_ticket(&mut app, alice(), ARCH_DEMON, None, &coins(120, ARCH_DEMON))
+            .unwrap();
+
+        let paid = |address: Addr| {
+            contract
+                .player_info(&app, address.as_str())
+                .unwrap()
+                .info
+                .unwrap()
+                .paid
+        };
+        assert_eq!(paid(bob()), Some(coin(54, ARCH_DEMON)));
+        assert_eq!(paid(parent()), Some(coin(53, ARCH_DEMON)));
+        assert_eq!(paid(alice()), Some(coin(120, ARCH_DEMON)));
+
+        // the refunds follow the price paid
+        contract.cancel_lottery(&mut app, owner()).unwrap();
+        for player in [alice(), bob()] {
+            contract.refund(&mut app, player).unwrap();
+        }
+        let balance = app.wrap().query_balance(alice(), ARCH_DEMON).unwrap();
+        assert_eq!(balance.amount.u128(), 840);
+        let balance = app.wrap().query_balance(bob(), ARCH_DEMON).unwrap();
+        assert_eq!(balance.amount.u128(), 54);
+    }
+
+    #[test]
+    fn ticket_resale_should_works() {
+        let mut app = mock_app(|router, _api, storage| {
+            for player in [alice(), bob(), parent()] {
+                router
+                    .bank
+                    .init_balance(storage, &player, coins(300, ARCH_DEMON))
+                    .unwrap();
+            }
+        });
+
+        let code_id = LotteryCodeId::store_code(&mut app);
+        let expiration = Utc::now().timestamp() as u64;
+        let contract = code_id
+            .instantiate(
+                &mut app,
+                owner(),
+                "LOTTERY",
+                "LOTTER",
+                100,
+                ARCH_DEMON,
+                "hour",
+                expiration,
+                WinnerSelection::Jackpot {},
+                2,
+                "Lottery label",
+            )
+            .unwrap();
+        let cw721_contract: Cw721Contract<Empty, Empty> =
+            Cw721Contract(contract.addr(), PhantomData, PhantomData);
+
+        for player in [alice(), bob()] {
+            contract
+                .buy_ticket(&mut app, player, ARCH_DEMON, None, &coins(100, ARCH_DEMON))
+                .unwrap();
+        }
+
+        let err = contract
+            .list_ticket(&mut app, bob(), "1", coin(150, ARCH_DEMON))
+            .unwrap_err();
+        assert_eq!(ContractError::Unauthorized {}, err.downcast().unwrap());
+        let err = contract
+            .list_ticket(&mut app, alice(), "1", coin(0, ARCH_DEMON))
+            .unwrap_err();
+        assert_eq!(
+            ContractError::InvalidListingPrice {},
+            err.downcast().unwrap()
+        );
+
+        contract
+            .list_ticket(&mut app, alice(), "1", coin(150, ARCH_DEMON))
+            .unwrap();
+        let listings = contract.listings(&app).unwrap().listings;
+        assert_eq!(listings.len(), 1);
+        assert_eq!(listings[0].listing.seller, alice());
+
+        let err = contract.cancel_listing(&mut app, bob(), "1").unwrap_err();
+        assert_eq!(ContractError::Unauthorized {}, err.downcast().unwrap());
+        let err = contract
+            .buy_listed_ticket(&mut app, parent(), "1", &coins(100, ARCH_DEMON))
+            .unwrap_err();
+        assert_eq!(
+            ContractError::ListingPriceMismatch {
+                price: coin(150, ARCH_DEMON)
+            },
+            err.downcast().unwrap()
+        );
+
+        // the ticket and the payment change hands together
+        contract
+            .buy_listed_ticket(&mut app, parent(), "1", &coins(150, ARCH_DEMON))
+            .unwrap();
+        let nft_resp = cw721_contract.owner_of(&app.wrap(), "1", true).unwrap();
+        assert_eq!(nft_resp.owner, parent());
+        assert!(nft_resp.approvals.is_empty());
+        let balance = app.wrap().query_balance(alice(), ARCH_DEMON).unwrap();
+        assert_eq!(balance.amount.u128(), 350);
+        let err = contract
+            .buy_listed_ticket(&mut app, bob(), "1", &coins(150, ARCH_DEMON))
+            .unwrap_err();
+        assert_eq!(
+            ContractError::ListingNotFound {
+                token_id: "1".to_string()
+            },
+            err.downcast().unwrap()
+        );
+
+        // moving or burning a listed ticket drops its listing
+        contract
+            .list_ticket(&mut app, bob(), "2", coin(80, ARCH_DEMON))
+            .unwrap();
+        contract
+            .transfer_ticket(&mut app, bob(), alice().to_string(), "2".to_string())
+            .unwrap();
+        assert!(contract.listings(&app).unwrap().listings.is_empty());
+        let err = contract
+            .buy_listed_ticket(&mut app, parent(), "2", &coins(80, ARCH_DEMON))
+            .unwrap_err();
+        assert_eq!(
+            ContractError::ListingNotFound {
+                token_id: "2".to_string()
+            },
+            err.downcast().unwrap()
+        );
+        contract
+            .list_ticket(&mut app, alice(), "2", coin(80, ARCH_DEMON))
+            .unwrap();
+        contract
+            .burn_ticket(&mut app, alice(), "2".to_string())
+            .unwrap();
+        assert!(contract.listings(&app).unwrap().listings.is_empty());
+        let err = contract
+            .buy_listed_ticket(&mut app, parent(), "2", &coins(80, ARCH_DEMON))
+            .unwrap_err();
+        assert_eq!(
+            ContractError::ListingNotFound {
+                token_id: "2".to_string()
+            },
+            err.downcast().unwrap()
+        );
+
+        // the draw drops the listings and their escrow
+        contract
+            .list_ticket(&mut app, parent(), "1", coin(200, ARCH_DEMON))
+            .unwrap();
+        contract.draw_lottery(&mut app, owner()).unwrap();
+        assert!(contract.listings(&app).unwrap().listings.is_empty());
+        let nft_resp = cw721_contract.owner_of(&app.wrap(), "1", true).unwrap();
+        assert!(nft_resp.approvals.is_empty());
+        contract
+            .buy_listed_ticket(&mut app, bob(), "1", &coins(200, ARCH_DEMON))
+            .unwrap_err();
+    }
+
+    #[test]
+    fn refund_after_resale_should_works() {
+        let mut app = mock_app(|router, _api, storage| {
+            for player in [alice(), bob(), parent()] {
+                router
+                    .bank
+                    .init_balance(storage, &player, coins(300, ARCH_DEMON))
+                    .unwrap();
+            }
+        });
+
+        let code_id = LotteryCodeId::store_code(&mut app);
+        let expiration = Utc::now().timestamp() as u64;
+        let contract = code_id
+            .instantiate(
+                &mut app,
+                owner(),
+                "LOTTERY",
+                "LOTTER",
+                100,
+                ARCH_DEMON,
+                "hour",
+                expiration,
+                WinnerSelection::Jackpot {},
+                3,
+                "Lottery label",
+            )
+            .unwrap();
+
+        for player in [alice(), bob()] {
+            contract
+                .buy_ticket(&mut app, player, ARCH_DEMON, None, &coins(100, ARCH_DEMON))
+                .unwrap();
+        }
+        contract
+            .list_ticket(&mut app, alice(), "1", coin(150, ARCH_DEMON))
+            .unwrap();
+        contract
+            .buy_listed_ticket(&mut app, parent(), "1", &coins(150, ARCH_DEMON))
+            .unwrap();
+
+        contract.cancel_lottery(&mut app, owner()).unwrap();
+
+        // the ticket is refunded to its holder, not to the player who bought it first
+        let err = contract.refund(&mut app, alice()).unwrap_err();
+        assert_eq!(
+            ContractError::NothingToRefund { player: alice() },
+            err.downcast().unwrap()
+        );
+        contract.refund(&mut app, parent()).unwrap();
+        let err = contract.refund(&mut app, parent()).unwrap_err();
+        assert_eq!(
+            ContractError::AlreadyRefunded { player: parent() },
+            err.downcast().unwrap()
+        );
+
+        // a refunded ticket isn't refunded again after a transfer
+        contract
+            .transfer_ticket(&mut app, parent(), bob().to_string(), "1".to_string())
+            .unwrap();
+        contract.refund(&mut app, bob()).unwrap();
+
+        let balance = |app: &App, addr: Addr| {
+            app.wrap()
+                .query_balance(addr, ARCH_DEMON)
+                .unwrap()
+                .amount
+                .u128()
+        };
+        assert_eq!(balance(&app, alice()), 350);
+        assert_eq!(balance(&app, parent()), 250);
+        assert_eq!(balance(&app, bob()), 300);
+        assert_eq!(balance(&app, contract.addr()), 0);
+    }
+
+    #[test]
+    fn burned_number_pick_ticket_should_works() {
+        let mut app = mock_app(|router, _api, storage| {
+            for player in [alice(), bob()] {
+                router
+                    .bank
+                    .init_balance(storage, &player, coins(100, ARCH_DEMON))
+                    .unwrap();
+            }
+        });
+
+        let code_id = LotteryCodeId::store_code(&mut app);
+        let msg = InstantiateMsg::new(
+            "LOTTERY",
+            "LOTTER",
+            100,
+            ARCH_DEMON,
+            "hour",
+            Utc::now().timestamp() as u64,
+            WinnerSelection::NumberPick {
+                pick: 1,
+                max_number: 2,
+                tiers: vec![PrizeTier {
+                    matches: 1,
+                    pct: 100,
+                }],
+            },
+            2,
+            None,
+        );
+        let contract: LotteryContract = app
+            .instantiate_contract(code_id.into(), owner(), &msg, &[], "Lottery label", None)
+            .unwrap()
+            .into();
+
+        for (player, number) in [(alice(), 1), (bob(), 2)] {
+            contract
+                .buy_ticket_with_numbers(
+                    &mut app,
+                    player,
+                    ARCH_DEMON,
+                    vec![number],
+                    &coins(100, ARCH_DEMON),
+                )
+                .unwrap();
+        }
+        for (player, ticket_id) in [(alice(), "1"), (bob(), "2")] {
+            contract
+                .burn_ticket(&mut app, player, ticket_id.to_string())
+                .unwrap();
+        }
+
+        // the prize of a burned ticket goes to its buyer
+        contract.draw_lottery(&mut app, owner()).unwrap();
+
+        let winner = &contract.winner(&app).unwrap().winner[0];
+        let balance = |app: &App, addr: &Addr| {
+            app.wrap()
+                .query_balance(addr, ARCH_DEMON)
+                .unwrap()
+                .amount
+                .u128()
+        };
+        assert_eq!(balance(&app, &winner.address), 200);
+        assert_eq!(balance(&app, &contract.addr()), 0);
+    }
+
+    #[test]
+    fn scratch_outcome_fixed_at_purchase_should_works() {
+        let buyers = [alice(), bob(), parent()];
+        // the same purchases, settled `delay` blocks later, some tickets burned meanwhile
+        let play = |delay: u64, burn: bool| {
+            let mut app = mock_app(|router, _api, storage| {
+                for (addr, amount) in [
+                    (alice(), 100),
+                    (bob(), 100),
+                    (parent(), 100),
+                    (owner(), 170),
+                ] {
+                    router
+                        .bank
+                        .init_balance(storage, &addr, coins(amount, ARCH_DEMON))
+                        .unwrap();
+                }
+            });
+            let code_id = LotteryCodeId::store_code(&mut app);
+            let prizes = [100u128, 50, 20]
+                .map(|amount| ScratchPrize {
+                    amount: amount.into(),
+                    count: 1,
+                })
+                .to_vec();
+            let msg = InstantiateMsg::new(
+                "LOTTERY",
+                "LOTTER",
+                100,
+                ARCH_DEMON,
+                "hour",
+                app.block_info().time.seconds() + 3600,
+                WinnerSelection::Scratch { prizes },
+                6,
+                None,
+            );
+            let contract: LotteryContract = app
+                .instantiate_contract(
+                    code_id.into(),
+                    owner(),
+                    &msg,
+                    &coins(170, ARCH_DEMON),
+                    "Lottery label",
+                    None,
+                )
+                .unwrap()
+                .into();
+
+            for (idx, buyer) in buyers.iter().enumerate() {
+                contract
+                    .buy_ticket(
+                        &mut app,
+                        buyer.clone(),
+                        ARCH_DEMON,
+                        None,
+                        &coins(100, ARCH_DEMON),
+                    )
+                    .unwrap();
+                if burn {
+                    contract
+                        .burn_ticket(&mut app, buyer.clone(), (idx + 1).to_string())
+                        .unwrap();
+                }
+            }
+
+            for _ in 0..delay {
+                app.update_block(next_block);
+            }
+            contract.settle_ticket(&mut app, owner()).unwrap();
+
+            let results = contract.scratch(&app).unwrap().results;
+            (app, results)
+        };
+
+        // settling later doesn't change the outcome, a settler can't retry for another one
+        let (_, results) = play(1, false);
+        let (_, later_results) = play(25, false);
+        assert_eq!(results, later_results);
+
+        // the prize of a burned ticket goes to its buyer
+        let (app, burned_results) = play(1, true);
+        assert_eq!(results, burned_results);
+        assert!(results.iter().any(|result| result.prize.is_some()));
+        for (buyer, result) in buyers.iter().zip(&results) {
+            let prize = result.prize.as_ref().map_or(0, |prize| prize.amount.u128());
+            let balance = app.wrap().query_balance(buyer, ARCH_DEMON).unwrap();
+            assert_eq!(balance.amount.u128(), prize);
+        }
+    }
+
+    #[test]
+    fn claim_lottery_should_fail() {
+        let mut app = mock_app(|router, _api, storage| {
+            router
+                .bank
+                .init_balance(storage, &alice(), coins(100, ARCH_DEMON))
+                .unwrap();
+        });
+
+        let code_id = LotteryCodeId::store_code(&mut app);
+        let expiration = app.block_info().time.seconds() + 3600;
+        let new_lottery = |app: &mut App| {
+            code_id
+                .instantiate(
+                    app,
+                    owner(),
+                    "LOTTERY",
+                    "LOTTER",
+                    100,
+                    ARCH_DEMON,
+                    "hour",
+                    expiration,
+                    WinnerSelection::Jackpot {},
+                    1,
+                    "Lottery label",
+                )
+                .unwrap()
+        };
+        let empty = new_lottery(&mut app);
+        let burned = new_lottery(&mut app);
+
+        burned
+            .buy_ticket(&mut app, alice(), ARCH_DEMON, None, &coins(100, ARCH_DEMON))
+            .unwrap();
+        burned
+            .burn_ticket(&mut app, alice(), "1".to_string())
+            .unwrap();
+        burned.draw_lottery(&mut app, owner()).unwrap();
+
+        // nobody holds a burned winning ticket, its prize stays locked
+        let err = burned.claim_lottery(&mut app, alice()).unwrap_err();
+        assert_eq!(ContractError::Unauthorized {}, err.downcast().unwrap());
+        let err = burned
+            .withdraw(&mut app, owner(), 1, ARCH_DEMON, None)
+            .unwrap_err();
+        assert_eq!(
+            ContractError::ExcessExceeded {
+                excess: coin(0, ARCH_DEMON)
+            },
+            err.downcast().unwrap()
+        );
+
+        // a lottery drawn without players has nothing to claim
+        app.update_block(|block| block.time = block.time.plus_seconds(3601));
+        empty.draw_lottery(&mut app, owner()).unwrap();
+        let err = empty.claim_lottery(&mut app, alice()).unwrap_err();
+        assert_eq!(ContractError::NothingToClaim {}, err.downcast().unwrap());
+    }
 }
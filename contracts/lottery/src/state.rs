@@ -1,8 +1,9 @@
-use std::str::FromStr;
+use std::{fmt, str::FromStr};
 
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{Addr, Coin, Timestamp};
+use cosmwasm_std::{Addr, Coin, Decimal, Empty, StdError, StdResult, Timestamp, Uint128};
 use cw_storage_plus::{Item, Map};
+use cw_utils::Expiration;
 
 use crate::{ContractError, Extension};
 
@@ -12,7 +13,7 @@ pub struct State {
     pub symbol: String,
     pub height: u64,
     pub created_at: Timestamp,
-    pub expiratoin: Timestamp,
+    pub expiration: Timestamp,
     pub unit_price: Coin,
     pub period: LotteryPeriod,
     pub selection: WinnerSelection,
@@ -22,12 +23,34 @@ pub struct State {
     pub seed: String,
     pub winner: Vec<WinnerInfo>,
     pub extension: Extension,
+    #[serde(default)]
+    pub pricing: Pricing,
 }
 
 impl State {
+    /// The price of `quantity` tickets bought at `at`
+    pub fn quote(&self, quantity: u32, at: Timestamp) -> Coin {
+        let unit_price = self
+            .pricing
+            .phases
+            .iter()
+            .rev()
+            .find(|phase| phase.starts_at <= at.seconds())
+            .map_or(self.unit_price.amount, |phase| phase.amount);
+
+        Coin {
+            denom: self.unit_price.denom.clone(),
+            amount: unit_price * Uint128::from(self.pricing.paid_tickets(quantity)),
+        }
+    }
+
     pub fn is_closed(&self) -> bool {
         self.status == GameStatus::Closed
     }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.status == GameStatus::Cancelled
+    }
 }
 
 #[cw_serde]
@@ -40,17 +63,103 @@ pub enum WinnerSelection {
         winner_count: u32,
         max_winner_count: Option<u32>,
     },
+    // Players pick `pick` numbers from 1 to `max_number`, and the prizes are
+    // shared by match tiers. Ex: pick 6 of 49
+    NumberPick {
+        pick: u8,
+        max_number: u8,
+        tiers: Vec<PrizeTier>,
+    },
+    // Every ticket is an instant win or loss, with fixed prizes from a table funded
+    // at instantiation. Ex: 1 prize of 1000 and 10 prizes of 100
+    Scratch {
+        prizes: Vec<ScratchPrize>,
+    },
 }
 
 impl WinnerSelection {
     pub fn is_jackpot(&self) -> bool {
         matches!(self, Self::Jackpot {})
     }
+
+    pub fn is_number_pick(&self) -> bool {
+        matches!(self, Self::NumberPick { .. })
+    }
+
+    pub fn is_scratch(&self) -> bool {
+        matches!(self, Self::Scratch { .. })
+    }
+
+    /// The prize tier of a ticket matching `matches` winning numbers
+    pub fn tier_of(&self, matches: u8) -> Option<&PrizeTier> {
+        match self {
+            Self::NumberPick { tiers, .. } => tiers.iter().find(|tier| tier.matches == matches),
+            _ => None,
+        }
+    }
 }
+
+/// Ticket price schedule, the `unit_price` applies until the first phase starts
+#[cw_serde]
+#[derive(Default)]
+pub struct Pricing {
+    /// In ascending start time. Ex: an early bird `unit_price`, then standard and last call phases
+    pub phases: Vec<PricePhase>,
+    /// Bulk discounts. Ex: buy 10, pay for 9
+    pub tiers: Vec<QuantityTier>,
+}
+
+impl Pricing {
+    /// How many of `quantity` tickets are paid, the largest bundles are taken first
+    pub fn paid_tickets(&self, quantity: u32) -> u64 {
+        let mut tiers: Vec<_> = self.tiers.iter().collect();
+        tiers.sort_by_key(|tier| std::cmp::Reverse(tier.quantity));
+
+        let mut left = quantity;
+        let mut paid = 0;
+        for tier in tiers {
+            paid += u64::from(left / tier.quantity) * u64::from(tier.paid);
+            left %= tier.quantity;
+        }
+
+        paid + u64::from(left)
+    }
+}
+
+/// The ticket price from `starts_at`, in seconds
+#[cw_serde]
+pub struct PricePhase {
+    pub starts_at: u64,
+    pub amount: Uint128,
+}
+
+/// Every bundle of `quantity` tickets bought together costs `paid` tickets
+#[cw_serde]
+pub struct QuantityTier {
+    pub quantity: u32,
+    pub paid: u32,
+}
+
+/// `count` prizes of `amount` in the unit price's denom
+#[cw_serde]
+pub struct ScratchPrize {
+    pub amount: Uint128,
+    pub count: u32,
+}
+
+/// `pct` percent of the pot is shared by the tickets matching `matches` numbers.
+/// A tier without winners rolls over into the next round
+#[cw_serde]
+pub struct PrizeTier {
+    pub matches: u8,
+    pub pct: u8,
+}
+
 #[cw_serde]
 pub enum GameStatus {
     Activing,
     Closed,
+    Cancelled,
 }
 
 #[cw_serde]
@@ -94,6 +203,20 @@ impl FromStr for LotteryPeriod {
     }
 }
 
+impl fmt::Display for LotteryPeriod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let period = match self {
+            Self::Hour {} => "hour",
+            Self::Day {} => "day",
+            Self::Week {} => "week",
+            Self::Month {} => "month",
+            Self::Year {} => "year",
+        };
+
+        f.write_str(period)
+    }
+}
+
 impl LotteryPeriod {
     pub fn is_hour(&self) -> bool {
         matches!(self, Self::Hour {})
@@ -139,6 +262,29 @@ pub struct PlayerInfo {
     pub height: u64,
     pub ticket_id: String,
     pub memo: Option<String>,
+    /// The numbers picked in a number-pick lottery, in ascending order
+    pub numbers: Option<Vec<u8>>,
+    /// The share of the payment for the ticket, the `unit_price` when missing
+    pub paid: Option<Coin>,
+}
+
+impl PlayerInfo {
+    /// How many of the `winning_numbers` the ticket picked
+    pub fn matches(&self, winning_numbers: &[u8]) -> u8 {
+        self.numbers.as_ref().map_or(0, |numbers| {
+            numbers
+                .iter()
+                .filter(|number| winning_numbers.contains(number))
+                .count() as u8
+        })
+    }
+
+    /// Tickets are numbered by purchase order, starting from 1
+    pub fn ticket_index(&self) -> StdResult<u64> {
+        self.ticket_id
+            .parse()
+            .map_err(|_| StdError::parse_err("u64", &self.ticket_id))
+    }
 }
 
 #[cw_serde]
@@ -146,6 +292,133 @@ pub struct WinnerInfo {
     pub address: Addr,
     pub prize: Vec<Coin>,
     pub ticket_id: String,
+    /// cw721 tokens won along `prize`
+    #[serde(default)]
+    pub nft_prizes: Vec<NftPrize>,
+}
+
+#[cw_serde]
+pub struct NftPrize {
+    pub collection: Addr,
+    pub token_id: String,
+}
+
+/// One `hash::update` step of the seed chain, recorded for every ticket bought
+#[cw_serde]
+pub struct SeedContribution {
+    pub player: Addr,
+    pub counter: u64,
+    pub height: u64,
+    pub memo_digest: Option<String>,
+}
+
+/// The `hash::finalize` inputs and the numbers the winners were chosen by
+#[cw_serde]
+pub struct DrawRecord {
+    pub sender: Addr,
+    pub height: u64,
+    pub lucky_phrase: Option<String>,
+    pub seed: String,
+    pub random_numbers: Vec<u64>,
+    pub winner_indices: Vec<u64>,
+    /// Picked by the random numbers in a number-pick lottery
+    pub winning_numbers: Option<Vec<u8>>,
+    /// Ticket index winning the progressive jackpot, by the two random numbers following `random_numbers`
+    pub jackpot_index: Option<u64>,
+}
+
+/// Who can buy tickets of a private lottery
+#[cw_serde]
+pub enum Allowlist {
+    /// Members are stored in `ALLOWLIST_MEMBERS`
+    Addresses {},
+    Group {
+        group: Addr,
+    },
+}
+
+/// Holders of `min_tokens` tokens of the `collection` can buy tickets
+#[cw_serde]
+pub struct NftGate {
+    pub collection: Addr,
+    pub min_tokens: u32,
+    pub once_per_token: bool,
+}
+
+/// The outcome of a scratch ticket, settled by the next purchase
+#[cw_serde]
+pub struct ScratchResult {
+    pub ticket_id: String,
+    pub player: Addr,
+    pub prize: Option<Coin>,
+}
+
+/// The reward of the referrer of a ticket
+#[cw_serde]
+pub struct Referral {
+    pub reward: Coin,
+    pub paid: bool,
+}
+
+/// Funds added to the pot without a ticket, summed by sponsor
+#[cw_serde]
+pub struct Sponsorship {
+    pub sponsor: Addr,
+    /// Shown along the contribution, the latest one given wins
+    pub name: Option<String>,
+    pub amount: Coin,
+}
+
+/// Share of the pot contributed to the platform's progressive jackpot at draw.
+/// The jackpot is won by a secondary draw among the tickets.
+#[cw_serde]
+pub struct ProgressiveJackpot {
+    pub contribution_bps: u16,
+    /// One draw in `odds` wins the jackpot, the draw of a yearly lottery always does
+    pub odds: u32,
+}
+
+/// A ticket for sale, escrowed by the lottery's cw721 approval until sold or cancelled
+#[cw_serde]
+pub struct Listing {
+    pub seller: Addr,
+    pub price: Coin,
+    pub listed_at: Timestamp,
+}
+
+/// Proposed to take over the ownership until `expiry`
+#[cw_serde]
+pub struct PendingOwner {
+    pub address: Addr,
+    pub expiry: Expiration,
+}
+
+/// Buying and drawing are stopped until `expires_at`, or until unpaused
+#[cw_serde]
+pub struct PauseInfo {
+    pub reason: String,
+    pub paused_by: Addr,
+    pub paused_at: Timestamp,
+    pub expires_at: Option<Timestamp>,
+}
+
+impl PauseInfo {
+    pub fn is_active(&self, now: Timestamp) -> bool {
+        self.expires_at.is_none_or(|expires_at| now < expires_at)
+    }
+}
+
+/// The creator's share of the pot, paid when the lottery is drawn
+#[cw_serde]
+pub struct Commission {
+    pub recipient: Addr,
+    pub rate: Decimal,
+}
+
+impl Commission {
+    pub fn of(&self, pot: &Coin) -> Coin {
+        Coin::new(pot.amount.mul_floor(self.rate).u128(), &pot.denom)
+    }
 }
 
 /// Storage
@@ -154,6 +427,34 @@ pub const STATE: Item<State> = Item::new("state");
 pub const PLAYERS: Map<&Addr, PlayerInfo> = Map::new("players");
 pub const PLAYER_COUNTER: Item<u64> = Item::new("player_counter");
 pub const IDX_2_ADDR: Map<u64, Addr> = Map::new("idx_2_addr");
+pub const PLATFORM: Item<Addr> = Item::new("platform");
+pub const POT_OWED: Item<Uint128> = Item::new("pot_owed"); // ticket payments and sponsorships not refunded yet
+pub const REFUNDS: Map<&Addr, Coin> = Map::new("refunds"); // (refunded address, total refunded)
+pub const REFUNDED_TICKETS: Map<&str, Empty> = Map::new("refunded_tickets");
+pub const SEED_CONTRIBUTIONS: Map<u64, SeedContribution> = Map::new("seed_contributions");
+pub const DRAW_RECORD: Item<DrawRecord> = Item::new("draw_record");
+pub const COMMISSION: Item<Commission> = Item::new("commission");
+pub const ALLOWLIST: Item<Allowlist> = Item::new("allowlist");
+pub const ALLOWLIST_MEMBERS: Map<&Addr, Empty> = Map::new("allowlist_members");
+pub const NFT_GATE: Item<NftGate> = Item::new("nft_gate");
+pub const SCRATCH_REMAINING: Item<Vec<u32>> = Item::new("scratch_remaining"); // prizes left by level
+pub const SCRATCH_PENDING: Item<u64> = Item::new("scratch_pending"); // index of the first ticket to settle
+pub const SCRATCH_BLOCK_SEEDS: Map<u64, String> = Map::new("scratch_block_seeds"); // (height, seed after the block's last purchase)
+pub const SCRATCH_RESULTS: Map<u64, ScratchResult> = Map::new("scratch_results"); // (ticket index, outcome)
+pub const NFT_PRIZES: Map<(&Addr, &str), Addr> = Map::new("nft_prizes"); // ((collection, token id), depositor)
+pub const CLAIMED: Item<Empty> = Item::new("claimed"); // set by the first claim
+pub const PRIZE_WITHDRAWN: Item<Uint128> = Item::new("prize_withdrawn"); // by the winner, in the pot denom
+pub const LISTINGS: Map<&str, Listing> = Map::new("listings"); // (token id, listing)
+pub const RESALE_ROYALTY_BPS: Item<u16> = Item::new("resale_royalty_bps");
+pub const PENDING_OWNER: Item<PendingOwner> = Item::new("pending_owner");
+pub const PAUSE: Item<PauseInfo> = Item::new("pause");
+pub const PROGRESSIVE_JACKPOT: Item<ProgressiveJackpot> = Item::new("progressive_jackpot");
+pub const REFERRAL_BPS: Item<u16> = Item::new("referral_bps");
+pub const REFERRERS: Map<&Addr, Addr> = Map::new("referrers"); // (player, referrer)
+pub const REFERRALS: Map<(&Addr, &Addr), Referral> = Map::new("referrals"); // ((referrer, player), reward)
+pub const SPONSORS: Map<&Addr, Sponsorship> = Map::new("sponsors");
+pub const SPONSORED: Item<Uint128> = Item::new("sponsored"); // by all the sponsors, in the pot denom
+pub const USED_GATE_TOKENS: Map<&str, Addr> = Map::new("used_gate_tokens"); // (token id, player)
 
 // pub const CLAIMS: Claims = Claims::new("claims");
 
@@ -189,5 +490,11 @@ mod tests {
         let period_y = year.parse().unwrap();
         assert_eq!(LotteryPeriod::Year {}, period_y);
         assert!(period_y.is_year());
+
+        assert_eq!(period_m.to_string(), "month");
+        assert_eq!(
+            period_y.to_string().parse::<LotteryPeriod>().unwrap(),
+            period_y
+        );
     }
 }
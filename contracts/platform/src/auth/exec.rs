@@ -0,0 +1,111 @@
+use cosmwasm_std::{
+    ensure, Addr, Coin, Deps, Empty, Env, MessageInfo, StdResult, Storage, Uint128,
+};
+use cw4::Cw4Contract;
+use cw_utils::must_pay;
+
+use crate::{
+    state::{Role, CREATION_CONFIG, OWNER, PAUSE, ROLE_GROUPS, ROLE_MEMBERS},
+    ContractError,
+};
+
+pub type UnitResult = Result<(), ContractError>;
+
+pub fn validate_role(deps: Deps, role: Role, sender: &Addr) -> UnitResult {
+    ensure!(
+        has_role(deps, role, sender)?,
+        ContractError::Unauthorized {}
+    );
+
+    Ok(())
+}
+
+/// Unauthorized once the ownership is renounced
+pub fn validate_owner(storage: &dyn Storage, sender: &Addr) -> UnitResult {
+    ensure!(
+        OWNER.may_load(storage)?.as_ref() == Some(sender),
+        ContractError::Unauthorized {}
+    );
+
+    Ok(())
+}
+
+/// Admins and guardians can pause
+pub fn validate_guardian(deps: Deps, sender: &Addr) -> UnitResult {
+    ensure!(
+        has_role(deps, Role::Admin, sender)? || has_role(deps, Role::Guardian, sender)?,
+        ContractError::Unauthorized {}
+    );
+
+    Ok(())
+}
+
+/// A pause lifts itself once it expires
+pub fn validate_not_paused(storage: &dyn Storage, env: &Env) -> UnitResult {
+    if let Some(pause) = PAUSE.may_load(storage)? {
+        ensure!(
+            !pause.is_active(env.block.time),
+            ContractError::Paused {
+                reason: pause.reason
+            }
+        );
+    }
+
+    Ok(())
+}
+
+/// The deposit to hold for each of the `count` lotteries created by the sender.
+/// Creators are trusted without a deposit, anyone else pays it if creation is open.
+pub fn validate_creation(
+    deps: Deps,
+    info: &MessageInfo,
+    count: usize,
+) -> Result<Option<Coin>, ContractError> {
+    if has_role(deps, Role::Creator, &info.sender)? {
+        return Ok(None);
+    }
+
+    let deposit = CREATION_CONFIG
+        .may_load(deps.storage)?
+        .and_then(|config| config.deposit)
+        .ok_or(ContractError::Unauthorized {})?;
+
+    let expected = Coin {
+        denom: deposit.denom.clone(),
+        amount: deposit.amount.checked_mul(Uint128::from(count as u128))?,
+    };
+    let paid = must_pay(info, &deposit.denom)?;
+    ensure!(
+        paid == expected.amount,
+        ContractError::InvalidDeposit { expected }
+    );
+
+    Ok(Some(deposit))
+}
+
+/// Whether `address` is granted `role`, directly or through the role's cw4 group
+pub fn has_role(deps: Deps, role: Role, address: &Addr) -> StdResult<bool> {
+    if role == Role::Admin && OWNER.may_load(deps.storage)?.as_ref() == Some(address) {
+        return Ok(true);
+    }
+
+    if ROLE_MEMBERS.has(deps.storage, (role.as_str(), address)) {
+        return Ok(true);
+    }
+
+    match ROLE_GROUPS.may_load(deps.storage, role.as_str())? {
+        Some(group) => Ok(Cw4Contract::new(group)
+            .is_member(&deps.querier, address, None)?
+            .is_some()),
+        None => Ok(false),
+    }
+}
+
+/// The owner creates and draws lotteries until the admin hands these roles over
+pub fn grant_owner_roles(storage: &mut dyn Storage, owner: &Addr) -> StdResult<()> {
+    for role in [Role::Creator, Role::Drawer] {
+        ROLE_MEMBERS.save(storage, (role.as_str(), owner), &Empty {})?;
+    }
+
+    Ok(())
+}
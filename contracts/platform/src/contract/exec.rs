@@ -1,18 +1,42 @@
 use cosmwasm_std::coin;
-use cosmwasm_std::{attr, to_binary, Addr, DepsMut, Env, MessageInfo, Response, SubMsg, WasmMsg};
+use cosmwasm_std::{
+    attr, ensure, instantiate2_address, to_json_binary, Addr, BankMsg, Binary, Coin, Decimal, Deps,
+    DepsMut, Empty, Env, MessageInfo, Order, Response, StdError, StdResult, Storage, SubMsg,
+    WasmMsg,
+};
+use cw_storage_plus::Bound;
+use cw_utils::Expiration;
+use sha2::{Digest, Sha256};
 
 use lottery::msg::ExecuteMsg as LotteryExecuteMsg;
 use lottery::msg::InstantiateMsg as LotteryInstantiateMsg;
-use lottery::state::WinnerSelection;
+use lottery::msg::LotteryHookMsg;
+use lottery::ContractError as LotteryContractError;
+use lottery::{
+    auth::exec::{
+        validate_acceptance, validate_owner_proposal, validate_pause, validate_progressive_jackpot,
+    },
+    state::ProgressiveJackpot,
+};
 
-use crate::state::{LotteryInfo, PENDING_LOTTERY};
+use crate::state::{
+    add_coins, save_lottery_code, take_coin, CreationConfig, JackpotEvent, JackpotRecord,
+    LotteryCode, LotteryInfo, LotteryStatus, PlayerEntry, PlayerWin, Role, CREATION_CONFIG,
+    CREATOR_TOTALS, JACKPOT, JACKPOT_CONFIG, JACKPOT_HISTORY, LOTTERIES, LOTTERY_ROUNDS,
+    NEXT_JACKPOT_RECORD_ID, NEXT_REPLY_ID, OWNER, PAUSE, PENDING_LOTTERIES, PENDING_OWNER,
+    PLAYER_ENTRIES, PLAYER_TOTALS, PLAYER_WINS, REFERRER_TOTALS, RESALE_ROYALTY_BPS, ROLE_GROUPS,
+    ROLE_MEMBERS, ROLLOVERS, SLASHED_DEPOSITS,
+};
 use crate::{
-    msg::ExecuteMsg,
-    state::{OWNER, STATE},
+    auth::exec::{
+        validate_creation, validate_guardian, validate_not_paused, validate_owner, validate_role,
+    },
+    msg::{CreateLotteryMsg, ExecuteMsg, LotteryBatchData},
+    state::STATE,
     ContractError,
 };
 
-use super::CREATE_LOTTERY_REPLY_ID;
+use super::{CREATE_LOTTERY_REPLY_ID_START, DEFAULT_LIMIT, MAX_LIMIT};
 
 pub fn execute(
     deps: DepsMut,
@@ -25,7 +49,7 @@ pub fn execute(
     match msg {
         CreateLottery {
             name,
-            symbol: symobl,
+            symbol,
             unit_price_amount,
             unit_price_denom,
             period,
@@ -33,80 +57,285 @@ pub fn execute(
             selection,
             max_players,
             label,
+            allowlist,
+            nft_gate,
+            referral_bps,
+            pricing,
         } => create_lottery(
             deps,
             &env,
             &info,
-            &name,
-            &symobl,
-            unit_price_amount,
-            &unit_price_denom,
-            &period,
-            expiration,
-            selection,
-            max_players,
-            &label,
+            CreateLotteryMsg {
+                name,
+                symbol,
+                unit_price_amount,
+                unit_price_denom,
+                period,
+                expiration,
+                selection,
+                max_players,
+                label,
+                allowlist,
+                nft_gate,
+                referral_bps,
+                pricing,
+            },
         ),
+        CreateLotteries { lotteries } => create_lotteries(deps, &env, &info, lotteries),
 
         DrawLottery { lottery } => draw_lottery(deps, &env, &info, &lottery),
+        CancelLottery { lottery } => cancel_lottery(deps, &env, &info, &lottery),
+        UpdateAllowlist {
+            lottery,
+            add,
+            remove,
+        } => update_allowlist(deps, &env, &info, &lottery, add, remove),
+        LotteryHook(hook) => lottery_hook(deps, &env, &info, hook),
+        UpdateLotteryCode { code_id } => update_lottery_code(deps, &env, &info, code_id),
+        MigrateLotteries {
+            code_id,
+            msg,
+            start_after,
+            limit,
+        } => migrate_lotteries(deps, &env, &info, code_id, msg, start_after, limit),
+        UpdateCreationConfig {
+            deposit,
+            commission,
+        } => update_creation_config(deps, &env, &info, deposit, commission),
+        WithdrawSlashed { recipient } => withdraw_slashed(deps, &env, &info, recipient),
+        UpdateJackpotConfig { config } => update_jackpot_config(deps, &env, &info, config),
+        UpdateResaleRoyalty { bps } => update_resale_royalty(deps, &env, &info, bps),
+        Pause { reason, expires_at } => pause(deps, &env, &info, reason, expires_at),
+        Unpause {} => unpause(deps, &env, &info),
+        PauseLotteries {
+            reason,
+            expires_at,
+            start_after,
+            limit,
+        } => pause_lotteries(deps, &env, &info, reason, expires_at, start_after, limit),
+        UnpauseLotteries { start_after, limit } => {
+            unpause_lotteries(deps, &env, &info, start_after, limit)
+        }
+        ProposeOwner { new_owner, expiry } => propose_owner(deps, &env, &info, new_owner, expiry),
+        AcceptOwnership {} => accept_ownership(deps, &env, &info),
+        CancelOwnershipTransfer {} => cancel_ownership_transfer(deps, &env, &info),
+        RenounceOwnership {} => renounce_ownership(deps, &env, &info),
+        GrantRole { role, address } => grant_role(deps, &env, &info, role, &address),
+        RevokeRole { role, address } => revoke_role(deps, &env, &info, role, &address),
+        SetRoleGroup { role, group } => set_role_group(deps, &env, &info, role, group),
     }
 }
 
-#[allow(clippy::too_many_arguments)]
 pub fn create_lottery(
-    deps: DepsMut,
+    mut deps: DepsMut,
     env: &Env,
     info: &MessageInfo,
-    name: &str,
-    symobl: &str,
-    unit_price_amount: u128,
-    unit_price_denom: &str,
-    period: &str,
-    expiration: u64,
-    selection: WinnerSelection,
-    max_players: u64,
-    label: &str,
+    lottery: CreateLotteryMsg,
 ) -> Result<Response, ContractError> {
     let sender = &info.sender;
-    let state = STATE.load(deps.storage)?;
 
-    let init_lottery_msg = LotteryInstantiateMsg::new(
+    validate_not_paused(deps.storage, env)?;
+
+    let deposit = validate_creation(deps.as_ref(), info, 1)?;
+
+    let msg = instantiate_lottery(deps.branch(), env, sender, deposit, lottery)?;
+    let attrs = vec![attr("action", "create_lottery"), attr("sender", sender)];
+
+    Ok(Response::new().add_submessage(msg).add_attributes(attrs))
+}
+
+pub fn create_lotteries(
+    mut deps: DepsMut,
+    env: &Env,
+    info: &MessageInfo,
+    lotteries: Vec<CreateLotteryMsg>,
+) -> Result<Response, ContractError> {
+    ensure!(!lotteries.is_empty(), ContractError::NoLotteriesToCreate {});
+
+    let sender = &info.sender;
+    let count = lotteries.len();
+
+    validate_not_paused(deps.storage, env)?;
+
+    let deposit = validate_creation(deps.as_ref(), info, count)?;
+
+    let msgs = lotteries
+        .into_iter()
+        .map(|lottery| instantiate_lottery(deps.branch(), env, sender, deposit.clone(), lottery))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let attrs = vec![
+        attr("action", "create_lotteries"),
+        attr("sender", sender),
+        attr("count", count.to_string()),
+    ];
+
+    Ok(Response::new().add_submessages(msgs).add_attributes(attrs))
+}
+
+/// Register the lottery at its `Instantiate2` address and build the instantiation.
+/// The reply checks that the lottery landed at the predicted address.
+fn instantiate_lottery(
+    deps: DepsMut,
+    env: &Env,
+    creator: &Addr,
+    deposit: Option<Coin>,
+    lottery: CreateLotteryMsg,
+) -> Result<SubMsg, ContractError> {
+    let CreateLotteryMsg {
         name,
-        symobl,
+        symbol,
         unit_price_amount,
         unit_price_denom,
         period,
         expiration,
+        selection,
+        max_players,
+        label,
+        allowlist,
+        nft_gate,
+        referral_bps,
+        pricing,
+    } = lottery;
+
+    ensure!(
+        !selection.is_scratch(),
+        ContractError::ScratchNotSupported {}
+    );
+
+    let mut state = STATE.load(deps.storage)?;
+
+    let round = LOTTERY_ROUNDS
+        .may_load(deps.storage, (creator, &symbol))?
+        .unwrap_or_default()
+        + 1;
+    LOTTERY_ROUNDS.save(deps.storage, (creator, &symbol), &round)?;
+
+    // The next number-pick round starts with the rollover of the previous ones
+    let mut rollovers = ROLLOVERS
+        .may_load(deps.storage, (creator, &symbol))?
+        .unwrap_or_default();
+    let rollover = if selection.is_number_pick() {
+        take_coin(&mut rollovers, &unit_price_denom)
+    } else {
+        None
+    };
+    if rollover.is_some() {
+        ROLLOVERS.save(deps.storage, (creator, &symbol), &rollovers)?;
+    }
+
+    let salt = lottery_salt(creator, &symbol, round);
+    let lottery_addr = lottery_address(deps.as_ref(), env, state.lottery_code_id, &salt)?;
+
+    let commission = CREATION_CONFIG
+        .may_load(deps.storage)?
+        .unwrap_or_default()
+        .commission;
+
+    let mut init_lottery_msg = LotteryInstantiateMsg::new(
+        &name,
+        &symbol,
+        unit_price_amount,
+        &unit_price_denom,
+        &period,
+        expiration,
         selection.clone(),
         max_players,
+        Some(env.contract.address.to_string()),
     );
+    if !commission.is_zero() {
+        init_lottery_msg = init_lottery_msg.with_commission(creator, commission);
+    }
+    if let Some(allowlist) = allowlist {
+        init_lottery_msg = init_lottery_msg.with_allowlist(allowlist);
+    }
+    if let Some(nft_gate) = nft_gate {
+        init_lottery_msg = init_lottery_msg.with_nft_gate(nft_gate);
+    }
+    if let Some(referral_bps) = referral_bps {
+        init_lottery_msg = init_lottery_msg.with_referral_bps(referral_bps);
+    }
+    if let Some(jackpot) = JACKPOT_CONFIG.may_load(deps.storage)? {
+        init_lottery_msg = init_lottery_msg.with_progressive_jackpot(jackpot);
+    }
+    if let Some(bps) = RESALE_ROYALTY_BPS.may_load(deps.storage)? {
+        init_lottery_msg = init_lottery_msg.with_resale_royalty_bps(bps);
+    }
+    if let Some(pricing) = pricing {
+        init_lottery_msg = init_lottery_msg.with_pricing(pricing);
+    }
 
-    let msg = WasmMsg::Instantiate {
+    let msg = WasmMsg::Instantiate2 {
         admin: Some(env.contract.address.to_string()),
         code_id: state.lottery_code_id,
-        msg: to_binary(&init_lottery_msg)?,
-        funds: vec![],
-        label: label.to_owned(),
+        label,
+        msg: to_json_binary(&init_lottery_msg)?,
+        funds: rollover.clone().into_iter().collect(),
+        salt,
     };
 
-    let msg = SubMsg::reply_on_success(msg, CREATE_LOTTERY_REPLY_ID);
-    let attrs = vec![attr("action", "create_lottery"), attr("sender", sender)];
-
     let lottery = LotteryInfo {
-        name: name.to_owned(),
-        symbol: symobl.to_owned(),
+        name,
+        symbol,
         height: env.block.height,
         created_at: env.block.time,
-        unit_price: coin(unit_price_amount, unit_price_denom),
+        unit_price: coin(unit_price_amount, &unit_price_denom),
         period: period.parse()?,
         selection,
         max_players,
-        contract_addr: Addr::unchecked(""), // update by reply
+        contract_addr: lottery_addr.clone(),
+        code_id: state.lottery_code_id,
+        status: LotteryStatus::Active,
+        player_count: 0,
+        pot: rollover.unwrap_or_else(|| coin(0, unit_price_denom)),
+        winner: vec![],
+        claimed: false,
+        creator: creator.clone(),
+        deposit: deposit.clone(),
+        commission,
+        commission_paid: None,
     };
 
-    PENDING_LOTTERY.save(deps.storage, &lottery)?;
+    LOTTERIES.save(deps.storage, &lottery_addr, &lottery)?;
 
-    Ok(Response::new().add_submessage(msg).add_attributes(attrs))
+    let mut totals = CREATOR_TOTALS
+        .may_load(deps.storage, creator)?
+        .unwrap_or_default();
+    totals.add_lottery(deposit)?;
+    CREATOR_TOTALS.save(deps.storage, creator, &totals)?;
+
+    state.lotteries_count += 1;
+    STATE.save(deps.storage, &state)?;
+
+    let reply_id = NEXT_REPLY_ID
+        .may_load(deps.storage)?
+        .unwrap_or(CREATE_LOTTERY_REPLY_ID_START);
+    NEXT_REPLY_ID.save(deps.storage, &(reply_id + 1))?;
+    PENDING_LOTTERIES.save(deps.storage, reply_id, &lottery_addr)?;
+
+    Ok(SubMsg::reply_on_success(msg, reply_id))
+}
+
+/// Salt of a lottery's `Instantiate2` address, unique per creator, series and round
+pub fn lottery_salt(creator: &Addr, series: &str, round: u64) -> Binary {
+    let mut sha256 = Sha256::new();
+    sha256.update((creator.as_str().len() as u64).to_be_bytes());
+    sha256.update(creator.as_bytes());
+    sha256.update((series.len() as u64).to_be_bytes());
+    sha256.update(series.as_bytes());
+    sha256.update(round.to_be_bytes());
+
+    Binary::from(sha256.finalize().to_vec())
+}
+
+/// The address the platform instantiates a lottery of `code_id` at with `salt`
+pub fn lottery_address(deps: Deps, env: &Env, code_id: u64, salt: &Binary) -> StdResult<Addr> {
+    let checksum = deps.querier.query_wasm_code_info(code_id)?.checksum;
+    let creator = deps.api.addr_canonicalize(env.contract.address.as_str())?;
+    let address = instantiate2_address(checksum.as_slice(), &creator, salt)
+        .map_err(|err| StdError::generic_err(err.to_string()))?;
+
+    deps.api.addr_humanize(&address)
 }
 
 // pub fn buy_lottery(
@@ -120,7 +349,7 @@ pub fn create_lottery(
 //     let buy_msg = LotteryExecuteMsg::BuyTicket { denom: denom.into(), memo };
 //     let msg = WasmMsg::Execute {
 //         contract_addr: lottery.to_string(),
-//         msg: to_binary(&buy_msg)?,
+//         msg: to_json_binary(&buy_msg)?,
 //         funds: info.funds,
 //     };
 
@@ -131,29 +360,960 @@ pub fn create_lottery(
 
 pub fn draw_lottery(
     deps: DepsMut,
+    env: &Env,
+    info: &MessageInfo,
+    lottery: &str,
+) -> Result<Response, ContractError> {
+    validate_role(deps.as_ref(), Role::Drawer, &info.sender)?;
+
+    validate_not_paused(deps.storage, env)?;
+
+    // The lottery reports its winner by `LotteryHook`
+    update_lottery_status(deps, lottery, LotteryStatus::Drawn)?;
+
+    let msg = LotteryExecuteMsg::DrawLottery {};
+    let msg = WasmMsg::Execute {
+        contract_addr: lottery.to_string(),
+        msg: to_json_binary(&msg)?,
+        funds: vec![],
+    };
+
+    let attrs = vec![
+        attr("action", "draw_lottery"),
+        attr("sender", info.sender.as_str()),
+    ];
+
+    Ok(Response::new().add_message(msg).add_attributes(attrs))
+}
+
+pub fn cancel_lottery(
+    mut deps: DepsMut,
     _env: &Env,
     info: &MessageInfo,
     lottery: &str,
 ) -> Result<Response, ContractError> {
     let sender = &info.sender;
 
-    let owner = OWNER.load(deps.storage)?;
+    let lottery_addr = deps.api.addr_validate(lottery)?;
 
-    if owner != sender {
-        return Err(ContractError::Unauthorized {});
+    // Creators can cancel their own lotteries, admins any of them
+    let creator = LOTTERIES
+        .may_load(deps.storage, &lottery_addr)?
+        .map(|lottery| lottery.creator);
+    if creator.as_ref() != Some(sender) {
+        validate_role(deps.as_ref(), Role::Admin, sender)?;
     }
 
-    let msg = LotteryExecuteMsg::DrawLottery {};
+    let mut lottery_info = update_lottery_status(deps.branch(), lottery, LotteryStatus::Cancelled)?;
+
+    // The deposit is slashed if the creator walks away from sold tickets
+    let slashed = *sender == lottery_info.creator && lottery_info.player_count > 0;
+    let deposit_msg = release_deposit(deps.storage, &mut lottery_info, slashed)?;
+    LOTTERIES.save(deps.storage, &lottery_addr, &lottery_info)?;
+
+    let msg = LotteryExecuteMsg::CancelLottery {};
     let msg = WasmMsg::Execute {
         contract_addr: lottery.to_string(),
-        msg: to_binary(&msg)?,
+        msg: to_json_binary(&msg)?,
         funds: vec![],
     };
 
     let attrs = vec![
-        attr("action", "draw_lottery"),
+        attr("action", "cancel_lottery"),
+        attr("sender", info.sender.as_str()),
+        attr("slashed", slashed.to_string()),
+    ];
+
+    Ok(Response::new()
+        .add_messages(deposit_msg)
+        .add_message(msg)
+        .add_attributes(attrs))
+}
+
+pub fn update_allowlist(
+    deps: DepsMut,
+    _env: &Env,
+    info: &MessageInfo,
+    lottery: &str,
+    add: Vec<String>,
+    remove: Vec<String>,
+) -> Result<Response, ContractError> {
+    let sender = &info.sender;
+
+    let lottery_addr = deps.api.addr_validate(lottery)?;
+    let lottery_info =
+        LOTTERIES
+            .may_load(deps.storage, &lottery_addr)?
+            .ok_or(ContractError::LotteryNotFound {
+                lottery: lottery.to_string(),
+            })?;
+
+    if lottery_info.creator != *sender {
+        validate_role(deps.as_ref(), Role::Admin, sender)?;
+    }
+
+    let msg = LotteryExecuteMsg::UpdateAllowlist { add, remove };
+    let msg = WasmMsg::Execute {
+        contract_addr: lottery_addr.to_string(),
+        msg: to_json_binary(&msg)?,
+        funds: vec![],
+    };
+
+    let attrs = vec![
+        attr("action", "update_allowlist"),
+        attr("sender", sender.as_str()),
+        attr("lottery", lottery_addr.as_str()),
+    ];
+
+    Ok(Response::new().add_message(msg).add_attributes(attrs))
+}
+
+pub fn lottery_hook(
+    deps: DepsMut,
+    env: &Env,
+    info: &MessageInfo,
+    hook: LotteryHookMsg,
+) -> Result<Response, ContractError> {
+    let lottery_addr = &info.sender;
+
+    // Only the lotteries created by the platform can report
+    let mut lottery = LOTTERIES
+        .may_load(deps.storage, lottery_addr)?
+        .ok_or(ContractError::Unauthorized {})?;
+
+    let mut msgs = vec![];
+    let action = match hook {
+        LotteryHookMsg::TicketBought {
+            player,
+            ticket_id,
+            player_count,
+            pot,
+            referrer,
+            paid,
+            ..
+        } => {
+            let player = deps.api.addr_validate(&player)?;
+            let paid = paid.unwrap_or_else(|| lottery.unit_price.clone());
+            record_entry(deps.storage, env, &lottery, &player, ticket_id, paid)?;
+            if let Some(referrer) = referrer {
+                record_referral(deps.storage, &deps.api.addr_validate(&referrer)?)?;
+            }
+
+            lottery.player_count = player_count;
+            lottery.pot = pot;
+            "ticket_bought"
+        }
+        LotteryHookMsg::Drawn {
+            winner,
+            pot,
+            commission,
+            rollover,
+            jackpot_contribution,
+            jackpot_winner,
+        } => {
+            lottery.status = LotteryStatus::Drawn;
+            lottery.winner = winner;
+            lottery.pot = pot;
+            lottery.commission_paid = commission;
+
+            record_wins(deps.storage, &lottery)?;
+            // Number-pick prizes are paid by the draw
+            if lottery.selection.is_number_pick() {
+                lottery.claimed = true;
+                record_claim(deps.storage, &lottery)?;
+            }
+            if let Some(rollover) = rollover {
+                record_rollover(deps.storage, &lottery, rollover)?;
+            }
+            record_commission(deps.storage, &lottery)?;
+            if let Some(contribution) = jackpot_contribution {
+                record_jackpot_contribution(deps.storage, env, &lottery, contribution)?;
+            }
+            if let Some(winner) = jackpot_winner {
+                let address = deps.api.addr_validate(&winner.address)?;
+                msgs.extend(pay_jackpot(
+                    deps.storage,
+                    env,
+                    &lottery,
+                    address,
+                    winner.ticket_id,
+                )?);
+            }
+            msgs.extend(release_deposit(deps.storage, &mut lottery, false)?);
+            "drawn"
+        }
+        LotteryHookMsg::Claimed { .. } => {
+            lottery.claimed = true;
+
+            record_claim(deps.storage, &lottery)?;
+            "claimed"
+        }
+        LotteryHookMsg::Cancelled {} => {
+            lottery.status = LotteryStatus::Cancelled;
+            "cancelled"
+        }
+        LotteryHookMsg::Sponsored { pot, .. } => {
+            lottery.pot = pot;
+            "sponsored"
+        }
+        LotteryHookMsg::ReferralsPaid { payouts } => {
+            for payout in payouts {
+                let referrer = deps.api.addr_validate(&payout.referrer)?;
+                record_referral_payout(deps.storage, &referrer, payout.reward)?;
+            }
+            "referrals_paid"
+        }
+    };
+
+    LOTTERIES.save(deps.storage, lottery_addr, &lottery)?;
+
+    let attrs = vec![
+        attr("action", "lottery_hook"),
+        attr("lottery", lottery_addr.as_str()),
+        attr("hook", action),
+    ];
+
+    Ok(Response::new().add_messages(msgs).add_attributes(attrs))
+}
+
+pub fn migrate_lotteries(
+    deps: DepsMut,
+    _env: &Env,
+    info: &MessageInfo,
+    code_id: u64,
+    msg: Binary,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    validate_role(deps.as_ref(), Role::Admin, &info.sender)?;
+
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start_after = start_after
+        .map(|addr| deps.api.addr_validate(&addr))
+        .transpose()?;
+    let start = start_after.as_ref().map(Bound::exclusive);
+
+    // Each call walks at most `limit` lotteries, those already running `code_id` are skipped
+    let lotteries = LOTTERIES
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()?;
+    let last_lottery = lotteries.last().map(|(addr, _)| addr.clone());
+
+    let mut msgs = vec![];
+    for (addr, mut lottery) in lotteries {
+        if lottery.code_id == code_id {
+            continue;
+        }
+
+        msgs.push(WasmMsg::Migrate {
+            contract_addr: addr.to_string(),
+            new_code_id: code_id,
+            msg: msg.clone(),
+        });
+
+        lottery.code_id = code_id;
+        LOTTERIES.save(deps.storage, &addr, &lottery)?;
+    }
+
+    let mut attrs = vec![
+        attr("action", "migrate_lotteries"),
+        attr("sender", info.sender.as_str()),
+        attr("code_id", code_id.to_string()),
+        attr("count", msgs.len().to_string()),
+    ];
+    if let Some(last_lottery) = &last_lottery {
+        attrs.push(attr("last_lottery", last_lottery.as_str()));
+    }
+
+    let data = LotteryBatchData { last_lottery };
+
+    Ok(Response::new()
+        .add_messages(msgs)
+        .add_attributes(attrs)
+        .set_data(to_json_binary(&data)?))
+}
+
+pub fn update_lottery_code(
+    deps: DepsMut,
+    env: &Env,
+    info: &MessageInfo,
+    code_id: u64,
+) -> Result<Response, ContractError> {
+    validate_role(deps.as_ref(), Role::Admin, &info.sender)?;
+
+    let mut state = STATE.load(deps.storage)?;
+
+    if state.lottery_code_id == code_id {
+        return Err(ContractError::LotteryCodeUnchanged { code_id });
+    }
+
+    let lottery_code = LotteryCode {
+        code_id,
+        height: env.block.height,
+        activated_at: env.block.time,
+    };
+
+    state.lottery_code_id = code_id;
+
+    STATE.save(deps.storage, &state)?;
+    save_lottery_code(deps.storage, &lottery_code)?;
+
+    let attrs = vec![
+        attr("action", "update_lottery_code"),
+        attr("sender", info.sender.as_str()),
+        attr("code_id", code_id.to_string()),
+    ];
+
+    Ok(Response::new().add_attributes(attrs))
+}
+
+fn update_lottery_status(
+    deps: DepsMut,
+    lottery: &str,
+    status: LotteryStatus,
+) -> Result<LotteryInfo, ContractError> {
+    let lottery_addr = deps.api.addr_validate(lottery)?;
+
+    LOTTERIES.update(deps.storage, &lottery_addr, |lottery| match lottery {
+        Some(mut lottery) => {
+            lottery.status = status;
+            Ok(lottery)
+        }
+        None => Err(ContractError::LotteryNotFound {
+            lottery: lottery_addr.to_string(),
+        }),
+    })
+}
+
+fn record_entry(
+    storage: &mut dyn Storage,
+    env: &Env,
+    lottery: &LotteryInfo,
+    player: &Addr,
+    ticket_id: String,
+    paid: Coin,
+) -> Result<(), ContractError> {
+    let entry = PlayerEntry {
+        lottery: lottery.contract_addr.clone(),
+        ticket_id,
+        paid,
+        height: env.block.height,
+        bought_at: env.block.time,
+    };
+
+    PLAYER_ENTRIES.save(storage, (player, &lottery.contract_addr), &entry)?;
+
+    let mut totals = PLAYER_TOTALS.may_load(storage, player)?.unwrap_or_default();
+    totals.add_entry(entry.paid)?;
+    PLAYER_TOTALS.save(storage, player, &totals)?;
+
+    Ok(())
+}
+
+fn record_wins(storage: &mut dyn Storage, lottery: &LotteryInfo) -> Result<(), ContractError> {
+    for winner in &lottery.winner {
+        let win = PlayerWin {
+            lottery: lottery.contract_addr.clone(),
+            ticket_id: winner.ticket_id.clone(),
+            prize: winner.prize.clone(),
+            claimed: false,
+        };
+
+        PLAYER_WINS.save(storage, (&winner.address, &lottery.contract_addr), &win)?;
+
+        let mut totals = PLAYER_TOTALS
+            .may_load(storage, &winner.address)?
+            .unwrap_or_default();
+        totals.add_win(&winner.prize)?;
+        PLAYER_TOTALS.save(storage, &winner.address, &totals)?;
+    }
+
+    Ok(())
+}
+
+fn record_rollover(
+    storage: &mut dyn Storage,
+    lottery: &LotteryInfo,
+    rollover: Coin,
+) -> StdResult<()> {
+    let key = (&lottery.creator, lottery.symbol.as_str());
+    let mut rollovers = ROLLOVERS.may_load(storage, key)?.unwrap_or_default();
+    add_coins(&mut rollovers, vec![rollover])?;
+    ROLLOVERS.save(storage, key, &rollovers)
+}
+
+fn record_jackpot_contribution(
+    storage: &mut dyn Storage,
+    env: &Env,
+    lottery: &LotteryInfo,
+    amount: Coin,
+) -> StdResult<()> {
+    let mut jackpot = JACKPOT.may_load(storage)?.unwrap_or_default();
+    add_coins(&mut jackpot, vec![amount.clone()])?;
+    JACKPOT.save(storage, &jackpot)?;
+
+    save_jackpot_record(storage, env, lottery, JackpotEvent::Contributed { amount })
+}
+
+/// The whole jackpot in the denom of the lottery goes to the holder of the winning ticket
+fn pay_jackpot(
+    storage: &mut dyn Storage,
+    env: &Env,
+    lottery: &LotteryInfo,
+    winner: Addr,
+    ticket_id: String,
+) -> StdResult<Option<BankMsg>> {
+    let mut jackpot = JACKPOT.may_load(storage)?.unwrap_or_default();
+    let Some(amount) = take_coin(&mut jackpot, &lottery.unit_price.denom) else {
+        return Ok(None);
+    };
+    JACKPOT.save(storage, &jackpot)?;
+
+    save_jackpot_record(
+        storage,
+        env,
+        lottery,
+        JackpotEvent::Won {
+            winner: winner.clone(),
+            ticket_id,
+            amount: amount.clone(),
+        },
+    )?;
+
+    Ok(Some(BankMsg::Send {
+        to_address: winner.into_string(),
+        amount: vec![amount],
+    }))
+}
+
+fn save_jackpot_record(
+    storage: &mut dyn Storage,
+    env: &Env,
+    lottery: &LotteryInfo,
+    event: JackpotEvent,
+) -> StdResult<()> {
+    let id = NEXT_JACKPOT_RECORD_ID.may_load(storage)?.unwrap_or(1);
+    NEXT_JACKPOT_RECORD_ID.save(storage, &(id + 1))?;
+
+    let record = JackpotRecord {
+        id,
+        lottery: lottery.contract_addr.clone(),
+        height: env.block.height,
+        time: env.block.time,
+        event,
+    };
+    JACKPOT_HISTORY.save(storage, id, &record)
+}
+
+fn record_commission(storage: &mut dyn Storage, lottery: &LotteryInfo) -> StdResult<()> {
+    let Some(commission) = lottery.commission_paid.clone() else {
+        return Ok(());
+    };
+
+    let mut totals = CREATOR_TOTALS
+        .may_load(storage, &lottery.creator)?
+        .unwrap_or_default();
+    totals.add_earning(commission)?;
+    CREATOR_TOTALS.save(storage, &lottery.creator, &totals)
+}
+
+fn record_referral(storage: &mut dyn Storage, referrer: &Addr) -> StdResult<()> {
+    let mut totals = REFERRER_TOTALS
+        .may_load(storage, referrer)?
+        .unwrap_or_default();
+    totals.referrals += 1;
+    REFERRER_TOTALS.save(storage, referrer, &totals)
+}
+
+fn record_referral_payout(
+    storage: &mut dyn Storage,
+    referrer: &Addr,
+    reward: Coin,
+) -> StdResult<()> {
+    let mut totals = REFERRER_TOTALS
+        .may_load(storage, referrer)?
+        .unwrap_or_default();
+    totals.add_earning(reward)?;
+    REFERRER_TOTALS.save(storage, referrer, &totals)
+}
+
+/// Refund the creation deposit to the creator, or keep it on the platform if `slashed`
+fn release_deposit(
+    storage: &mut dyn Storage,
+    lottery: &mut LotteryInfo,
+    slashed: bool,
+) -> StdResult<Option<BankMsg>> {
+    let Some(deposit) = lottery.deposit.take() else {
+        return Ok(None);
+    };
+
+    let mut totals = CREATOR_TOTALS
+        .may_load(storage, &lottery.creator)?
+        .unwrap_or_default();
+    let msg = if slashed {
+        totals.slash(deposit.clone())?;
+        let mut withheld = SLASHED_DEPOSITS.may_load(storage)?.unwrap_or_default();
+        add_coins(&mut withheld, vec![deposit])?;
+        SLASHED_DEPOSITS.save(storage, &withheld)?;
+        None
+    } else {
+        totals.refund(deposit.clone())?;
+        Some(BankMsg::Send {
+            to_address: lottery.creator.to_string(),
+            amount: vec![deposit],
+        })
+    };
+    CREATOR_TOTALS.save(storage, &lottery.creator, &totals)?;
+
+    Ok(msg)
+}
+
+/// The prize is accounted to the winning buyer, even when the ticket was transferred
+fn record_claim(storage: &mut dyn Storage, lottery: &LotteryInfo) -> Result<(), ContractError> {
+    for winner in &lottery.winner {
+        let key = (&winner.address, &lottery.contract_addr);
+        let Some(mut win) = PLAYER_WINS.may_load(storage, key)? else {
+            continue;
+        };
+        if win.claimed {
+            continue;
+        }
+
+        win.claimed = true;
+        PLAYER_WINS.save(storage, key, &win)?;
+
+        let mut totals = PLAYER_TOTALS.load(storage, &winner.address)?;
+        totals.claim(&win.prize)?;
+        PLAYER_TOTALS.save(storage, &winner.address, &totals)?;
+    }
+
+    Ok(())
+}
+
+pub fn update_creation_config(
+    deps: DepsMut,
+    _env: &Env,
+    info: &MessageInfo,
+    deposit: Option<Coin>,
+    commission: Decimal,
+) -> Result<Response, ContractError> {
+    validate_role(deps.as_ref(), Role::Admin, &info.sender)?;
+
+    ensure!(
+        commission <= Decimal::one(),
+        ContractError::InvalidCommission { rate: commission }
+    );
+
+    let config = CreationConfig {
+        deposit,
+        commission,
+    };
+    CREATION_CONFIG.save(deps.storage, &config)?;
+
+    let mut attrs = vec![
+        attr("action", "update_creation_config"),
+        attr("sender", info.sender.as_str()),
+        attr("commission", commission.to_string()),
+    ];
+    if let Some(deposit) = config.deposit {
+        attrs.push(attr("deposit", deposit.to_string()));
+    }
+
+    Ok(Response::new().add_attributes(attrs))
+}
+
+pub fn propose_owner(
+    deps: DepsMut,
+    env: &Env,
+    info: &MessageInfo,
+    new_owner: String,
+    expiry: Option<Expiration>,
+) -> Result<Response, ContractError> {
+    validate_owner(deps.storage, &info.sender)?;
+
+    let new_owner = deps.api.addr_validate(&new_owner)?;
+    let pending = validate_owner_proposal(env, new_owner, expiry)?;
+    PENDING_OWNER.save(deps.storage, &pending)?;
+
+    let attrs = vec![
+        attr("action", "propose_owner"),
+        attr("sender", info.sender.as_str()),
+        attr("new_owner", pending.address.as_str()),
+        attr("expiry", pending.expiry.to_string()),
+    ];
+
+    Ok(Response::new().add_attributes(attrs))
+}
+
+pub fn accept_ownership(
+    deps: DepsMut,
+    env: &Env,
+    info: &MessageInfo,
+) -> Result<Response, ContractError> {
+    let sender = &info.sender;
+
+    validate_acceptance(env, PENDING_OWNER.may_load(deps.storage)?, sender)?;
+
+    OWNER.save(deps.storage, sender)?;
+    PENDING_OWNER.remove(deps.storage);
+
+    let attrs = vec![
+        attr("action", "accept_ownership"),
+        attr("sender", sender.as_str()),
+    ];
+
+    Ok(Response::new().add_attributes(attrs))
+}
+
+pub fn cancel_ownership_transfer(
+    deps: DepsMut,
+    _env: &Env,
+    info: &MessageInfo,
+) -> Result<Response, ContractError> {
+    validate_owner(deps.storage, &info.sender)?;
+
+    ensure!(
+        PENDING_OWNER.exists(deps.storage),
+        LotteryContractError::NoPendingOwner {}
+    );
+    PENDING_OWNER.remove(deps.storage);
+
+    let attrs = vec![
+        attr("action", "cancel_ownership_transfer"),
+        attr("sender", info.sender.as_str()),
+    ];
+
+    Ok(Response::new().add_attributes(attrs))
+}
+
+pub fn renounce_ownership(
+    deps: DepsMut,
+    _env: &Env,
+    info: &MessageInfo,
+) -> Result<Response, ContractError> {
+    validate_owner(deps.storage, &info.sender)?;
+
+    OWNER.remove(deps.storage);
+    PENDING_OWNER.remove(deps.storage);
+
+    let attrs = vec![
+        attr("action", "renounce_ownership"),
+        attr("sender", info.sender.as_str()),
+    ];
+
+    Ok(Response::new().add_attributes(attrs))
+}
+
+pub fn pause(
+    deps: DepsMut,
+    env: &Env,
+    info: &MessageInfo,
+    reason: String,
+    expires_at: Option<u64>,
+) -> Result<Response, ContractError> {
+    let sender = &info.sender;
+
+    validate_guardian(deps.as_ref(), sender)?;
+
+    let pause = validate_pause(env, sender, reason, expires_at)?;
+    PAUSE.save(deps.storage, &pause)?;
+
+    let mut attrs = vec![
+        attr("action", "pause"),
+        attr("sender", sender.as_str()),
+        attr("reason", pause.reason),
+    ];
+    if let Some(expires_at) = pause.expires_at {
+        attrs.push(attr("expires_at", expires_at.seconds().to_string()));
+    }
+
+    Ok(Response::new().add_attributes(attrs))
+}
+
+pub fn unpause(deps: DepsMut, _env: &Env, info: &MessageInfo) -> Result<Response, ContractError> {
+    validate_guardian(deps.as_ref(), &info.sender)?;
+
+    PAUSE.remove(deps.storage);
+
+    let attrs = vec![
+        attr("action", "unpause"),
+        attr("sender", info.sender.as_str()),
+    ];
+
+    Ok(Response::new().add_attributes(attrs))
+}
+
+pub fn pause_lotteries(
+    deps: DepsMut,
+    env: &Env,
+    info: &MessageInfo,
+    reason: String,
+    expires_at: Option<u64>,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    let sender = &info.sender;
+
+    validate_guardian(deps.as_ref(), sender)?;
+
+    // Fail here rather than in every lottery
+    validate_pause(env, sender, reason.clone(), expires_at)?;
+
+    let msg = LotteryExecuteMsg::Pause { reason, expires_at };
+    let (msgs, last_lottery) = active_lottery_msgs(deps.as_ref(), &msg, start_after, limit)?;
+
+    lottery_batch_response(msgs, "pause_lotteries", sender, last_lottery)
+}
+
+pub fn unpause_lotteries(
+    deps: DepsMut,
+    _env: &Env,
+    info: &MessageInfo,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    let sender = &info.sender;
+
+    validate_guardian(deps.as_ref(), sender)?;
+
+    let msg = LotteryExecuteMsg::Unpause {};
+    let (msgs, last_lottery) = active_lottery_msgs(deps.as_ref(), &msg, start_after, limit)?;
+
+    lottery_batch_response(msgs, "unpause_lotteries", sender, last_lottery)
+}
+
+/// `msg` to the next `limit` active lotteries of the platform, with the last of them
+fn active_lottery_msgs(
+    deps: Deps,
+    msg: &LotteryExecuteMsg,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<(Vec<WasmMsg>, Option<Addr>)> {
+    let msg = to_json_binary(msg)?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start_after = start_after
+        .map(|addr| deps.api.addr_validate(&addr))
+        .transpose()?;
+    let start = start_after.as_ref().map(Bound::exclusive);
+
+    let lotteries = LOTTERIES
+        .idx
+        .status
+        .prefix(LotteryStatus::Active.to_string())
+        .keys(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()?;
+    let last_lottery = lotteries.last().cloned();
+
+    let msgs = lotteries
+        .into_iter()
+        .map(|lottery| WasmMsg::Execute {
+            contract_addr: lottery.into_string(),
+            msg: msg.clone(),
+            funds: vec![],
+        })
+        .collect();
+
+    Ok((msgs, last_lottery))
+}
+
+fn lottery_batch_response(
+    msgs: Vec<WasmMsg>,
+    action: &str,
+    sender: &Addr,
+    last_lottery: Option<Addr>,
+) -> Result<Response, ContractError> {
+    let mut attrs = vec![
+        attr("action", action),
+        attr("sender", sender.as_str()),
+        attr("count", msgs.len().to_string()),
+    ];
+    if let Some(last_lottery) = &last_lottery {
+        attrs.push(attr("last_lottery", last_lottery.as_str()));
+    }
+
+    let data = LotteryBatchData { last_lottery };
+
+    Ok(Response::new()
+        .add_messages(msgs)
+        .add_attributes(attrs)
+        .set_data(to_json_binary(&data)?))
+}
+
+pub fn withdraw_slashed(
+    deps: DepsMut,
+    _env: &Env,
+    info: &MessageInfo,
+    recipient: Option<String>,
+) -> Result<Response, ContractError> {
+    validate_role(deps.as_ref(), Role::Admin, &info.sender)?;
+
+    let slashed = SLASHED_DEPOSITS.may_load(deps.storage)?.unwrap_or_default();
+    ensure!(!slashed.is_empty(), ContractError::NothingSlashed {});
+    SLASHED_DEPOSITS.remove(deps.storage);
+
+    let recipient = match recipient {
+        Some(recipient) => deps.api.addr_validate(&recipient)?,
+        None => info.sender.clone(),
+    };
+
+    let msg = BankMsg::Send {
+        to_address: recipient.to_string(),
+        amount: slashed,
+    };
+
+    let attrs = vec![
+        attr("action", "withdraw_slashed"),
         attr("sender", info.sender.as_str()),
+        attr("recipient", recipient.as_str()),
     ];
 
     Ok(Response::new().add_message(msg).add_attributes(attrs))
 }
+
+pub fn update_jackpot_config(
+    deps: DepsMut,
+    _env: &Env,
+    info: &MessageInfo,
+    config: Option<ProgressiveJackpot>,
+) -> Result<Response, ContractError> {
+    validate_role(deps.as_ref(), Role::Admin, &info.sender)?;
+
+    let mut attrs = vec![
+        attr("action", "update_jackpot_config"),
+        attr("sender", info.sender.as_str()),
+    ];
+    match config {
+        Some(config) => {
+            validate_progressive_jackpot(&config)?;
+            attrs.push(attr(
+                "contribution_bps",
+                config.contribution_bps.to_string(),
+            ));
+            attrs.push(attr("odds", config.odds.to_string()));
+            JACKPOT_CONFIG.save(deps.storage, &config)?;
+        }
+        None => JACKPOT_CONFIG.remove(deps.storage),
+    }
+
+    Ok(Response::new().add_attributes(attrs))
+}
+
+pub fn update_resale_royalty(
+    deps: DepsMut,
+    _env: &Env,
+    info: &MessageInfo,
+    bps: Option<u16>,
+) -> Result<Response, ContractError> {
+    validate_role(deps.as_ref(), Role::Admin, &info.sender)?;
+
+    let mut attrs = vec![
+        attr("action", "update_resale_royalty"),
+        attr("sender", info.sender.as_str()),
+    ];
+    match bps {
+        Some(bps) => {
+            ensure!(
+                bps <= 10_000,
+                LotteryContractError::InvalidResaleRoyalty { bps }
+            );
+            attrs.push(attr("bps", bps.to_string()));
+            RESALE_ROYALTY_BPS.save(deps.storage, &bps)?;
+        }
+        None => RESALE_ROYALTY_BPS.remove(deps.storage),
+    }
+
+    Ok(Response::new().add_attributes(attrs))
+}
+
+pub fn grant_role(
+    deps: DepsMut,
+    _env: &Env,
+    info: &MessageInfo,
+    role: Role,
+    address: &str,
+) -> Result<Response, ContractError> {
+    validate_role(deps.as_ref(), Role::Admin, &info.sender)?;
+
+    let address = deps.api.addr_validate(address)?;
+
+    ensure!(
+        !ROLE_MEMBERS.has(deps.storage, (role.as_str(), &address)),
+        ContractError::RoleAlreadyGranted {
+            address: address.to_string(),
+            role: role.to_string(),
+        }
+    );
+
+    ROLE_MEMBERS.save(deps.storage, (role.as_str(), &address), &Empty {})?;
+
+    let attrs = vec![
+        attr("action", "grant_role"),
+        attr("sender", info.sender.as_str()),
+        attr("role", role.as_str()),
+        attr("address", address.as_str()),
+    ];
+
+    Ok(Response::new().add_attributes(attrs))
+}
+
+pub fn revoke_role(
+    deps: DepsMut,
+    _env: &Env,
+    info: &MessageInfo,
+    role: Role,
+    address: &str,
+) -> Result<Response, ContractError> {
+    validate_role(deps.as_ref(), Role::Admin, &info.sender)?;
+
+    let address = deps.api.addr_validate(address)?;
+
+    ensure!(
+        ROLE_MEMBERS.has(deps.storage, (role.as_str(), &address)),
+        ContractError::RoleNotGranted {
+            address: address.to_string(),
+            role: role.to_string(),
+        }
+    );
+
+    ROLE_MEMBERS.remove(deps.storage, (role.as_str(), &address));
+
+    let attrs = vec![
+        attr("action", "revoke_role"),
+        attr("sender", info.sender.as_str()),
+        attr("role", role.as_str()),
+        attr("address", address.as_str()),
+    ];
+
+    Ok(Response::new().add_attributes(attrs))
+}
+
+/// Grant `role` to the members of a cw4 group, or stop doing so when `group` is `None`
+pub fn set_role_group(
+    deps: DepsMut,
+    _env: &Env,
+    info: &MessageInfo,
+    role: Role,
+    group: Option<String>,
+) -> Result<Response, ContractError> {
+    validate_role(deps.as_ref(), Role::Admin, &info.sender)?;
+
+    let group = group.map(|g| deps.api.addr_validate(&g)).transpose()?;
+
+    match &group {
+        Some(group) => ROLE_GROUPS.save(deps.storage, role.as_str(), group)?,
+        None => ROLE_GROUPS.remove(deps.storage, role.as_str()),
+    }
+
+    let mut attrs = vec![
+        attr("action", "set_role_group"),
+        attr("sender", info.sender.as_str()),
+        attr("role", role.as_str()),
+    ];
+    if let Some(group) = group {
+        attrs.push(attr("group", group));
+    }
+
+    Ok(Response::new().add_attributes(attrs))
+}
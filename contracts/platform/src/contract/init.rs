@@ -2,8 +2,9 @@ use cosmwasm_std::{attr, DepsMut, Env, MessageInfo, Response};
 use cw2::set_contract_version;
 
 use crate::{
+    auth::exec::grant_owner_roles,
     msg::InstantiateMsg,
-    state::{State, OWNER, STATE},
+    state::{save_lottery_code, LotteryCode, State, OWNER, STATE},
     ContractError,
 };
 
@@ -26,8 +27,16 @@ pub fn instantiate(
         msg.lottery_code_id,
     );
 
+    let lottery_code = LotteryCode {
+        code_id: msg.lottery_code_id,
+        height: env.block.height,
+        activated_at: env.block.time,
+    };
+
     STATE.save(deps.storage, &state)?;
     OWNER.save(deps.storage, sender)?;
+    grant_owner_roles(deps.storage, sender)?;
+    save_lottery_code(deps.storage, &lottery_code)?;
 
     let attributes = vec![
         attr("action", "instantitate_platform"),
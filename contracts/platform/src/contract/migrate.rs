@@ -0,0 +1,338 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{
+    attr, ensure, Addr, Coin, Decimal, DepsMut, Env, Order, Response, StdResult, Timestamp,
+};
+use cw2::{get_contract_version, set_contract_version};
+use cw_storage_plus::{Bound, Item, Map};
+use lottery::{
+    msg::{OwnerResp, QueryMsg as LotteryQueryMsg},
+    state::{GameStatus, LotteryPeriod, WinnerInfo, WinnerSelection},
+};
+use semver::Version;
+use serde::Deserialize;
+
+use crate::{
+    auth::exec::grant_owner_roles,
+    msg::MigrateMsg,
+    state::{save_lottery_code, LotteryCode, LotteryInfo, LotteryStatus, LOTTERIES, OWNER, STATE},
+    ContractError,
+};
+
+use super::{CONTRACT_NAME, CONTRACT_VERSION, DEFAULT_LIMIT, LEGACY_CONTRACT_NAME, MAX_LIMIT};
+
+/// `LotteryInfo` as stored by 1.0.x, before the lottery code id was recorded
+#[cw_serde]
+struct LotteryInfoV1 {
+    pub name: String,
+    pub symbol: String,
+    pub height: u64,
+    pub created_at: Timestamp,
+    pub unit_price: Coin,
+    pub period: LotteryPeriod,
+    pub selection: WinnerSelection,
+    pub max_players: u64,
+    pub contract_addr: Addr,
+}
+
+const LOTTERIES_V1: Map<&Addr, LotteryInfoV1> = Map::new("lotteries");
+/// 1.0.x cached a single lottery between `create_lottery` and its reply
+const PENDING_LOTTERY_V1: Item<LotteryInfoV1> = Item::new("pending_lottery");
+/// The last 1.0.x lottery converted while the migration is in progress
+const LOTTERIES_V1_CURSOR: Item<Addr> = Item::new("lotteries_v1_cursor");
+
+/// The part of a lottery's `CurrentState` which is readable from every lottery version
+#[derive(Deserialize)]
+struct LotteryStateResp {
+    state: LotteryState,
+}
+
+#[derive(Deserialize)]
+struct LotteryState {
+    status: GameStatus,
+    player_count: u64,
+    winner: Vec<WinnerInfo>,
+}
+
+pub fn migrate(mut deps: DepsMut, env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+    let stored = get_contract_version(deps.storage)?;
+
+    let stored_version: Version = stored.version.parse()?;
+    let current_version: Version = CONTRACT_VERSION.parse()?;
+
+    let is_legacy =
+        stored.contract == LEGACY_CONTRACT_NAME && stored_version < Version::new(1, 1, 0);
+    ensure!(
+        stored.contract == CONTRACT_NAME || is_legacy,
+        ContractError::MigrateContractMismatch {
+            contract: stored.contract
+        }
+    );
+
+    ensure!(
+        stored_version <= current_version,
+        ContractError::MigrateVersionDowngrade {
+            stored: stored.version,
+            current: CONTRACT_VERSION.to_string(),
+        }
+    );
+
+    let mut attrs = vec![
+        attr("action", "migrate"),
+        attr("from_version", stored_version.to_string()),
+    ];
+
+    if stored_version < Version::new(1, 1, 0) {
+        let (count, done) = migrate_lotteries_from_v1(deps.branch(), &env, msg.limit)?;
+        attrs.push(attr("lotteries_migrated", count.to_string()));
+        if !done {
+            // The stored version stays behind until the next call picks up the rest
+            return Ok(Response::new().add_attributes(attrs));
+        }
+    }
+
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    attrs.push(attr("to_version", CONTRACT_VERSION));
+
+    Ok(Response::new().add_attributes(attrs))
+}
+
+/// Convert the next `limit` 1.0.x lotteries, and whether they're all converted
+fn migrate_lotteries_from_v1(
+    deps: DepsMut,
+    env: &Env,
+    limit: Option<u32>,
+) -> Result<(usize, bool), ContractError> {
+    let cursor = LOTTERIES_V1_CURSOR.may_load(deps.storage)?;
+    let platform_owner = OWNER.load(deps.storage)?;
+
+    // The first call records what 1.0.x knew of the platform
+    if cursor.is_none() {
+        let state = STATE.load(deps.storage)?;
+        // 1.0.x could only create lotteries from the instantiated code
+        let lottery_code = LotteryCode {
+            code_id: state.lottery_code_id,
+            height: state.height,
+            activated_at: state.created_at,
+        };
+        save_lottery_code(deps.storage, &lottery_code)?;
+        // 1.0.x only knew the owner
+        grant_owner_roles(deps.storage, &platform_owner)?;
+    }
+
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = cursor.as_ref().map(Bound::exclusive);
+    let lotteries = LOTTERIES_V1
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit + 1)
+        .collect::<StdResult<Vec<_>>>()?;
+    let done = lotteries.len() <= limit;
+    let lotteries: Vec<_> = lotteries.into_iter().take(limit).collect();
+    let count = lotteries.len();
+    if let Some((last, _)) = lotteries.last() {
+        LOTTERIES_V1_CURSOR.save(deps.storage, last)?;
+    }
+
+    for (addr, old) in lotteries {
+        let code_id = deps.querier.query_wasm_contract_info(&addr)?.code_id;
+        let resp: LotteryStateResp = deps
+            .querier
+            .query_wasm_smart(&addr, &LotteryQueryMsg::CurrentState {})?;
+        let status = match resp.state.status {
+            GameStatus::Activing => LotteryStatus::Active,
+            GameStatus::Closed => LotteryStatus::Drawn,
+            GameStatus::Cancelled => LotteryStatus::Cancelled,
+        };
+        let pot = match resp.state.winner.first() {
+            Some(winner) => winner.prize[0].clone(),
+            None => deps.querier.query_balance(&addr, &old.unit_price.denom)?,
+        };
+        // The winner takes over the lottery from the platform by claiming it
+        let owner: OwnerResp = deps
+            .querier
+            .query_wasm_smart(&addr, &LotteryQueryMsg::Owner {})?;
+        let claimed = owner.owner != env.contract.address;
+
+        let lottery = LotteryInfo {
+            name: old.name,
+            symbol: old.symbol,
+            height: old.height,
+            created_at: old.created_at,
+            unit_price: old.unit_price,
+            period: old.period,
+            selection: old.selection,
+            max_players: old.max_players,
+            contract_addr: old.contract_addr,
+            code_id,
+            status,
+            player_count: resp.state.player_count,
+            pot,
+            winner: resp.state.winner,
+            claimed,
+            // 1.0.x didn't record the creator, nor took deposits or commissions
+            creator: platform_owner.clone(),
+            deposit: None,
+            commission: Decimal::zero(),
+            commission_paid: None,
+        };
+
+        // Remove the raw entry first, `IndexedMap` can't read the old layout
+        LOTTERIES_V1.remove(deps.storage, &addr);
+        LOTTERIES.save(deps.storage, &addr, &lottery)?;
+    }
+
+    if done {
+        PENDING_LOTTERY_V1.remove(deps.storage);
+        LOTTERIES_V1_CURSOR.remove(deps.storage);
+    }
+
+    Ok((count, done))
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::testing::{mock_dependencies, mock_env, MOCK_CONTRACT_ADDR};
+    use cosmwasm_std::{
+        coin, from_json, to_json_binary, Binary, ContractInfoResponse, ContractResult,
+        SystemResult, WasmQuery,
+    };
+
+    use crate::state::{Role, State, LOTTERY_CODES, ROLE_MEMBERS};
+
+    use super::*;
+
+    #[test]
+    fn migrate_from_legacy_name_should_works() {
+        let mut deps = mock_dependencies();
+        set_contract_version(deps.as_mut().storage, LEGACY_CONTRACT_NAME, "1.0.0").unwrap();
+        let state = State::new(
+            "PLATFORM".to_string(),
+            12_345,
+            Timestamp::from_seconds(1_000),
+            Addr::unchecked("owner"),
+            7,
+        );
+        STATE.save(deps.as_mut().storage, &state).unwrap();
+        OWNER
+            .save(deps.as_mut().storage, &Addr::unchecked("owner"))
+            .unwrap();
+
+        migrate(deps.as_mut(), mock_env(), MigrateMsg { limit: None }).unwrap();
+
+        let code = LOTTERY_CODES.load(deps.as_ref().storage, 1).unwrap();
+        assert_eq!(code.height, 12_345);
+
+        let owner = Addr::unchecked("owner");
+        for role in [Role::Creator, Role::Drawer] {
+            assert!(ROLE_MEMBERS.has(deps.as_ref().storage, (role.as_str(), &owner)));
+        }
+
+        let version = get_contract_version(deps.as_ref().storage).unwrap();
+        assert_eq!(version.contract, CONTRACT_NAME);
+        assert_eq!(version.version, CONTRACT_VERSION);
+    }
+
+    #[test]
+    fn migrate_lotteries_in_batches_should_works() {
+        let mut deps = mock_dependencies();
+        set_contract_version(deps.as_mut().storage, LEGACY_CONTRACT_NAME, "1.0.0").unwrap();
+        let state = State::new(
+            "PLATFORM".to_string(),
+            12_345,
+            Timestamp::from_seconds(1_000),
+            Addr::unchecked("owner"),
+            7,
+        );
+        STATE.save(deps.as_mut().storage, &state).unwrap();
+        OWNER
+            .save(deps.as_mut().storage, &Addr::unchecked("owner"))
+            .unwrap();
+
+        let addrs = ["lottery1", "lottery2", "lottery3"].map(Addr::unchecked);
+        for addr in &addrs {
+            let old = LotteryInfoV1 {
+                name: addr.to_string(),
+                symbol: "LOTTER".to_string(),
+                height: 12_345,
+                created_at: Timestamp::from_seconds(1_000),
+                unit_price: coin(100, "uconst"),
+                period: LotteryPeriod::Hour {},
+                selection: WinnerSelection::Jackpot {},
+                max_players: 3,
+                contract_addr: addr.clone(),
+            };
+            LOTTERIES_V1
+                .save(deps.as_mut().storage, addr, &old)
+                .unwrap();
+        }
+
+        deps.querier.update_wasm(|query| match query {
+            WasmQuery::ContractInfo { .. } => {
+                let mut info = ContractInfoResponse::default();
+                info.code_id = 7;
+                SystemResult::Ok(ContractResult::Ok(to_json_binary(&info).unwrap()))
+            }
+            WasmQuery::Smart { msg, .. } => {
+                let resp = match from_json(msg).unwrap() {
+                    LotteryQueryMsg::Owner {} => to_json_binary(&OwnerResp {
+                        owner: Addr::unchecked(MOCK_CONTRACT_ADDR),
+                    })
+                    .unwrap(),
+                    _ => Binary::from(
+                        br#"{"state":{"status":"activing","player_count":0,"winner":[]}}"#,
+                    ),
+                };
+                SystemResult::Ok(ContractResult::Ok(resp))
+            }
+            _ => unimplemented!(),
+        });
+
+        let resp = migrate(deps.as_mut(), mock_env(), MigrateMsg { limit: Some(2) }).unwrap();
+        assert!(!resp.attributes.iter().any(|attr| attr.key == "to_version"));
+        assert!(LOTTERIES.has(deps.as_ref().storage, &addrs[1]));
+        assert!(LOTTERIES_V1.has(deps.as_ref().storage, &addrs[2]));
+        let version = get_contract_version(deps.as_ref().storage).unwrap();
+        assert_eq!(version.contract, LEGACY_CONTRACT_NAME);
+
+        migrate(deps.as_mut(), mock_env(), MigrateMsg { limit: Some(2) }).unwrap();
+        let lottery = LOTTERIES.load(deps.as_ref().storage, &addrs[2]).unwrap();
+        assert_eq!(lottery.code_id, 7);
+        assert_eq!(lottery.status, LotteryStatus::Active);
+        assert!(!LOTTERIES_V1_CURSOR.exists(deps.as_ref().storage));
+        // the platform records are only written once
+        assert!(!LOTTERY_CODES.has(deps.as_ref().storage, 2));
+
+        let version = get_contract_version(deps.as_ref().storage).unwrap();
+        assert_eq!(version.contract, CONTRACT_NAME);
+        assert_eq!(version.version, CONTRACT_VERSION);
+    }
+
+    #[test]
+    fn migrate_should_fail() {
+        let mut deps = mock_dependencies();
+        set_contract_version(
+            deps.as_mut().storage,
+            LEGACY_CONTRACT_NAME,
+            CONTRACT_VERSION,
+        )
+        .unwrap();
+
+        let err = migrate(deps.as_mut(), mock_env(), MigrateMsg { limit: None }).unwrap_err();
+        assert_eq!(
+            ContractError::MigrateContractMismatch {
+                contract: LEGACY_CONTRACT_NAME.to_string()
+            },
+            err
+        );
+
+        set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "99.0.0").unwrap();
+
+        let err = migrate(deps.as_mut(), mock_env(), MigrateMsg { limit: None }).unwrap_err();
+        assert_eq!(
+            ContractError::MigrateVersionDowngrade {
+                stored: "99.0.0".to_string(),
+                current: CONTRACT_VERSION.to_string(),
+            },
+            err
+        );
+    }
+}
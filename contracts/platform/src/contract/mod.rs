@@ -1,5 +1,6 @@
 pub mod exec;
 pub mod init;
+pub mod migrate;
 pub mod query;
 pub mod reply;
 
@@ -8,13 +9,18 @@ use cosmwasm_std::entry_point;
 use cosmwasm_std::{Binary, Deps, DepsMut, Env, MessageInfo, Reply, Response, StdResult};
 
 use crate::error::ContractError;
-use crate::msg::{ExecuteMsg, InstantiateMsg, QueryMsg};
+use crate::msg::{ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg};
 
-pub const CREATE_LOTTERY_REPLY_ID: u64 = 1;
-pub const DRAW_LOTTERY_REPLY_ID: u64 = 2;
+/// Every lottery creation replies with its own id, allocated from here on
+pub const CREATE_LOTTERY_REPLY_ID_START: u64 = 1 << 32;
+
+pub const DEFAULT_LIMIT: u32 = 10;
+pub const MAX_LIMIT: u32 = 30;
 
 // version info for migration info
-const CONTRACT_NAME: &str = "crates.io:colottery-lottery";
+const CONTRACT_NAME: &str = "crates.io:colottery-platform";
+// 1.0.x was released under the lottery's contract name
+const LEGACY_CONTRACT_NAME: &str = "crates.io:colottery-lottery";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
 #[cfg_attr(not(feature = "library"), entry_point)]
@@ -42,6 +48,11 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     query::query(deps, env, msg)
 }
 
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+    migrate::migrate(deps, env, msg)
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn reply(deps: DepsMut, env: Env, reply: Reply) -> Result<Response, ContractError> {
     reply::reply(deps, env, reply)
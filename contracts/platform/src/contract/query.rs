@@ -1,48 +1,407 @@
-use cosmwasm_std::{to_binary, Binary, Deps, Env, Order, StdResult};
+use cosmwasm_std::{to_json_binary, Addr, Binary, Deps, Env, Order, StdResult};
+use cw_storage_plus::Bound;
 
 use crate::{
-    msg::{CurrentStateResp, LotteriesResp, OwnerResp, QueryMsg},
-    state::{LOTTERIES, OWNER, STATE},
+    auth::exec::has_role,
+    msg::{
+        CreationConfigResp, CreatorTotalsResp, CurrentStateResp, JackpotHistoryResp, JackpotResp,
+        LotteriesResp, LotteryCodesResp, LotteryFilter, OwnerResp, OwnershipResp, PauseResp,
+        PlayerEntriesResp, PlayerTotalsResp, PlayerWinsResp, PredictLotteryAddressResp, QueryMsg,
+        ReferrerTotalsResp, ResaleRoyaltyResp, RoleMembersResp, RolesResp, RolloverResp,
+    },
+    state::{
+        LotteryInfo, Role, CREATION_CONFIG, CREATOR_TOTALS, JACKPOT, JACKPOT_CONFIG,
+        JACKPOT_HISTORY, LOTTERIES, LOTTERY_CODES, LOTTERY_ROUNDS, OWNER, PAUSE, PENDING_OWNER,
+        PLAYER_ENTRIES, PLAYER_TOTALS, PLAYER_WINS, REFERRER_TOTALS, RESALE_ROYALTY_BPS,
+        ROLE_GROUPS, ROLE_MEMBERS, ROLLOVERS, STATE,
+    },
+};
+
+use super::{
+    exec::{lottery_address, lottery_salt},
+    DEFAULT_LIMIT, MAX_LIMIT,
 };
 
 pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::Owner {} => owner(deps),
+        QueryMsg::Ownership {} => ownership(deps),
         QueryMsg::CurrentState {} => current_state(deps),
         QueryMsg::Balances {} => balances(deps, &env),
-        // QueryMsg::Players {} => players(deps),
-        QueryMsg::Lotteries {} => lotteries(deps),
+        QueryMsg::Lotteries {
+            filter,
+            start_after,
+            limit,
+        } => lotteries(deps, filter, start_after, limit),
+        QueryMsg::LotteryCodes {} => lottery_codes(deps),
+        QueryMsg::PredictLotteryAddress {
+            creator,
+            series,
+            round,
+        } => predict_lottery_address(deps, &env, creator, series, round),
+        QueryMsg::OutdatedLotteries { start_after, limit } => {
+            outdated_lotteries(deps, start_after, limit)
+        }
+        QueryMsg::PlayerEntries {
+            player,
+            start_after,
+            limit,
+        } => player_entries(deps, player, start_after, limit),
+        QueryMsg::PlayerWins {
+            player,
+            unclaimed_only,
+            start_after,
+            limit,
+        } => player_wins(deps, player, unclaimed_only, start_after, limit),
+        QueryMsg::PlayerTotals { player } => player_totals(deps, player),
+        QueryMsg::CreationConfig {} => creation_config(deps),
+        QueryMsg::CreatorTotals { creator } => creator_totals(deps, creator),
+        QueryMsg::Rollover { creator, symbol } => rollover(deps, creator, symbol),
+        QueryMsg::Pause {} => pause(deps, &env),
+        QueryMsg::Jackpot {} => jackpot(deps),
+        QueryMsg::ResaleRoyalty {} => resale_royalty(deps),
+        QueryMsg::JackpotHistory { start_after, limit } => {
+            jackpot_history(deps, start_after, limit)
+        }
+        QueryMsg::ReferrerTotals { referrer } => referrer_totals(deps, referrer),
+        QueryMsg::Roles { address } => roles(deps, address),
+        QueryMsg::RoleMembers {
+            role,
+            start_after,
+            limit,
+        } => role_members(deps, role, start_after, limit),
     }
 }
 
 pub fn owner(deps: Deps) -> StdResult<Binary> {
     let owner = OWNER.load(deps.storage)?;
-    to_binary(&OwnerResp { owner })
+    to_json_binary(&OwnerResp { owner })
+}
+
+pub fn ownership(deps: Deps) -> StdResult<Binary> {
+    to_json_binary(&OwnershipResp {
+        owner: OWNER.may_load(deps.storage)?,
+        pending_owner: PENDING_OWNER.may_load(deps.storage)?,
+    })
 }
 
 pub fn current_state(deps: Deps) -> StdResult<Binary> {
     let state = STATE.load(deps.storage)?;
-    to_binary(&CurrentStateResp { state })
+    to_json_binary(&CurrentStateResp { state })
 }
 
 pub fn balances(deps: Deps, env: &Env) -> StdResult<Binary> {
     deps.querier
         .query_all_balances(&env.contract.address)
-        .and_then(|balances| to_binary(&balances))
+        .and_then(|balances| to_json_binary(&balances))
 }
 
-// pub fn players(deps: Deps) -> StdResult<Binary> {
-//     let players: StdResult<Vec<_>> = PLAYERS
-//         .range(deps.storage, None, None, Order::Ascending)
-//         .collect();
-//     let players = players?.into_iter().map(|(_, player)| player).collect();
-//     to_binary(&PlayersResp { players })
-// }
+type LotteryRecords<'a> = Box<dyn Iterator<Item = StdResult<(Addr, LotteryInfo)>> + 'a>;
 
-pub fn lotteries(deps: Deps) -> StdResult<Binary> {
-    let lotteries: StdResult<Vec<_>> = LOTTERIES
+pub fn lotteries(
+    deps: Deps,
+    filter: Option<LotteryFilter>,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<Binary> {
+    let filter = filter.unwrap_or_default();
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start_after = start_after
+        .map(|addr| deps.api.addr_validate(&addr))
+        .transpose()?;
+    let start = start_after.as_ref().map(Bound::exclusive);
+    let first_addr = Addr::unchecked("");
+
+    // Iterate over one secondary index, the rest of the filter is checked on each lottery
+    let records: LotteryRecords = if filter.by_created_at() {
+        let created_after = filter.created_after.unwrap_or_default();
+        let min = match start_after.as_ref() {
+            Some(addr) => {
+                let start_at = LOTTERIES.load(deps.storage, addr)?.created_at.seconds();
+                if start_at >= created_after {
+                    Bound::exclusive((start_at, addr))
+                } else {
+                    Bound::inclusive((created_after, &first_addr))
+                }
+            }
+            None => Bound::inclusive((created_after, &first_addr)),
+        };
+        let max = filter
+            .created_before
+            .map(|ts| Bound::exclusive((ts.saturating_add(1), &first_addr)));
+
+        LOTTERIES
+            .idx
+            .created_at
+            .range(deps.storage, Some(min), max, Order::Ascending)
+    } else if let Some(status) = &filter.status {
+        LOTTERIES.idx.status.prefix(status.to_string()).range(
+            deps.storage,
+            start,
+            None,
+            Order::Ascending,
+        )
+    } else if let Some(period) = &filter.period {
+        LOTTERIES.idx.period.prefix(period.to_string()).range(
+            deps.storage,
+            start,
+            None,
+            Order::Ascending,
+        )
+    } else if let Some(creator) = &filter.creator {
+        LOTTERIES.idx.creator.prefix(creator.clone()).range(
+            deps.storage,
+            start,
+            None,
+            Order::Ascending,
+        )
+    } else if let Some(denom) = &filter.denom {
+        LOTTERIES
+            .idx
+            .denom
+            .prefix(denom.clone())
+            .range(deps.storage, start, None, Order::Ascending)
+    } else {
+        LOTTERIES.range(deps.storage, start, None, Order::Ascending)
+    };
+
+    let lotteries: StdResult<Vec<_>> = records
+        .filter(|item| {
+            item.as_ref()
+                .map_or(true, |(_, lottery)| filter.matches(lottery))
+        })
+        .take(limit)
+        .map(|item| item.map(|(_, lottery)| lottery))
+        .collect();
+    to_json_binary(&LotteriesResp {
+        lotteries: lotteries?,
+    })
+}
+
+pub fn lottery_codes(deps: Deps) -> StdResult<Binary> {
+    let state = STATE.load(deps.storage)?;
+    let codes: StdResult<Vec<_>> = LOTTERY_CODES
         .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(_, code)| code))
+        .collect();
+    to_json_binary(&LotteryCodesResp {
+        current: state.lottery_code_id,
+        codes: codes?,
+    })
+}
+
+pub fn outdated_lotteries(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<Binary> {
+    let state = STATE.load(deps.storage)?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start_after = start_after
+        .map(|addr| deps.api.addr_validate(&addr))
+        .transpose()?;
+    let start = start_after.as_ref().map(Bound::exclusive);
+
+    let lotteries: StdResult<Vec<_>> = LOTTERIES
+        .range(deps.storage, start, None, Order::Ascending)
+        .filter(|item| {
+            item.as_ref()
+                .map_or(true, |(_, lottery)| lottery.is_outdated(&state))
+        })
+        .take(limit)
+        .map(|item| item.map(|(_, lottery)| lottery))
+        .collect();
+    to_json_binary(&LotteriesResp {
+        lotteries: lotteries?,
+    })
+}
+
+pub fn player_entries(
+    deps: Deps,
+    player: String,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<Binary> {
+    let player = deps.api.addr_validate(&player)?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start_after = start_after
+        .map(|addr| deps.api.addr_validate(&addr))
+        .transpose()?;
+    let start = start_after.as_ref().map(Bound::exclusive);
+
+    let entries: StdResult<Vec<_>> = PLAYER_ENTRIES
+        .prefix(&player)
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(_, entry)| entry))
+        .collect();
+    to_json_binary(&PlayerEntriesResp { entries: entries? })
+}
+
+pub fn player_wins(
+    deps: Deps,
+    player: String,
+    unclaimed_only: Option<bool>,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<Binary> {
+    let player = deps.api.addr_validate(&player)?;
+    let unclaimed_only = unclaimed_only.unwrap_or_default();
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start_after = start_after
+        .map(|addr| deps.api.addr_validate(&addr))
+        .transpose()?;
+    let start = start_after.as_ref().map(Bound::exclusive);
+
+    let wins: StdResult<Vec<_>> = PLAYER_WINS
+        .prefix(&player)
+        .range(deps.storage, start, None, Order::Ascending)
+        .filter(|item| {
+            item.as_ref()
+                .map_or(true, |(_, win)| !(unclaimed_only && win.claimed))
+        })
+        .take(limit)
+        .map(|item| item.map(|(_, win)| win))
+        .collect();
+    to_json_binary(&PlayerWinsResp { wins: wins? })
+}
+
+pub fn player_totals(deps: Deps, player: String) -> StdResult<Binary> {
+    let player = deps.api.addr_validate(&player)?;
+    let totals = PLAYER_TOTALS
+        .may_load(deps.storage, &player)?
+        .unwrap_or_default();
+    to_json_binary(&PlayerTotalsResp { totals })
+}
+
+pub fn predict_lottery_address(
+    deps: Deps,
+    env: &Env,
+    creator: String,
+    series: String,
+    round: Option<u64>,
+) -> StdResult<Binary> {
+    let creator = deps.api.addr_validate(&creator)?;
+    let round = match round {
+        Some(round) => round,
+        None => {
+            LOTTERY_ROUNDS
+                .may_load(deps.storage, (&creator, &series))?
+                .unwrap_or_default()
+                + 1
+        }
+    };
+
+    let state = STATE.load(deps.storage)?;
+    let salt = lottery_salt(&creator, &series, round);
+    let address = lottery_address(deps, env, state.lottery_code_id, &salt)?;
+
+    to_json_binary(&PredictLotteryAddressResp {
+        address,
+        round,
+        salt,
+    })
+}
+
+pub fn creation_config(deps: Deps) -> StdResult<Binary> {
+    let config = CREATION_CONFIG.may_load(deps.storage)?.unwrap_or_default();
+    to_json_binary(&CreationConfigResp { config })
+}
+
+pub fn creator_totals(deps: Deps, creator: String) -> StdResult<Binary> {
+    let creator = deps.api.addr_validate(&creator)?;
+    let totals = CREATOR_TOTALS
+        .may_load(deps.storage, &creator)?
+        .unwrap_or_default();
+    to_json_binary(&CreatorTotalsResp { totals })
+}
+
+pub fn rollover(deps: Deps, creator: String, symbol: String) -> StdResult<Binary> {
+    let creator = deps.api.addr_validate(&creator)?;
+    let rollover = ROLLOVERS
+        .may_load(deps.storage, (&creator, &symbol))?
+        .unwrap_or_default();
+    to_json_binary(&RolloverResp { rollover })
+}
+
+pub fn pause(deps: Deps, env: &Env) -> StdResult<Binary> {
+    let pause = PAUSE
+        .may_load(deps.storage)?
+        .filter(|pause| pause.is_active(env.block.time));
+    to_json_binary(&PauseResp { pause })
+}
+
+pub fn jackpot(deps: Deps) -> StdResult<Binary> {
+    to_json_binary(&JackpotResp {
+        config: JACKPOT_CONFIG.may_load(deps.storage)?,
+        balance: JACKPOT.may_load(deps.storage)?.unwrap_or_default(),
+    })
+}
+
+pub fn resale_royalty(deps: Deps) -> StdResult<Binary> {
+    to_json_binary(&ResaleRoyaltyResp {
+        bps: RESALE_ROYALTY_BPS.may_load(deps.storage)?,
+    })
+}
+
+pub fn jackpot_history(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Binary> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    let records: StdResult<Vec<_>> = JACKPOT_HISTORY
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(_, record)| record))
+        .collect();
+    to_json_binary(&JackpotHistoryResp { records: records? })
+}
+
+pub fn referrer_totals(deps: Deps, referrer: String) -> StdResult<Binary> {
+    let referrer = deps.api.addr_validate(&referrer)?;
+    let totals = REFERRER_TOTALS
+        .may_load(deps.storage, &referrer)?
+        .unwrap_or_default();
+    to_json_binary(&ReferrerTotalsResp { totals })
+}
+
+/// Roles held by `address`, including the ones held through a cw4 group
+pub fn roles(deps: Deps, address: String) -> StdResult<Binary> {
+    let address = deps.api.addr_validate(&address)?;
+
+    let mut roles = vec![];
+    for role in Role::ALL {
+        if has_role(deps, role, &address)? {
+            roles.push(role);
+        }
+    }
+    to_json_binary(&RolesResp { roles })
+}
+
+/// Granted members of `role`, the members of its cw4 group are listed by the group
+pub fn role_members(
+    deps: Deps,
+    role: Role,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<Binary> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start_after = start_after
+        .map(|addr| deps.api.addr_validate(&addr))
+        .transpose()?;
+    let start = start_after.as_ref().map(Bound::exclusive);
+
+    let members: StdResult<Vec<_>> = ROLE_MEMBERS
+        .prefix(role.as_str())
+        .keys(deps.storage, start, None, Order::Ascending)
+        .take(limit)
         .collect();
-    let lotteries = lotteries?.into_iter().map(|(_, lottery)| lottery).collect();
-    to_binary(&LotteriesResp { lotteries })
+    let group = ROLE_GROUPS.may_load(deps.storage, role.as_str())?;
+    to_json_binary(&RoleMembersResp {
+        members: members?,
+        group,
+    })
 }
@@ -1,18 +1,16 @@
-use cosmwasm_std::{attr, to_binary, DepsMut, Env, Reply, Response, StdError, SubMsgResponse};
+use cosmwasm_std::{
+    attr, ensure, to_json_binary, DepsMut, Env, Reply, Response, StdError, SubMsgResponse,
+};
 use cw_utils::parse_instantiate_response_data;
 
-use crate::{
-    msg::InstantiationData,
-    state::{LOTTERIES, PENDING_LOTTERY, STATE},
-    ContractError,
-};
+use crate::{msg::InstantiationData, state::PENDING_LOTTERIES, ContractError};
 
-use super::CREATE_LOTTERY_REPLY_ID;
+use super::CREATE_LOTTERY_REPLY_ID_START;
 
 pub fn reply(deps: DepsMut, env: Env, reply: Reply) -> Result<Response, ContractError> {
     match reply.id {
-        CREATE_LOTTERY_REPLY_ID => {
-            initial_lottery_instantiated(deps, env, reply.result.into_result())
+        id if id >= CREATE_LOTTERY_REPLY_ID_START => {
+            initial_lottery_instantiated(deps, env, id, reply.result.into_result())
         }
         id => Err(ContractError::UnRecognizedReplyId { id }),
     }
@@ -21,6 +19,7 @@ pub fn reply(deps: DepsMut, env: Env, reply: Reply) -> Result<Response, Contract
 pub fn initial_lottery_instantiated(
     deps: DepsMut,
     _env: Env,
+    reply_id: u64,
     reply: Result<SubMsgResponse, String>,
 ) -> Result<Response, ContractError> {
     // Parse data from reply
@@ -30,22 +29,28 @@ pub fn initial_lottery_instantiated(
 
     let lottery_addr = &deps.api.addr_validate(&resp.contract_address)?;
 
-    let mut lottery = PENDING_LOTTERY.load(deps.storage)?;
-    lottery.contract_addr = lottery_addr.to_owned();
+    let predicted = PENDING_LOTTERIES
+        .may_load(deps.storage, reply_id)?
+        .ok_or(ContractError::UnRecognizedReplyId { id: reply_id })?;
+    PENDING_LOTTERIES.remove(deps.storage, reply_id);
 
-    LOTTERIES.save(deps.storage, lottery_addr, &lottery)?;
-
-    STATE.update(deps.storage, |mut state| -> Result<_, ContractError> {
-        state.lotteries_count += 1;
-        Ok(state)
-    })?;
+    ensure!(
+        lottery_addr == predicted,
+        ContractError::LotteryAddressMismatch {
+            predicted,
+            actual: lottery_addr.to_owned(),
+        }
+    );
 
-    let attrs = vec![attr("action", "reply_create_lottery")];
+    let attrs = vec![
+        attr("action", "reply_create_lottery"),
+        attr("lottery", lottery_addr),
+    ];
 
     let data = InstantiationData {
         addr: lottery_addr.to_owned(),
     };
-    let data = to_binary(&data)?;
+    let data = to_json_binary(&data)?;
 
     Ok(Response::new().add_attributes(attrs).set_data(data))
 }
@@ -1,5 +1,5 @@
-use cosmwasm_std::StdError;
-use cw_utils::ParseReplyError;
+use cosmwasm_std::{Addr, Coin, Decimal, OverflowError, StdError};
+use cw_utils::{ParseReplyError, PaymentError};
 use thiserror::Error;
 
 use lottery::ContractError as LotteryContractError;
@@ -9,9 +9,18 @@ pub enum ContractError {
     #[error("{0}")]
     Std(#[from] StdError),
 
+    #[error("{0}")]
+    Overflow(#[from] OverflowError),
+
     #[error("Unauthorized")]
     Unauthorized {},
 
+    #[error("{address} is already granted the {role} role")]
+    RoleAlreadyGranted { address: String, role: String },
+
+    #[error("{address} is not granted the {role} role")]
+    RoleNotGranted { address: String, role: String },
+
     #[error("{id} is an unknown reply id")]
     UnRecognizedReplyId { id: u64 },
 
@@ -21,6 +30,51 @@ pub enum ContractError {
     #[error("{0}")]
     ParseErr(#[from] ParseReplyError),
 
+    #[error("Lottery: {lottery} is not created by this platform")]
+    LotteryNotFound { lottery: String },
+
+    #[error("Lottery code: {code_id} is already in use")]
+    LotteryCodeUnchanged { code_id: u64 },
+
+    #[error("Lottery is instantiated at {actual} instead of the predicted {predicted}")]
+    LotteryAddressMismatch { predicted: Addr, actual: Addr },
+
+    #[error("Creating the lotteries requires a deposit of {expected}")]
+    InvalidDeposit { expected: Coin },
+
+    #[error("Commission rate: {rate} must not exceed 1")]
+    InvalidCommission { rate: Decimal },
+
+    #[error("No slashed deposit to withdraw")]
+    NothingSlashed {},
+
+    #[error("{0}")]
+    Payment(#[from] PaymentError),
+
+    #[error("Platform is paused: {reason}")]
+    Paused { reason: String },
+
+    #[error("No lottery to create")]
+    NoLotteriesToCreate {},
+
+    #[error("Scratch lotteries are funded at instantiation, the platform can't create them")]
+    ScratchNotSupported {},
+
     #[error("{0}")]
     LotteryContractErr(#[from] LotteryContractError),
+
+    #[error("Can't migrate from contract: {contract}")]
+    MigrateContractMismatch { contract: String },
+
+    #[error("Can't migrate from version {stored} to older version {current}")]
+    MigrateVersionDowngrade { stored: String, current: String },
+
+    #[error("Invalid contract version: {0}")]
+    InvalidContractVersion(String),
+}
+
+impl From<semver::Error> for ContractError {
+    fn from(err: semver::Error) -> Self {
+        ContractError::InvalidContractVersion(err.to_string())
+    }
 }
@@ -1,6 +1,12 @@
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::Addr;
-use lottery::state::WinnerSelection;
+use cosmwasm_std::{Addr, Binary, Coin, Decimal};
+use cw_utils::Expiration;
+use lottery::{
+    msg::{AllowlistMsg, LotteryHookMsg, NftGateMsg},
+    state::{Pricing, ProgressiveJackpot, WinnerSelection},
+};
+
+use crate::state::Role;
 
 #[allow(clippy::large_enum_variant)]
 #[cw_serde]
@@ -15,13 +21,137 @@ pub enum ExecuteMsg {
         selection: WinnerSelection,
         max_players: u64,
         label: String,
+        allowlist: Option<AllowlistMsg>,
+        nft_gate: Option<NftGateMsg>,
+        /// Share of the ticket price credited to the referrers, in basis points
+        referral_bps: Option<u16>,
+        /// Early bird, last call and bulk prices of the tickets
+        pricing: Option<Pricing>,
+    },
+    /// Create all the lotteries or none of them
+    CreateLotteries {
+        lotteries: Vec<CreateLotteryMsg>,
     },
     DrawLottery {
         lottery: String,
     },
+    CancelLottery {
+        lottery: String,
+    },
+    /// Edit the inline allowlist of a lottery, by its creator or an admin
+    UpdateAllowlist {
+        lottery: String,
+        add: Vec<String>,
+        remove: Vec<String>,
+    },
+    /// Lifecycle notifications from the lotteries created by the platform
+    LotteryHook(LotteryHookMsg),
+    /// Create new lotteries from `code_id`, the existing ones keep their code
+    UpdateLotteryCode {
+        code_id: u64,
+    },
+    /// Migrate the lotteries not running `code_id` yet among the next `limit` ones after
+    /// `start_after`. The response data holds the last lottery visited, pass it as
+    /// `start_after` to the next call until it's `None`.
+    MigrateLotteries {
+        code_id: u64,
+        msg: Binary,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Open lottery creation to anyone paying `deposit` per lottery, the creators
+    /// earn `commission` on the pot of their lotteries
+    UpdateCreationConfig {
+        deposit: Option<Coin>,
+        commission: Decimal,
+    },
+    /// Send the deposits slashed from the creators to `recipient`, the sender by default
+    WithdrawSlashed {
+        recipient: Option<String>,
+    },
+    /// Lotteries created from now on contribute to the progressive jackpot, `None` stops it.
+    /// The jackpot balance is kept.
+    UpdateJackpotConfig {
+        config: Option<ProgressiveJackpot>,
+    },
+    /// Share of the ticket resales of the lotteries created from now on, in basis points.
+    /// `None` stops it.
+    UpdateResaleRoyalty {
+        bps: Option<u16>,
+    },
+    /// Stop creating and drawing lotteries, by an admin or a guardian.
+    /// The lotteries don't check the platform pause: their tickets are still sold, settled,
+    /// refunded and resold until they're paused themselves by `PauseLotteries`.
+    Pause {
+        reason: String,
+        /// Seconds since epoch, the pause lasts until `Unpause` without it
+        expires_at: Option<u64>,
+    },
+    Unpause {},
+    /// Pause the next `limit` active lotteries after `start_after`. Like `MigrateLotteries`,
+    /// the response data holds the last lottery paused to resume from.
+    PauseLotteries {
+        reason: String,
+        expires_at: Option<u64>,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Unpause the next `limit` active lotteries after `start_after`
+    UnpauseLotteries {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Offer the ownership to `new_owner`, who takes it over with `AcceptOwnership`
+    /// until `expiry`. A new proposal replaces the pending one.
+    ProposeOwner {
+        new_owner: String,
+        expiry: Option<Expiration>,
+    },
+    AcceptOwnership {},
+    CancelOwnershipTransfer {},
+    /// Give up the owner powers for good, the granted admins keep theirs
+    RenounceOwnership {},
+    /// Admin only, the owner is always an admin
+    GrantRole {
+        role: Role,
+        address: String,
+    },
+    RevokeRole {
+        role: Role,
+        address: String,
+    },
+    /// Members of the cw4 `group` hold `role` besides the granted addresses
+    SetRoleGroup {
+        role: Role,
+        group: Option<String>,
+    },
+}
+
+#[cw_serde]
+pub struct CreateLotteryMsg {
+    pub name: String,
+    pub symbol: String,
+    pub unit_price_amount: u128,
+    pub unit_price_denom: String,
+    pub period: String,
+    pub expiration: u64,
+    pub selection: WinnerSelection,
+    pub max_players: u64,
+    pub label: String,
+    pub allowlist: Option<AllowlistMsg>,
+    pub nft_gate: Option<NftGateMsg>,
+    pub referral_bps: Option<u16>,
+    /// Early bird, last call and bulk prices of the tickets
+    pub pricing: Option<Pricing>,
 }
 
 #[cw_serde]
 pub struct InstantiationData {
     pub addr: Addr,
 }
+
+/// Response data of the messages handling the lotteries in batches
+#[cw_serde]
+pub struct LotteryBatchData {
+    pub last_lottery: Option<Addr>,
+}
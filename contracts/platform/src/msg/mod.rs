@@ -20,3 +20,10 @@ impl InstantiateMsg {
         }
     }
 }
+
+#[cw_serde]
+pub struct MigrateMsg {
+    /// How many 1.0.x lotteries to convert in this call. The version is only bumped once
+    /// they're all converted, migrate to the same code again until then.
+    pub limit: Option<u32>,
+}
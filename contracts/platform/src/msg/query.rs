@@ -1,22 +1,134 @@
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::{Addr, Coin};
-use lottery::state::PlayerInfo;
+use cosmwasm_std::{Addr, Binary, Coin};
+use lottery::state::{LotteryPeriod, PauseInfo, PendingOwner, ProgressiveJackpot};
 
-use crate::state::{LotteryInfo, State};
+use crate::state::{
+    CreationConfig, CreatorTotals, JackpotRecord, LotteryCode, LotteryInfo, LotteryStatus,
+    PlayerEntry, PlayerTotals, PlayerWin, ReferrerTotals, Role, State,
+};
 
 #[cw_serde]
 #[derive(QueryResponses)]
 pub enum QueryMsg {
-    // #[returns(PlayersResp)]
-    // Players {},
+    /// Ordered by address, or by creation time when filtered by a creation time range
     #[returns(LotteriesResp)]
-    Lotteries {},
+    Lotteries {
+        filter: Option<LotteryFilter>,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
     #[returns(BalancesResp)]
     Balances {},
     #[returns(OwnerResp)]
     Owner {},
+    /// The owner, `None` once renounced, and the proposed one
+    #[returns(OwnershipResp)]
+    Ownership {},
     #[returns(CurrentStateResp)]
     CurrentState {},
+    /// The current lottery code and every activation, oldest first
+    #[returns(LotteryCodesResp)]
+    LotteryCodes {},
+    /// Where `creator`'s lottery of the `series` symbol is created in `round`,
+    /// which defaults to the next round
+    #[returns(PredictLotteryAddressResp)]
+    PredictLotteryAddress {
+        creator: String,
+        series: String,
+        round: Option<u64>,
+    },
+    /// Lotteries which were not created from, or migrated to, the current lottery code
+    #[returns(LotteriesResp)]
+    OutdatedLotteries {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Tickets of `player` in the platform's lotteries, `start_after` is a lottery address
+    #[returns(PlayerEntriesResp)]
+    PlayerEntries {
+        player: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    #[returns(PlayerWinsResp)]
+    PlayerWins {
+        player: String,
+        unclaimed_only: Option<bool>,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    #[returns(PlayerTotalsResp)]
+    PlayerTotals { player: String },
+    #[returns(CreationConfigResp)]
+    CreationConfig {},
+    /// Lotteries hosted by `creator` are listed by `Lotteries` filtered by creator
+    #[returns(CreatorTotalsResp)]
+    CreatorTotals { creator: String },
+    /// Left by the prize tiers without winners, to fund the next round of the series
+    #[returns(RolloverResp)]
+    Rollover { creator: String, symbol: String },
+    /// The pause of the platform in effect, if any
+    #[returns(PauseResp)]
+    Pause {},
+    /// The progressive jackpot by denom and the contribution of new lotteries
+    #[returns(JackpotResp)]
+    Jackpot {},
+    /// The share of the ticket resales of new lotteries, in basis points
+    #[returns(ResaleRoyaltyResp)]
+    ResaleRoyalty {},
+    /// Contributions to and payouts of the progressive jackpot, `start_after` is a record id
+    #[returns(JackpotHistoryResp)]
+    JackpotHistory {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    #[returns(ReferrerTotalsResp)]
+    ReferrerTotals { referrer: String },
+    /// Roles of `address`, directly granted or through a cw4 group
+    #[returns(RolesResp)]
+    Roles { address: String },
+    #[returns(RoleMembersResp)]
+    RoleMembers {
+        role: Role,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+}
+
+#[cw_serde]
+#[derive(Default)]
+pub struct LotteryFilter {
+    pub period: Option<LotteryPeriod>,
+    pub status: Option<LotteryStatus>,
+    pub denom: Option<String>,
+    /// Seconds since epoch, inclusive
+    pub created_after: Option<u64>,
+    /// Seconds since epoch, inclusive
+    pub created_before: Option<u64>,
+    pub creator: Option<String>,
+}
+
+impl LotteryFilter {
+    pub fn by_created_at(&self) -> bool {
+        self.created_after.is_some() || self.created_before.is_some()
+    }
+
+    pub fn matches(&self, lottery: &LotteryInfo) -> bool {
+        let created_at = lottery.created_at.seconds();
+
+        self.period.as_ref().is_none_or(|p| *p == lottery.period)
+            && self.status.as_ref().is_none_or(|s| *s == lottery.status)
+            && self
+                .denom
+                .as_ref()
+                .is_none_or(|d| *d == lottery.unit_price.denom)
+            && self.created_after.is_none_or(|ts| created_at >= ts)
+            && self.created_before.is_none_or(|ts| created_at <= ts)
+            && self
+                .creator
+                .as_ref()
+                .is_none_or(|c| c == lottery.creator.as_str())
+    }
 }
 
 #[cw_serde]
@@ -35,11 +147,93 @@ pub struct OwnerResp {
 }
 
 #[cw_serde]
-pub struct PlayersResp {
-    pub players: Vec<PlayerInfo>,
+pub struct PlayerEntriesResp {
+    pub entries: Vec<PlayerEntry>,
+}
+
+#[cw_serde]
+pub struct PlayerWinsResp {
+    pub wins: Vec<PlayerWin>,
+}
+
+#[cw_serde]
+pub struct PlayerTotalsResp {
+    pub totals: PlayerTotals,
 }
 
 #[cw_serde]
 pub struct LotteriesResp {
     pub lotteries: Vec<LotteryInfo>,
 }
+
+#[cw_serde]
+pub struct LotteryCodesResp {
+    pub current: u64,
+    pub codes: Vec<LotteryCode>,
+}
+
+#[cw_serde]
+pub struct PredictLotteryAddressResp {
+    pub address: Addr,
+    pub round: u64,
+    pub salt: Binary,
+}
+
+#[cw_serde]
+pub struct CreationConfigResp {
+    pub config: CreationConfig,
+}
+
+#[cw_serde]
+pub struct CreatorTotalsResp {
+    pub totals: CreatorTotals,
+}
+
+#[cw_serde]
+pub struct RolloverResp {
+    pub rollover: Vec<Coin>,
+}
+
+#[cw_serde]
+pub struct OwnershipResp {
+    pub owner: Option<Addr>,
+    pub pending_owner: Option<PendingOwner>,
+}
+
+#[cw_serde]
+pub struct PauseResp {
+    pub pause: Option<PauseInfo>,
+}
+
+#[cw_serde]
+pub struct ResaleRoyaltyResp {
+    pub bps: Option<u16>,
+}
+
+#[cw_serde]
+pub struct JackpotResp {
+    pub config: Option<ProgressiveJackpot>,
+    pub balance: Vec<Coin>,
+}
+
+#[cw_serde]
+pub struct JackpotHistoryResp {
+    pub records: Vec<JackpotRecord>,
+}
+
+#[cw_serde]
+pub struct ReferrerTotalsResp {
+    pub totals: ReferrerTotals,
+}
+
+#[cw_serde]
+pub struct RolesResp {
+    pub roles: Vec<Role>,
+}
+
+#[cw_serde]
+pub struct RoleMembersResp {
+    pub members: Vec<Addr>,
+    /// Its members hold the role as well
+    pub group: Option<Addr>,
+}
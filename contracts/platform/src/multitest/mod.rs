@@ -3,13 +3,19 @@ mod tests;
 
 use anyhow::Result as AnyResult;
 
-use cosmwasm_std::{from_binary, Addr, Coin, StdResult};
-use cw_multi_test::{App, AppResponse, ContractWrapper, Executor};
-use lottery::state::WinnerSelection;
+use cosmwasm_std::{from_json, to_json_binary, Addr, Coin, Decimal, StdResult};
+use cw_multi_test::{AppResponse, ContractWrapper, Executor};
+use cw_utils::Expiration;
+use lottery::multitest::App;
+use lottery::{
+    msg::LotteryHookMsg,
+    state::{ProgressiveJackpot, WinnerSelection},
+};
 
 use crate::{
-    contract::{execute, instantiate, query, reply},
+    contract::{execute, instantiate, migrate, query, reply},
     msg::*,
+    state::Role,
 };
 
 #[derive(Clone, Debug, Copy)]
@@ -17,7 +23,9 @@ pub struct PlatformCodeId(u64);
 
 impl PlatformCodeId {
     pub fn store_code(app: &mut App) -> Self {
-        let contract = ContractWrapper::new(execute, instantiate, query).with_reply(reply);
+        let contract = ContractWrapper::new(execute, instantiate, query)
+            .with_reply(reply)
+            .with_migrate(migrate);
         let code_id = app.store_code(Box::new(contract));
         Self(code_id)
     }
@@ -99,19 +107,36 @@ impl PlatformContract {
             selection,
             max_players,
             label: label.into(),
+            allowlist: None,
+            nft_gate: None,
+            referral_bps: None,
+            pricing: None,
         };
 
-        let resp = app
-            .execute_contract(sender, self.addr(), &msg, &[])
-            .unwrap();
+        let resp = app.execute_contract(sender, self.addr(), &msg, &[])?;
 
         // println!("execute create lottery resp:{:?}", resp);
 
-        let data = from_binary(&resp.data.unwrap()).unwrap();
+        let data = resp.data.map(from_json).transpose()?;
 
         Ok(data)
     }
 
+    pub fn create_lotteries(
+        &self,
+        app: &mut App,
+        sender: Addr,
+        lotteries: Vec<CreateLotteryMsg>,
+        funds: &[Coin],
+    ) -> AnyResult<AppResponse> {
+        app.execute_contract(
+            sender,
+            self.addr(),
+            &ExecuteMsg::CreateLotteries { lotteries },
+            funds,
+        )
+    }
+
     // #[track_caller]
     // pub fn buy_lottery(
     //     &self,
@@ -151,9 +176,461 @@ impl PlatformContract {
         )
     }
 
-    pub fn lotteries(&self, app: &App) -> StdResult<LotteriesResp> {
+    #[track_caller]
+    pub fn cancel_lottery(
+        &self,
+        app: &mut App,
+        sender: Addr,
+        lottery: &str,
+    ) -> AnyResult<AppResponse> {
+        app.execute_contract(
+            sender,
+            self.addr(),
+            &ExecuteMsg::CancelLottery {
+                lottery: lottery.into(),
+            },
+            &[],
+        )
+    }
+
+    #[track_caller]
+    pub fn lottery_hook(
+        &self,
+        app: &mut App,
+        sender: Addr,
+        hook: LotteryHookMsg,
+    ) -> AnyResult<AppResponse> {
+        app.execute_contract(sender, self.addr(), &ExecuteMsg::LotteryHook(hook), &[])
+    }
+
+    #[track_caller]
+    pub fn migrate_lotteries(
+        &self,
+        app: &mut App,
+        sender: Addr,
+        code_id: u64,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> AnyResult<Option<Addr>> {
+        let resp = app.execute_contract(
+            sender,
+            self.addr(),
+            &ExecuteMsg::MigrateLotteries {
+                code_id,
+                msg: to_json_binary(&lottery::msg::MigrateMsg { platform: None })?,
+                start_after,
+                limit,
+            },
+            &[],
+        )?;
+
+        let data: Option<LotteryBatchData> = resp.data.map(from_json).transpose()?;
+
+        Ok(data.and_then(|data| data.last_lottery))
+    }
+
+    #[track_caller]
+    pub fn update_lottery_code(
+        &self,
+        app: &mut App,
+        sender: Addr,
+        code_id: u64,
+    ) -> AnyResult<AppResponse> {
+        app.execute_contract(
+            sender,
+            self.addr(),
+            &ExecuteMsg::UpdateLotteryCode { code_id },
+            &[],
+        )
+    }
+
+    #[track_caller]
+    pub fn update_allowlist(
+        &self,
+        app: &mut App,
+        sender: Addr,
+        lottery: &str,
+        add: Vec<String>,
+        remove: Vec<String>,
+    ) -> AnyResult<AppResponse> {
+        app.execute_contract(
+            sender,
+            self.addr(),
+            &ExecuteMsg::UpdateAllowlist {
+                lottery: lottery.into(),
+                add,
+                remove,
+            },
+            &[],
+        )
+    }
+
+    #[track_caller]
+    pub fn update_creation_config(
+        &self,
+        app: &mut App,
+        sender: Addr,
+        deposit: Option<Coin>,
+        commission: Decimal,
+    ) -> AnyResult<AppResponse> {
+        app.execute_contract(
+            sender,
+            self.addr(),
+            &ExecuteMsg::UpdateCreationConfig {
+                deposit,
+                commission,
+            },
+            &[],
+        )
+    }
+
+    #[track_caller]
+    pub fn propose_owner(
+        &self,
+        app: &mut App,
+        sender: Addr,
+        new_owner: &str,
+        expiry: Option<Expiration>,
+    ) -> AnyResult<AppResponse> {
+        app.execute_contract(
+            sender,
+            self.addr(),
+            &ExecuteMsg::ProposeOwner {
+                new_owner: new_owner.into(),
+                expiry,
+            },
+            &[],
+        )
+    }
+
+    #[track_caller]
+    pub fn accept_ownership(&self, app: &mut App, sender: Addr) -> AnyResult<AppResponse> {
+        app.execute_contract(sender, self.addr(), &ExecuteMsg::AcceptOwnership {}, &[])
+    }
+
+    #[track_caller]
+    pub fn cancel_ownership_transfer(&self, app: &mut App, sender: Addr) -> AnyResult<AppResponse> {
+        app.execute_contract(
+            sender,
+            self.addr(),
+            &ExecuteMsg::CancelOwnershipTransfer {},
+            &[],
+        )
+    }
+
+    #[track_caller]
+    pub fn renounce_ownership(&self, app: &mut App, sender: Addr) -> AnyResult<AppResponse> {
+        app.execute_contract(sender, self.addr(), &ExecuteMsg::RenounceOwnership {}, &[])
+    }
+
+    #[track_caller]
+    pub fn pause(
+        &self,
+        app: &mut App,
+        sender: Addr,
+        reason: &str,
+        expires_at: Option<u64>,
+    ) -> AnyResult<AppResponse> {
+        app.execute_contract(
+            sender,
+            self.addr(),
+            &ExecuteMsg::Pause {
+                reason: reason.into(),
+                expires_at,
+            },
+            &[],
+        )
+    }
+
+    #[track_caller]
+    pub fn unpause(&self, app: &mut App, sender: Addr) -> AnyResult<AppResponse> {
+        app.execute_contract(sender, self.addr(), &ExecuteMsg::Unpause {}, &[])
+    }
+
+    #[track_caller]
+    pub fn pause_lotteries(
+        &self,
+        app: &mut App,
+        sender: Addr,
+        reason: &str,
+        expires_at: Option<u64>,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> AnyResult<Option<Addr>> {
+        let resp = app.execute_contract(
+            sender,
+            self.addr(),
+            &ExecuteMsg::PauseLotteries {
+                reason: reason.into(),
+                expires_at,
+                start_after,
+                limit,
+            },
+            &[],
+        )?;
+
+        let data: Option<LotteryBatchData> = resp.data.map(from_json).transpose()?;
+
+        Ok(data.and_then(|data| data.last_lottery))
+    }
+
+    #[track_caller]
+    pub fn unpause_lotteries(
+        &self,
+        app: &mut App,
+        sender: Addr,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> AnyResult<Option<Addr>> {
+        let resp = app.execute_contract(
+            sender,
+            self.addr(),
+            &ExecuteMsg::UnpauseLotteries { start_after, limit },
+            &[],
+        )?;
+
+        let data: Option<LotteryBatchData> = resp.data.map(from_json).transpose()?;
+
+        Ok(data.and_then(|data| data.last_lottery))
+    }
+
+    #[track_caller]
+    pub fn update_jackpot_config(
+        &self,
+        app: &mut App,
+        sender: Addr,
+        config: Option<ProgressiveJackpot>,
+    ) -> AnyResult<AppResponse> {
+        app.execute_contract(
+            sender,
+            self.addr(),
+            &ExecuteMsg::UpdateJackpotConfig { config },
+            &[],
+        )
+    }
+
+    #[track_caller]
+    pub fn withdraw_slashed(
+        &self,
+        app: &mut App,
+        sender: Addr,
+        recipient: Option<String>,
+    ) -> AnyResult<AppResponse> {
+        app.execute_contract(
+            sender,
+            self.addr(),
+            &ExecuteMsg::WithdrawSlashed { recipient },
+            &[],
+        )
+    }
+
+    pub fn update_resale_royalty(
+        &self,
+        app: &mut App,
+        sender: Addr,
+        bps: Option<u16>,
+    ) -> AnyResult<AppResponse> {
+        app.execute_contract(
+            sender,
+            self.addr(),
+            &ExecuteMsg::UpdateResaleRoyalty { bps },
+            &[],
+        )
+    }
+
+    #[track_caller]
+    pub fn grant_role(
+        &self,
+        app: &mut App,
+        sender: Addr,
+        role: Role,
+        address: &str,
+    ) -> AnyResult<AppResponse> {
+        app.execute_contract(
+            sender,
+            self.addr(),
+            &ExecuteMsg::GrantRole {
+                role,
+                address: address.into(),
+            },
+            &[],
+        )
+    }
+
+    #[track_caller]
+    pub fn revoke_role(
+        &self,
+        app: &mut App,
+        sender: Addr,
+        role: Role,
+        address: &str,
+    ) -> AnyResult<AppResponse> {
+        app.execute_contract(
+            sender,
+            self.addr(),
+            &ExecuteMsg::RevokeRole {
+                role,
+                address: address.into(),
+            },
+            &[],
+        )
+    }
+
+    #[track_caller]
+    pub fn set_role_group(
+        &self,
+        app: &mut App,
+        sender: Addr,
+        role: Role,
+        group: Option<&str>,
+    ) -> AnyResult<AppResponse> {
+        app.execute_contract(
+            sender,
+            self.addr(),
+            &ExecuteMsg::SetRoleGroup {
+                role,
+                group: group.map(Into::into),
+            },
+            &[],
+        )
+    }
+
+    pub fn creation_config(&self, app: &App) -> StdResult<CreationConfigResp> {
         app.wrap()
-            .query_wasm_smart(self.addr(), &QueryMsg::Lotteries {})
+            .query_wasm_smart(self.addr(), &QueryMsg::CreationConfig {})
+    }
+
+    pub fn creator_totals(&self, app: &App, creator: &str) -> StdResult<CreatorTotalsResp> {
+        app.wrap().query_wasm_smart(
+            self.addr(),
+            &QueryMsg::CreatorTotals {
+                creator: creator.into(),
+            },
+        )
+    }
+
+    pub fn rollover(&self, app: &App, creator: &str, symbol: &str) -> StdResult<RolloverResp> {
+        app.wrap().query_wasm_smart(
+            self.addr(),
+            &QueryMsg::Rollover {
+                creator: creator.into(),
+                symbol: symbol.into(),
+            },
+        )
+    }
+
+    pub fn ownership(&self, app: &App) -> StdResult<OwnershipResp> {
+        app.wrap()
+            .query_wasm_smart(self.addr(), &QueryMsg::Ownership {})
+    }
+
+    pub fn pause_info(&self, app: &App) -> StdResult<PauseResp> {
+        app.wrap()
+            .query_wasm_smart(self.addr(), &QueryMsg::Pause {})
+    }
+
+    pub fn resale_royalty(&self, app: &App) -> StdResult<ResaleRoyaltyResp> {
+        app.wrap()
+            .query_wasm_smart(self.addr(), &QueryMsg::ResaleRoyalty {})
+    }
+
+    pub fn jackpot(&self, app: &App) -> StdResult<JackpotResp> {
+        app.wrap()
+            .query_wasm_smart(self.addr(), &QueryMsg::Jackpot {})
+    }
+
+    pub fn jackpot_history(&self, app: &App) -> StdResult<JackpotHistoryResp> {
+        app.wrap().query_wasm_smart(
+            self.addr(),
+            &QueryMsg::JackpotHistory {
+                start_after: None,
+                limit: None,
+            },
+        )
+    }
+
+    pub fn referrer_totals(&self, app: &App, referrer: &str) -> StdResult<ReferrerTotalsResp> {
+        app.wrap().query_wasm_smart(
+            self.addr(),
+            &QueryMsg::ReferrerTotals {
+                referrer: referrer.into(),
+            },
+        )
+    }
+
+    pub fn roles(&self, app: &App, address: &str) -> StdResult<RolesResp> {
+        app.wrap().query_wasm_smart(
+            self.addr(),
+            &QueryMsg::Roles {
+                address: address.into(),
+            },
+        )
+    }
+
+    pub fn role_members(&self, app: &App, role: Role) -> StdResult<RoleMembersResp> {
+        app.wrap().query_wasm_smart(
+            self.addr(),
+            &QueryMsg::RoleMembers {
+                role,
+                start_after: None,
+                limit: None,
+            },
+        )
+    }
+
+    pub fn predict_lottery_address(
+        &self,
+        app: &App,
+        creator: &str,
+        series: &str,
+        round: Option<u64>,
+    ) -> StdResult<PredictLotteryAddressResp> {
+        app.wrap().query_wasm_smart(
+            self.addr(),
+            &QueryMsg::PredictLotteryAddress {
+                creator: creator.into(),
+                series: series.into(),
+                round,
+            },
+        )
+    }
+
+    pub fn lottery_codes(&self, app: &App) -> StdResult<LotteryCodesResp> {
+        app.wrap()
+            .query_wasm_smart(self.addr(), &QueryMsg::LotteryCodes {})
+    }
+
+    pub fn outdated_lotteries(
+        &self,
+        app: &App,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<LotteriesResp> {
+        app.wrap().query_wasm_smart(
+            self.addr(),
+            &QueryMsg::OutdatedLotteries { start_after, limit },
+        )
+    }
+
+    pub fn lotteries(&self, app: &App) -> StdResult<LotteriesResp> {
+        self.lotteries_by(app, None, None, None)
+    }
+
+    pub fn lotteries_by(
+        &self,
+        app: &App,
+        filter: Option<LotteryFilter>,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<LotteriesResp> {
+        app.wrap().query_wasm_smart(
+            self.addr(),
+            &QueryMsg::Lotteries {
+                filter,
+                start_after,
+                limit,
+            },
+        )
     }
     pub fn owner(&self, app: &App) -> StdResult<OwnerResp> {
         app.wrap()
@@ -169,10 +646,42 @@ impl PlatformContract {
             .query_wasm_smart(self.addr(), &QueryMsg::CurrentState {})
     }
 
-    // pub fn players(&self, app: &App) -> StdResult<PlayersResp> {
-    //     app.wrap()
-    //         .query_wasm_smart(self.addr(), &QueryMsg::Players {})
-    // }
+    pub fn player_entries(&self, app: &App, player: &str) -> StdResult<PlayerEntriesResp> {
+        app.wrap().query_wasm_smart(
+            self.addr(),
+            &QueryMsg::PlayerEntries {
+                player: player.into(),
+                start_after: None,
+                limit: None,
+            },
+        )
+    }
+
+    pub fn player_wins(
+        &self,
+        app: &App,
+        player: &str,
+        unclaimed_only: bool,
+    ) -> StdResult<PlayerWinsResp> {
+        app.wrap().query_wasm_smart(
+            self.addr(),
+            &QueryMsg::PlayerWins {
+                player: player.into(),
+                unclaimed_only: Some(unclaimed_only),
+                start_after: None,
+                limit: None,
+            },
+        )
+    }
+
+    pub fn player_totals(&self, app: &App, player: &str) -> StdResult<PlayerTotalsResp> {
+        app.wrap().query_wasm_smart(
+            self.addr(),
+            &QueryMsg::PlayerTotals {
+                player: player.into(),
+            },
+        )
+    }
 }
 
 impl From<Addr> for PlatformContract {
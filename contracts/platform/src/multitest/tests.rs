@@ -1,21 +1,28 @@
 #[cfg(test)]
 mod test {
     use chrono::Utc;
-    use cosmwasm_std::{coin, coins};
-    use cw_multi_test::App;
+    use cosmwasm_std::{coin, coins, Decimal, OverflowError, OverflowOperation, Uint128};
+    use cw4::Member;
+    use cw_multi_test::{ContractWrapper, Executor};
+    use cw_utils::Expiration;
     use lottery::{
-        multitest::{LotteryCodeId, LotteryContract},
-        state::{LotteryPeriod, WinnerSelection},
+        msg::{AllowlistMsg, LotteryHookMsg},
+        multitest::{mock_app, App, LotteryCodeId, LotteryContract},
+        state::{LotteryPeriod, PrizeTier, ProgressiveJackpot, ScratchPrize, WinnerSelection},
+        ContractError as LotteryContractError,
     };
 
     use crate::{
-        multitest::{alice, bob, owner, PlatformCodeId, PlatformContract},
+        contract::CREATE_LOTTERY_REPLY_ID_START,
+        msg::{CreateLotteryMsg, LotteryFilter},
+        multitest::{alice, bob, owner, parent, PlatformCodeId, PlatformContract},
+        state::{JackpotEvent, LotteryStatus, Role, PENDING_LOTTERIES},
         ContractError, ARCH_DEMON,
     };
 
     #[test]
     fn platform_instantiate_should_works() {
-        let mut app = App::default();
+        let mut app = mock_app(|_, _, _| {});
         let code_id = PlatformCodeId::store_code(&mut app);
         let lottery_code_id = LotteryCodeId::store_code(&mut app);
         let name = "PLATFORM";
@@ -41,7 +48,7 @@ mod test {
 
     #[test]
     fn platform_create_lottery_should_works() {
-        let mut app = App::default();
+        let mut app = mock_app(|_, _, _| {});
         let code_id = PlatformCodeId::store_code(&mut app);
         let lottery_code_id = LotteryCodeId::store_code(&mut app);
         let name = "PLATFORM";
@@ -101,7 +108,7 @@ mod test {
 
     #[test]
     fn platform_buy_and_draw_lottery_should_works() {
-        let mut app = App::new(|router, _api, storage| {
+        let mut app = mock_app(|router, _api, storage| {
             router
                 .bank
                 .init_balance(storage, &alice(), coins(300, ARCH_DEMON))
@@ -201,28 +208,29 @@ mod test {
 
         // check lottery winner
         let winner = lottery_contract.winner(&app).unwrap();
-        assert_eq!(winner.winner[0].address, alice());
+        assert_eq!(winner.winner[0].address, bob());
         assert_eq!(winner.winner[0].prize, coins(200, ARCH_DEMON));
 
-        lottery_contract.claim_lottery(&mut app, alice()).unwrap();
+        lottery_contract.claim_lottery(&mut app, bob()).unwrap();
 
-        let owner = lottery_contract.owner(&app).unwrap();
-        assert_eq!(owner.owner, alice());
+        // the platform keeps the lottery, the winner withdraws the prize
+        let lottery_owner = lottery_contract.owner(&app).unwrap();
+        assert_eq!(lottery_owner.owner, contract.addr());
 
         let state = lottery_contract.query_state(&app).unwrap();
         assert_eq!(state.state.player_count, 2);
         assert_eq!(state.state.winner.len(), 1);
 
         let winner = state.state.winner.first().unwrap();
-        assert_eq!(winner.address, alice());
+        assert_eq!(winner.address, bob());
         assert_eq!(winner.prize, coins(200, ARCH_DEMON));
 
         // withdraw funds
         lottery_contract
-            .withdraw(&mut app, alice(), 100, ARCH_DEMON, None)
+            .withdraw(&mut app, bob(), 100, ARCH_DEMON, None)
             .unwrap();
         lottery_contract
-            .withdraw(&mut app, alice(), 100, ARCH_DEMON, Some(bob().to_string()))
+            .withdraw(&mut app, bob(), 100, ARCH_DEMON, Some(alice().to_string()))
             .unwrap();
 
         let balances = LotteryContract::query_balances(&app, contract.addr()).unwrap();
@@ -237,7 +245,7 @@ mod test {
 
     #[test]
     fn draw_lottery_should_fail() {
-        let mut app = App::new(|router, _api, storage| {
+        let mut app = mock_app(|router, _api, storage| {
             router
                 .bank
                 .init_balance(storage, &alice(), coins(300, ARCH_DEMON))
@@ -314,4 +322,1943 @@ mod test {
             .unwrap_err();
         assert_eq!(ContractError::Unauthorized {}, err.downcast().unwrap())
     }
+
+    #[test]
+    fn migrate_lotteries_should_works() {
+        let mut app = mock_app(|_, _, _| {});
+        let code_id = PlatformCodeId::store_code(&mut app);
+        let lottery_code_id = LotteryCodeId::store_code(&mut app);
+        let new_lottery_code_id: u64 = LotteryCodeId::store_code(&mut app).into();
+        let contract = code_id
+            .instantiate(
+                &mut app,
+                owner(),
+                "PLATFORM",
+                lottery_code_id.into(),
+                "label",
+            )
+            .unwrap();
+
+        let expiration = Utc::now().timestamp() as u64;
+        for name in ["LOTTERY1", "LOTTERY2"] {
+            contract
+                .create_lottery(
+                    &mut app,
+                    owner(),
+                    name,
+                    "LOTTER",
+                    100,
+                    ARCH_DEMON,
+                    "hour",
+                    expiration,
+                    WinnerSelection::Jackpot {},
+                    3,
+                    name,
+                )
+                .unwrap();
+        }
+
+        let err = contract
+            .migrate_lotteries(&mut app, alice(), new_lottery_code_id, None, None)
+            .unwrap_err();
+        assert_eq!(ContractError::Unauthorized {}, err.downcast().unwrap());
+
+        let code_ids = |app: &App| -> Vec<u64> {
+            contract
+                .lotteries(app)
+                .unwrap()
+                .lotteries
+                .iter()
+                .map(|lottery| {
+                    app.wrap()
+                        .query_wasm_contract_info(&lottery.contract_addr)
+                        .unwrap()
+                        .code_id
+                })
+                .collect()
+        };
+
+        // migrate in batches of one, resuming after the last lottery visited
+        let last = contract
+            .migrate_lotteries(&mut app, owner(), new_lottery_code_id, None, Some(1))
+            .unwrap();
+        assert!(last.is_some());
+        let migrated = code_ids(&app);
+        assert_eq!(
+            migrated
+                .iter()
+                .filter(|id| **id == new_lottery_code_id)
+                .count(),
+            1
+        );
+
+        let last = contract
+            .migrate_lotteries(
+                &mut app,
+                owner(),
+                new_lottery_code_id,
+                last.map(String::from),
+                Some(1),
+            )
+            .unwrap();
+        assert_eq!(code_ids(&app), vec![new_lottery_code_id; 2]);
+
+        // nothing is left after the last lottery
+        let last = contract
+            .migrate_lotteries(
+                &mut app,
+                owner(),
+                new_lottery_code_id,
+                last.map(String::from),
+                Some(1),
+            )
+            .unwrap();
+        assert_eq!(last, None);
+
+        // lotteries already running the code are visited but not migrated again
+        let last = contract
+            .migrate_lotteries(&mut app, owner(), new_lottery_code_id, None, None)
+            .unwrap();
+        assert!(last.is_some());
+    }
+
+    #[test]
+    fn update_lottery_code_should_works() {
+        let mut app = mock_app(|_, _, _| {});
+        let code_id = PlatformCodeId::store_code(&mut app);
+        let lottery_code_id: u64 = LotteryCodeId::store_code(&mut app).into();
+        let new_lottery_code_id: u64 = LotteryCodeId::store_code(&mut app).into();
+        let contract = code_id
+            .instantiate(&mut app, owner(), "PLATFORM", lottery_code_id, "label")
+            .unwrap();
+
+        let expiration = Utc::now().timestamp() as u64;
+        let create = |app: &mut App, name: &str| {
+            contract
+                .create_lottery(
+                    app,
+                    owner(),
+                    name,
+                    "LOTTER",
+                    100,
+                    ARCH_DEMON,
+                    "hour",
+                    expiration,
+                    WinnerSelection::Jackpot {},
+                    3,
+                    name,
+                )
+                .unwrap()
+                .unwrap()
+                .addr
+        };
+
+        let old_lottery = create(&mut app, "LOTTERY1");
+
+        let err = contract
+            .update_lottery_code(&mut app, alice(), new_lottery_code_id)
+            .unwrap_err();
+        assert_eq!(ContractError::Unauthorized {}, err.downcast().unwrap());
+
+        let err = contract
+            .update_lottery_code(&mut app, owner(), lottery_code_id)
+            .unwrap_err();
+        assert_eq!(
+            ContractError::LotteryCodeUnchanged {
+                code_id: lottery_code_id
+            },
+            err.downcast().unwrap()
+        );
+
+        contract
+            .update_lottery_code(&mut app, owner(), new_lottery_code_id)
+            .unwrap();
+
+        let codes = contract.lottery_codes(&app).unwrap();
+        assert_eq!(codes.current, new_lottery_code_id);
+        assert_eq!(
+            codes.codes.iter().map(|c| c.code_id).collect::<Vec<_>>(),
+            vec![lottery_code_id, new_lottery_code_id]
+        );
+
+        let new_lottery = create(&mut app, "LOTTERY2");
+        let info = app.wrap().query_wasm_contract_info(&new_lottery).unwrap();
+        assert_eq!(info.code_id, new_lottery_code_id);
+
+        let outdated = contract.outdated_lotteries(&app, None, None).unwrap();
+        assert_eq!(outdated.lotteries.len(), 1);
+        assert_eq!(outdated.lotteries[0].contract_addr, old_lottery);
+        assert_eq!(outdated.lotteries[0].code_id, lottery_code_id);
+
+        contract
+            .migrate_lotteries(&mut app, owner(), new_lottery_code_id, None, None)
+            .unwrap();
+
+        let outdated = contract.outdated_lotteries(&app, None, None).unwrap();
+        assert!(outdated.lotteries.is_empty());
+
+        // switching back keeps the whole activation history
+        contract
+            .update_lottery_code(&mut app, owner(), lottery_code_id)
+            .unwrap();
+        let codes = contract.lottery_codes(&app).unwrap();
+        assert_eq!(codes.current, lottery_code_id);
+        assert_eq!(
+            codes.codes.iter().map(|c| c.code_id).collect::<Vec<_>>(),
+            vec![lottery_code_id, new_lottery_code_id, lottery_code_id]
+        );
+    }
+
+    #[test]
+    fn create_lotteries_should_works() {
+        let mut app = mock_app(|_, _, _| {});
+        let code_id = PlatformCodeId::store_code(&mut app);
+        let lottery_code_id = LotteryCodeId::store_code(&mut app);
+        let contract = code_id
+            .instantiate(
+                &mut app,
+                owner(),
+                "PLATFORM",
+                lottery_code_id.into(),
+                "label",
+            )
+            .unwrap();
+
+        contract
+            .grant_role(&mut app, owner(), Role::Creator, alice().as_str())
+            .unwrap();
+
+        let expiration = Utc::now().timestamp() as u64;
+        let new_lottery = |name: &str, period: &str, expiration: u64| CreateLotteryMsg {
+            name: name.to_string(),
+            symbol: "LOTTER".to_string(),
+            unit_price_amount: 100,
+            unit_price_denom: ARCH_DEMON.to_string(),
+            period: period.to_string(),
+            expiration,
+            selection: WinnerSelection::Jackpot {},
+            max_players: 3,
+            label: name.to_string(),
+            allowlist: None,
+            nft_gate: None,
+            referral_bps: None,
+            pricing: None,
+        };
+
+        let err = contract
+            .create_lotteries(&mut app, alice(), vec![], &[])
+            .unwrap_err();
+        assert_eq!(
+            ContractError::NoLotteriesToCreate {},
+            err.downcast().unwrap()
+        );
+
+        contract
+            .create_lotteries(
+                &mut app,
+                alice(),
+                vec![
+                    new_lottery("LOTTERY1", "hour", expiration),
+                    new_lottery("LOTTERY2", "day", expiration),
+                    new_lottery("LOTTERY3", "week", expiration),
+                ],
+                &[],
+            )
+            .unwrap();
+
+        let state = contract.query_state(&app).unwrap().state;
+        assert_eq!(state.lotteries_count, 3);
+
+        // every lottery gets its own metadata
+        let lotteries = contract.lotteries(&app).unwrap().lotteries;
+        assert_eq!(lotteries.len(), 3);
+        for lottery in &lotteries {
+            let lottery_contract: LotteryContract = lottery.contract_addr.clone().into();
+            let lottery_state = lottery_contract.query_state(&app).unwrap().state;
+            assert_eq!(lottery.name, lottery_state.name);
+            assert_eq!(lottery.period, lottery_state.period);
+        }
+
+        // pending entries are cleaned up by the replies
+        let pending = PENDING_LOTTERIES.key(CREATE_LOTTERY_REPLY_ID_START);
+        let raw = app
+            .wrap()
+            .query_wasm_raw(contract.addr(), pending.to_vec())
+            .unwrap();
+        assert!(raw.is_none());
+
+        // a failed instantiation reverts the whole batch
+        contract
+            .create_lotteries(
+                &mut app,
+                alice(),
+                vec![
+                    new_lottery("LOTTERY4", "hour", expiration),
+                    new_lottery("LOTTERY5", "hour", 0),
+                ],
+                &[],
+            )
+            .unwrap_err();
+
+        let state = contract.query_state(&app).unwrap().state;
+        assert_eq!(state.lotteries_count, 3);
+        assert_eq!(contract.lotteries(&app).unwrap().lotteries.len(), 3);
+    }
+
+    #[test]
+    fn predict_lottery_address_should_works() {
+        let mut app = mock_app(|_, _, _| {});
+        let code_id = PlatformCodeId::store_code(&mut app);
+        let lottery_code_id = LotteryCodeId::store_code(&mut app);
+        let contract = code_id
+            .instantiate(
+                &mut app,
+                owner(),
+                "PLATFORM",
+                lottery_code_id.into(),
+                "label",
+            )
+            .unwrap();
+
+        contract
+            .grant_role(&mut app, owner(), Role::Creator, alice().as_str())
+            .unwrap();
+
+        let predicted = contract
+            .predict_lottery_address(&app, alice().as_str(), "LOTTER", None)
+            .unwrap();
+        assert_eq!(predicted.round, 1);
+
+        // the address depends on the creator
+        let other = contract
+            .predict_lottery_address(&app, bob().as_str(), "LOTTER", None)
+            .unwrap();
+        assert_ne!(predicted.address, other.address);
+
+        let expiration = Utc::now().timestamp() as u64;
+        let resp = contract
+            .create_lottery(
+                &mut app,
+                alice(),
+                "LOTTERY",
+                "LOTTER",
+                100,
+                ARCH_DEMON,
+                "hour",
+                expiration,
+                WinnerSelection::Jackpot {},
+                3,
+                "LOTTERY",
+            )
+            .unwrap()
+            .unwrap();
+        assert_eq!(resp.addr, predicted.address);
+
+        let lottery: LotteryContract = resp.addr.into();
+        assert_eq!(lottery.query_state(&app).unwrap().state.name, "LOTTERY");
+
+        // the next round of the series gets a new address
+        let next = contract
+            .predict_lottery_address(&app, alice().as_str(), "LOTTER", None)
+            .unwrap();
+        assert_eq!(next.round, 2);
+        assert_ne!(next.address, predicted.address);
+
+        let first = contract
+            .predict_lottery_address(&app, alice().as_str(), "LOTTER", Some(1))
+            .unwrap();
+        assert_eq!(first, predicted);
+    }
+
+    #[test]
+    fn lotteries_query_should_works() {
+        let mut app = mock_app(|_, _, _| {});
+        let code_id = PlatformCodeId::store_code(&mut app);
+        let lottery_code_id = LotteryCodeId::store_code(&mut app);
+        let contract = code_id
+            .instantiate(
+                &mut app,
+                owner(),
+                "PLATFORM",
+                lottery_code_id.into(),
+                "label",
+            )
+            .unwrap();
+
+        let expiration = Utc::now().timestamp() as u64;
+        let started_at = app.block_info().time.seconds();
+        let lotteries = [
+            ("LOTTERY1", "hour", ARCH_DEMON),
+            ("LOTTERY2", "day", ARCH_DEMON),
+            ("LOTTERY3", "hour", "uatom"),
+            ("LOTTERY4", "day", "uatom"),
+        ];
+        let mut addrs = vec![];
+        for (name, period, denom) in lotteries {
+            let resp = contract
+                .create_lottery(
+                    &mut app,
+                    owner(),
+                    name,
+                    "LOTTER",
+                    100,
+                    denom,
+                    period,
+                    expiration,
+                    WinnerSelection::Jackpot {},
+                    3,
+                    name,
+                )
+                .unwrap();
+            addrs.push(resp.unwrap().addr);
+            app.update_block(|block| block.time = block.time.plus_seconds(100));
+        }
+
+        contract
+            .draw_lottery(&mut app, owner(), addrs[1].as_str())
+            .unwrap();
+
+        let names = |resp: crate::msg::LotteriesResp| -> Vec<String> {
+            resp.lotteries.into_iter().map(|l| l.name).collect()
+        };
+
+        // pagination
+        let first = contract.lotteries_by(&app, None, None, Some(3)).unwrap();
+        assert_eq!(first.lotteries.len(), 3);
+        let last = first.lotteries.last().unwrap().contract_addr.to_string();
+        let rest = contract.lotteries_by(&app, None, Some(last), None).unwrap();
+        assert_eq!(rest.lotteries.len(), 1);
+
+        // by period
+        let filter = LotteryFilter {
+            period: Some(LotteryPeriod::Hour {}),
+            ..Default::default()
+        };
+        let mut hourly = names(
+            contract
+                .lotteries_by(&app, Some(filter), None, None)
+                .unwrap(),
+        );
+        hourly.sort();
+        assert_eq!(hourly, vec!["LOTTERY1", "LOTTERY3"]);
+
+        // by status
+        let filter = LotteryFilter {
+            status: Some(LotteryStatus::Drawn),
+            ..Default::default()
+        };
+        let drawn = names(
+            contract
+                .lotteries_by(&app, Some(filter), None, None)
+                .unwrap(),
+        );
+        assert_eq!(drawn, vec!["LOTTERY2"]);
+
+        // by status and denom
+        let filter = LotteryFilter {
+            status: Some(LotteryStatus::Active),
+            denom: Some("uatom".to_string()),
+            ..Default::default()
+        };
+        let mut active = names(
+            contract
+                .lotteries_by(&app, Some(filter), None, None)
+                .unwrap(),
+        );
+        active.sort();
+        assert_eq!(active, vec!["LOTTERY3", "LOTTERY4"]);
+
+        // by creation time, ordered by creation time
+        let filter = LotteryFilter {
+            created_after: Some(started_at + 100),
+            created_before: Some(started_at + 300),
+            ..Default::default()
+        };
+        let created = contract
+            .lotteries_by(&app, Some(filter.clone()), None, Some(2))
+            .unwrap();
+        let last = created.lotteries.last().unwrap().contract_addr.to_string();
+        assert_eq!(names(created), vec!["LOTTERY2", "LOTTERY3"]);
+
+        let created = contract
+            .lotteries_by(&app, Some(filter), Some(last), Some(2))
+            .unwrap();
+        assert_eq!(names(created), vec!["LOTTERY4"]);
+    }
+
+    #[test]
+    fn lottery_hooks_should_works() {
+        let mut app = mock_app(|router, _api, storage| {
+            router
+                .bank
+                .init_balance(storage, &alice(), coins(300, ARCH_DEMON))
+                .unwrap();
+            router
+                .bank
+                .init_balance(storage, &bob(), coins(500, ARCH_DEMON))
+                .unwrap();
+        });
+
+        let code_id = PlatformCodeId::store_code(&mut app);
+        let lottery_code_id = LotteryCodeId::store_code(&mut app);
+        let contract = code_id
+            .instantiate(
+                &mut app,
+                owner(),
+                "PLATFORM",
+                lottery_code_id.into(),
+                "label",
+            )
+            .unwrap();
+
+        let expiration = Utc::now().timestamp() as u64;
+        let mut lotteries = vec![];
+        for name in ["LOTTERY1", "LOTTERY2"] {
+            let resp = contract
+                .create_lottery(
+                    &mut app,
+                    owner(),
+                    name,
+                    "LOTTER",
+                    100,
+                    ARCH_DEMON,
+                    "hour",
+                    expiration,
+                    WinnerSelection::Jackpot {},
+                    3,
+                    name,
+                )
+                .unwrap();
+            lotteries.push(LotteryContract::from(resp.unwrap().addr));
+        }
+        let lottery_info = |app: &App, lottery: &LotteryContract| {
+            contract
+                .lotteries(app)
+                .unwrap()
+                .lotteries
+                .into_iter()
+                .find(|info| info.contract_addr == lottery.addr())
+                .unwrap()
+        };
+
+        let lottery = &lotteries[0];
+        for player in [alice(), bob()] {
+            lottery
+                .buy_ticket(&mut app, player, ARCH_DEMON, None, &coins(100, ARCH_DEMON))
+                .unwrap();
+        }
+
+        let info = lottery_info(&app, lottery);
+        assert_eq!(info.status, LotteryStatus::Active);
+        assert_eq!(info.player_count, 2);
+        assert_eq!(info.pot, coin(200, ARCH_DEMON));
+
+        contract
+            .draw_lottery(&mut app, owner(), lottery.addr().as_str())
+            .unwrap();
+
+        let info = lottery_info(&app, lottery);
+        assert_eq!(info.status, LotteryStatus::Drawn);
+        assert_eq!(info.winner, lottery.winner(&app).unwrap().winner);
+        assert!(!info.claimed);
+
+        let winner = info.winner[0].address.clone();
+        lottery.claim_lottery(&mut app, winner).unwrap();
+        assert!(lottery_info(&app, lottery).claimed);
+
+        // cancel through the platform
+        let lottery = &lotteries[1];
+        lottery
+            .buy_ticket(&mut app, alice(), ARCH_DEMON, None, &coins(100, ARCH_DEMON))
+            .unwrap();
+        contract
+            .cancel_lottery(&mut app, owner(), lottery.addr().as_str())
+            .unwrap();
+
+        let info = lottery_info(&app, lottery);
+        assert_eq!(info.status, LotteryStatus::Cancelled);
+        assert_eq!(info.player_count, 1);
+
+        lottery.refund(&mut app, alice()).unwrap();
+
+        // only the platform's lotteries can report
+        let err = contract
+            .lottery_hook(&mut app, alice(), LotteryHookMsg::Cancelled {})
+            .unwrap_err();
+        assert_eq!(ContractError::Unauthorized {}, err.downcast().unwrap());
+    }
+
+    #[test]
+    fn player_queries_should_works() {
+        let mut app = mock_app(|router, _api, storage| {
+            router
+                .bank
+                .init_balance(storage, &alice(), coins(300, ARCH_DEMON))
+                .unwrap();
+            router
+                .bank
+                .init_balance(storage, &bob(), coins(500, ARCH_DEMON))
+                .unwrap();
+        });
+
+        let code_id = PlatformCodeId::store_code(&mut app);
+        let lottery_code_id = LotteryCodeId::store_code(&mut app);
+        let contract = code_id
+            .instantiate(
+                &mut app,
+                owner(),
+                "PLATFORM",
+                lottery_code_id.into(),
+                "label",
+            )
+            .unwrap();
+
+        let expiration = Utc::now().timestamp() as u64;
+        let mut lotteries = vec![];
+        for name in ["LOTTERY1", "LOTTERY2"] {
+            let resp = contract
+                .create_lottery(
+                    &mut app,
+                    owner(),
+                    name,
+                    "LOTTER",
+                    100,
+                    ARCH_DEMON,
+                    "hour",
+                    expiration,
+                    WinnerSelection::Jackpot {},
+                    3,
+                    name,
+                )
+                .unwrap();
+            lotteries.push(LotteryContract::from(resp.unwrap().addr));
+        }
+
+        for player in [alice(), bob()] {
+            lotteries[0]
+                .buy_ticket(&mut app, player, ARCH_DEMON, None, &coins(100, ARCH_DEMON))
+                .unwrap();
+        }
+        lotteries[1]
+            .buy_ticket(&mut app, alice(), ARCH_DEMON, None, &coins(100, ARCH_DEMON))
+            .unwrap();
+
+        let entries = contract.player_entries(&app, alice().as_str()).unwrap();
+        assert_eq!(entries.entries.len(), 2);
+        assert!(entries
+            .entries
+            .iter()
+            .all(|e| e.paid == coin(100, ARCH_DEMON)));
+
+        let totals = contract
+            .player_totals(&app, alice().as_str())
+            .unwrap()
+            .totals;
+        assert_eq!(totals.entries, 2);
+        assert_eq!(totals.spent, coins(200, ARCH_DEMON));
+        assert_eq!(totals.wins, 0);
+
+        contract
+            .draw_lottery(&mut app, owner(), lotteries[0].addr().as_str())
+            .unwrap();
+
+        let winner = lotteries[0].winner(&app).unwrap().winner[0].address.clone();
+
+        let wins = contract.player_wins(&app, winner.as_str(), true).unwrap();
+        assert_eq!(wins.wins.len(), 1);
+        assert_eq!(wins.wins[0].lottery, lotteries[0].addr());
+        assert_eq!(wins.wins[0].prize, coins(200, ARCH_DEMON));
+
+        let totals = contract
+            .player_totals(&app, winner.as_str())
+            .unwrap()
+            .totals;
+        assert_eq!(totals.wins, 1);
+        assert_eq!(totals.won, coins(200, ARCH_DEMON));
+        assert_eq!(totals.unclaimed, coins(200, ARCH_DEMON));
+
+        lotteries[0]
+            .claim_lottery(&mut app, winner.clone())
+            .unwrap();
+
+        let wins = contract.player_wins(&app, winner.as_str(), true).unwrap();
+        assert!(wins.wins.is_empty());
+        let wins = contract.player_wins(&app, winner.as_str(), false).unwrap();
+        assert!(wins.wins[0].claimed);
+
+        let totals = contract
+            .player_totals(&app, winner.as_str())
+            .unwrap()
+            .totals;
+        assert!(totals.unclaimed.is_empty());
+        assert_eq!(totals.won, coins(200, ARCH_DEMON));
+
+        // claiming again doesn't count the win twice
+        lotteries[0]
+            .claim_lottery(&mut app, winner.clone())
+            .unwrap();
+
+        let totals = contract
+            .player_totals(&app, winner.as_str())
+            .unwrap()
+            .totals;
+        assert!(totals.unclaimed.is_empty());
+        assert_eq!(totals.wins, 1);
+    }
+
+    #[test]
+    fn roles_should_works() {
+        let mut app = mock_app(|_, _, _| {});
+        let code_id = PlatformCodeId::store_code(&mut app);
+        let lottery_code_id = LotteryCodeId::store_code(&mut app);
+        let contract = code_id
+            .instantiate(
+                &mut app,
+                owner(),
+                "PLATFORM",
+                lottery_code_id.into(),
+                "label",
+            )
+            .unwrap();
+
+        // the owner holds every role after instantiation
+        let roles = contract.roles(&app, owner().as_str()).unwrap().roles;
+        assert_eq!(roles, vec![Role::Admin, Role::Creator, Role::Drawer]);
+        assert!(contract
+            .roles(&app, alice().as_str())
+            .unwrap()
+            .roles
+            .is_empty());
+
+        let expiration = Utc::now().timestamp() as u64;
+        let create = |app: &mut App, sender| {
+            contract.create_lottery(
+                app,
+                sender,
+                "LOTTERY",
+                "LOTTER",
+                100,
+                ARCH_DEMON,
+                "hour",
+                expiration,
+                WinnerSelection::Jackpot {},
+                3,
+                "LOTTERY",
+            )
+        };
+
+        let err = create(&mut app, alice()).unwrap_err();
+        assert_eq!(ContractError::Unauthorized {}, err.downcast().unwrap());
+
+        // only admins grant roles
+        let err = contract
+            .grant_role(&mut app, alice(), Role::Creator, alice().as_str())
+            .unwrap_err();
+        assert_eq!(ContractError::Unauthorized {}, err.downcast().unwrap());
+
+        contract
+            .grant_role(&mut app, owner(), Role::Creator, alice().as_str())
+            .unwrap();
+        let err = contract
+            .grant_role(&mut app, owner(), Role::Creator, alice().as_str())
+            .unwrap_err();
+        assert_eq!(
+            ContractError::RoleAlreadyGranted {
+                address: alice().to_string(),
+                role: "creator".to_string(),
+            },
+            err.downcast().unwrap()
+        );
+
+        let lottery = create(&mut app, alice()).unwrap().unwrap().addr;
+
+        // a creator can't draw
+        let err = contract
+            .draw_lottery(&mut app, alice(), lottery.as_str())
+            .unwrap_err();
+        assert_eq!(ContractError::Unauthorized {}, err.downcast().unwrap());
+
+        let members = contract.role_members(&app, Role::Creator).unwrap();
+        assert_eq!(members.members.len(), 2);
+        assert!(members.members.contains(&alice()));
+        assert_eq!(members.group, None);
+
+        contract
+            .revoke_role(&mut app, owner(), Role::Creator, alice().as_str())
+            .unwrap();
+        let err = contract
+            .revoke_role(&mut app, owner(), Role::Creator, alice().as_str())
+            .unwrap_err();
+        assert_eq!(
+            ContractError::RoleNotGranted {
+                address: alice().to_string(),
+                role: "creator".to_string(),
+            },
+            err.downcast().unwrap()
+        );
+        let err = create(&mut app, alice()).unwrap_err();
+        assert_eq!(ContractError::Unauthorized {}, err.downcast().unwrap());
+
+        // members of a cw4 group hold the role as well
+        let group_code_id = app.store_code(Box::new(ContractWrapper::new(
+            cw4_group::contract::execute,
+            cw4_group::contract::instantiate,
+            cw4_group::contract::query,
+        )));
+        let group = app
+            .instantiate_contract(
+                group_code_id,
+                owner(),
+                &cw4_group::msg::InstantiateMsg {
+                    admin: Some(owner().to_string()),
+                    members: vec![Member {
+                        addr: bob().to_string(),
+                        weight: 1,
+                    }],
+                },
+                &[],
+                "drawers",
+                None,
+            )
+            .unwrap();
+
+        let err = contract
+            .set_role_group(&mut app, bob(), Role::Drawer, Some(group.as_str()))
+            .unwrap_err();
+        assert_eq!(ContractError::Unauthorized {}, err.downcast().unwrap());
+
+        contract
+            .set_role_group(&mut app, owner(), Role::Drawer, Some(group.as_str()))
+            .unwrap();
+        let roles = contract.roles(&app, bob().as_str()).unwrap().roles;
+        assert_eq!(roles, vec![Role::Drawer]);
+        let members = contract.role_members(&app, Role::Drawer).unwrap();
+        assert_eq!(members.members, vec![owner()]);
+        assert_eq!(members.group, Some(group.clone()));
+
+        contract
+            .draw_lottery(&mut app, bob(), lottery.as_str())
+            .unwrap();
+
+        contract
+            .set_role_group(&mut app, owner(), Role::Drawer, None)
+            .unwrap();
+        assert!(contract
+            .roles(&app, bob().as_str())
+            .unwrap()
+            .roles
+            .is_empty());
+    }
+
+    #[test]
+    fn referrer_totals_should_works() {
+        let mut app = mock_app(|router, _api, storage| {
+            for player in [alice(), bob()] {
+                router
+                    .bank
+                    .init_balance(storage, &player, coins(200, ARCH_DEMON))
+                    .unwrap();
+            }
+        });
+        let code_id = PlatformCodeId::store_code(&mut app);
+        let lottery_code_id = LotteryCodeId::store_code(&mut app);
+        let contract = code_id
+            .instantiate(
+                &mut app,
+                owner(),
+                "PLATFORM",
+                lottery_code_id.into(),
+                "label",
+            )
+            .unwrap();
+
+        let expiration = Utc::now().timestamp() as u64;
+        let new_lottery = |name: &str| CreateLotteryMsg {
+            name: name.to_string(),
+            symbol: name.to_string(),
+            unit_price_amount: 100,
+            unit_price_denom: ARCH_DEMON.to_string(),
+            period: "hour".to_string(),
+            expiration,
+            selection: WinnerSelection::Jackpot {},
+            max_players: 2,
+            label: name.to_string(),
+            allowlist: None,
+            nft_gate: None,
+            referral_bps: Some(1_000),
+            pricing: None,
+        };
+        contract
+            .create_lotteries(
+                &mut app,
+                owner(),
+                vec![new_lottery("LOTTERY1"), new_lottery("LOTTERY2")],
+                &[],
+            )
+            .unwrap();
+
+        let lotteries = contract.lotteries(&app).unwrap().lotteries;
+        let lottery_of = |name: &str| -> LotteryContract {
+            lotteries
+                .iter()
+                .find(|lottery| lottery.name == name)
+                .unwrap()
+                .contract_addr
+                .clone()
+                .into()
+        };
+        let (claimed, drawn) = (lottery_of("LOTTERY1"), lottery_of("LOTTERY2"));
+        let ticket = coins(100, ARCH_DEMON);
+
+        // the rewards are claimed from one lottery and paid at draw by the other
+        for player in [alice(), bob()] {
+            claimed
+                .buy_ticket_with_referrer(&mut app, player, ARCH_DEMON, &parent(), &ticket)
+                .unwrap();
+        }
+        claimed.claim_referral_rewards(&mut app, parent()).unwrap();
+
+        drawn
+            .buy_ticket_with_referrer(&mut app, alice(), ARCH_DEMON, &parent(), &ticket)
+            .unwrap();
+        contract
+            .draw_lottery(&mut app, owner(), drawn.addr().as_str())
+            .unwrap();
+
+        let parent_balances = LotteryContract::query_balances(&app, parent()).unwrap();
+        assert_eq!(parent_balances, coins(30, ARCH_DEMON));
+
+        let totals = contract
+            .referrer_totals(&app, parent().as_str())
+            .unwrap()
+            .totals;
+        assert_eq!(totals.referrals, 3);
+        assert_eq!(totals.earned, coins(30, ARCH_DEMON));
+    }
+
+    #[test]
+    fn number_pick_rollover_should_works() {
+        let mut app = mock_app(|router, _api, storage| {
+            for player in [alice(), bob()] {
+                router
+                    .bank
+                    .init_balance(storage, &player, coins(100, ARCH_DEMON))
+                    .unwrap();
+            }
+        });
+        let code_id = PlatformCodeId::store_code(&mut app);
+        let lottery_code_id = LotteryCodeId::store_code(&mut app);
+        let contract = code_id
+            .instantiate(
+                &mut app,
+                owner(),
+                "PLATFORM",
+                lottery_code_id.into(),
+                "label",
+            )
+            .unwrap();
+
+        let expiration = Utc::now().timestamp() as u64;
+        let pick_lottery = || CreateLotteryMsg {
+            name: "PICK".to_string(),
+            symbol: "PICK".to_string(),
+            unit_price_amount: 100,
+            unit_price_denom: ARCH_DEMON.to_string(),
+            period: "hour".to_string(),
+            expiration,
+            selection: WinnerSelection::NumberPick {
+                pick: 2,
+                max_number: 4,
+                tiers: vec![
+                    PrizeTier {
+                        matches: 2,
+                        pct: 70,
+                    },
+                    PrizeTier {
+                        matches: 1,
+                        pct: 30,
+                    },
+                ],
+            },
+            max_players: 2,
+            label: "PICK".to_string(),
+            allowlist: None,
+            nft_gate: None,
+            referral_bps: None,
+            pricing: None,
+        };
+        contract
+            .create_lotteries(&mut app, owner(), vec![pick_lottery()], &[])
+            .unwrap();
+        let first = contract.lotteries(&app).unwrap().lotteries[0]
+            .contract_addr
+            .clone();
+        let first_contract: LotteryContract = first.clone().into();
+
+        for (player, numbers) in [(alice(), vec![1, 2]), (bob(), vec![3, 4])] {
+            first_contract
+                .buy_ticket_with_numbers(
+                    &mut app,
+                    player,
+                    ARCH_DEMON,
+                    numbers,
+                    &coins(100, ARCH_DEMON),
+                )
+                .unwrap();
+        }
+        contract
+            .draw_lottery(&mut app, owner(), first.as_str())
+            .unwrap();
+
+        // the prizes are paid by the draw, the rest rolls over
+        let winners = first_contract.winner(&app).unwrap().winner;
+        let paid: u128 = winners.iter().map(|w| w.prize[0].amount.u128()).sum();
+        assert!(paid == 60 || paid == 140);
+        for winner in &winners {
+            let totals = contract
+                .player_totals(&app, winner.address.as_str())
+                .unwrap()
+                .totals;
+            assert!(totals.unclaimed.is_empty());
+        }
+
+        let rollover = contract
+            .rollover(&app, owner().as_str(), "PICK")
+            .unwrap()
+            .rollover;
+        assert_eq!(rollover, coins(200 - paid, ARCH_DEMON));
+
+        // only a number-pick round takes the rollover
+        let jackpot_lottery = CreateLotteryMsg {
+            selection: WinnerSelection::Jackpot {},
+            ..pick_lottery()
+        };
+        contract
+            .create_lotteries(&mut app, owner(), vec![jackpot_lottery], &[])
+            .unwrap();
+        let rollover = contract
+            .rollover(&app, owner().as_str(), "PICK")
+            .unwrap()
+            .rollover;
+        assert_eq!(rollover, coins(200 - paid, ARCH_DEMON));
+
+        let scratch_lottery = CreateLotteryMsg {
+            selection: WinnerSelection::Scratch {
+                prizes: vec![ScratchPrize {
+                    amount: Uint128::new(100),
+                    count: 1,
+                }],
+            },
+            ..pick_lottery()
+        };
+        let err = contract
+            .create_lotteries(&mut app, owner(), vec![scratch_lottery], &[])
+            .unwrap_err();
+        assert_eq!(
+            ContractError::ScratchNotSupported {},
+            err.downcast().unwrap()
+        );
+
+        // the next round of the series starts with the rollover
+        contract
+            .create_lotteries(&mut app, owner(), vec![pick_lottery()], &[])
+            .unwrap();
+        let next = contract
+            .lotteries(&app)
+            .unwrap()
+            .lotteries
+            .into_iter()
+            .find(|lottery| lottery.contract_addr != first && lottery.selection.is_number_pick())
+            .unwrap();
+        assert_eq!(next.pot, coin(200 - paid, ARCH_DEMON));
+
+        let next_balances = LotteryContract::query_balances(&app, next.contract_addr).unwrap();
+        assert_eq!(next_balances, coins(200 - paid, ARCH_DEMON));
+
+        let rollover = contract
+            .rollover(&app, owner().as_str(), "PICK")
+            .unwrap()
+            .rollover;
+        assert!(rollover.is_empty());
+    }
+
+    #[test]
+    fn creator_deposit_and_commission_should_works() {
+        let mut app = mock_app(|router, _api, storage| {
+            for (addr, amount) in [(alice(), 300), (bob(), 100), (parent(), 1_000)] {
+                router
+                    .bank
+                    .init_balance(storage, &addr, coins(amount, ARCH_DEMON))
+                    .unwrap();
+            }
+        });
+        let code_id = PlatformCodeId::store_code(&mut app);
+        let lottery_code_id = LotteryCodeId::store_code(&mut app);
+        let contract = code_id
+            .instantiate(
+                &mut app,
+                owner(),
+                "PLATFORM",
+                lottery_code_id.into(),
+                "label",
+            )
+            .unwrap();
+
+        let expiration = Utc::now().timestamp() as u64;
+        let new_lottery = |name: &str, max_players: u64| CreateLotteryMsg {
+            name: name.to_string(),
+            symbol: name.to_string(),
+            unit_price_amount: 100,
+            unit_price_denom: ARCH_DEMON.to_string(),
+            period: "hour".to_string(),
+            expiration,
+            selection: WinnerSelection::Jackpot {},
+            max_players,
+            label: name.to_string(),
+            allowlist: None,
+            nft_gate: None,
+            referral_bps: None,
+            pricing: None,
+        };
+        let lotteries = vec![
+            new_lottery("LOTTERY1", 2),
+            new_lottery("LOTTERY2", 3),
+            new_lottery("LOTTERY3", 3),
+        ];
+
+        // creation is closed to third parties without a deposit
+        let err = contract
+            .create_lotteries(
+                &mut app,
+                parent(),
+                lotteries.clone(),
+                &coins(300, ARCH_DEMON),
+            )
+            .unwrap_err();
+        assert_eq!(ContractError::Unauthorized {}, err.downcast().unwrap());
+
+        let err = contract
+            .update_creation_config(&mut app, owner(), None, Decimal::percent(101))
+            .unwrap_err();
+        assert_eq!(
+            ContractError::InvalidCommission {
+                rate: Decimal::percent(101)
+            },
+            err.downcast().unwrap()
+        );
+
+        // a deposit too large to multiply by the batch size is rejected
+        contract
+            .update_creation_config(
+                &mut app,
+                owner(),
+                Some(coin(u128::MAX, ARCH_DEMON)),
+                Decimal::percent(10),
+            )
+            .unwrap();
+        let err = contract
+            .create_lotteries(
+                &mut app,
+                parent(),
+                lotteries.clone(),
+                &coins(300, ARCH_DEMON),
+            )
+            .unwrap_err();
+        assert_eq!(
+            ContractError::Overflow(OverflowError::new(
+                OverflowOperation::Mul,
+                Uint128::MAX,
+                Uint128::new(3)
+            )),
+            err.downcast().unwrap()
+        );
+
+        contract
+            .update_creation_config(
+                &mut app,
+                owner(),
+                Some(coin(100, ARCH_DEMON)),
+                Decimal::percent(10),
+            )
+            .unwrap();
+        let config = contract.creation_config(&app).unwrap().config;
+        assert_eq!(config.deposit, Some(coin(100, ARCH_DEMON)));
+
+        // every lottery of the batch is paid for
+        let err = contract
+            .create_lotteries(
+                &mut app,
+                parent(),
+                lotteries.clone(),
+                &coins(100, ARCH_DEMON),
+            )
+            .unwrap_err();
+        assert_eq!(
+            ContractError::InvalidDeposit {
+                expected: coin(300, ARCH_DEMON)
+            },
+            err.downcast().unwrap()
+        );
+
+        contract
+            .create_lotteries(&mut app, parent(), lotteries, &coins(300, ARCH_DEMON))
+            .unwrap();
+
+        let totals = contract
+            .creator_totals(&app, parent().as_str())
+            .unwrap()
+            .totals;
+        assert_eq!(totals.lotteries, 3);
+        assert_eq!(totals.deposits, coins(300, ARCH_DEMON));
+
+        let filter = LotteryFilter {
+            creator: Some(parent().to_string()),
+            ..Default::default()
+        };
+        let created = contract
+            .lotteries_by(&app, Some(filter), None, None)
+            .unwrap()
+            .lotteries;
+        assert_eq!(created.len(), 3);
+        assert!(created
+            .iter()
+            .all(|lottery| lottery.commission == Decimal::percent(10)));
+        let lottery_of = |name: &str| {
+            created
+                .iter()
+                .find(|lottery| lottery.name == name)
+                .unwrap()
+                .contract_addr
+                .clone()
+        };
+        let (drawn, slashed, refunded) = (
+            lottery_of("LOTTERY1"),
+            lottery_of("LOTTERY2"),
+            lottery_of("LOTTERY3"),
+        );
+
+        // the commission is paid and the deposit refunded at draw
+        let drawn_contract: LotteryContract = drawn.clone().into();
+        for player in [alice(), bob()] {
+            drawn_contract
+                .buy_ticket(&mut app, player, ARCH_DEMON, None, &coins(100, ARCH_DEMON))
+                .unwrap();
+        }
+        contract
+            .draw_lottery(&mut app, owner(), drawn.as_str())
+            .unwrap();
+
+        let winner = drawn_contract.winner(&app).unwrap().winner;
+        assert_eq!(winner[0].prize, coins(180, ARCH_DEMON));
+
+        // the creator walks away from sold tickets and loses the deposit
+        let slashed_contract: LotteryContract = slashed.clone().into();
+        slashed_contract
+            .buy_ticket(&mut app, alice(), ARCH_DEMON, None, &coins(100, ARCH_DEMON))
+            .unwrap();
+
+        let err = contract
+            .cancel_lottery(&mut app, bob(), slashed.as_str())
+            .unwrap_err();
+        assert_eq!(ContractError::Unauthorized {}, err.downcast().unwrap());
+
+        contract
+            .cancel_lottery(&mut app, parent(), slashed.as_str())
+            .unwrap();
+        contract
+            .cancel_lottery(&mut app, parent(), refunded.as_str())
+            .unwrap();
+
+        let totals = contract
+            .creator_totals(&app, parent().as_str())
+            .unwrap()
+            .totals;
+        assert_eq!(totals.earned, coins(20, ARCH_DEMON));
+        assert!(totals.deposits.is_empty());
+        assert_eq!(totals.slashed, coins(100, ARCH_DEMON));
+
+        let lotteries = contract.lotteries(&app).unwrap().lotteries;
+        let drawn_info = lotteries.iter().find(|l| l.contract_addr == drawn).unwrap();
+        assert_eq!(drawn_info.commission_paid, Some(coin(20, ARCH_DEMON)));
+        assert!(lotteries.iter().all(|l| l.deposit.is_none()));
+
+        let parent_balances = PlatformContract::query_balances(&app, parent()).unwrap();
+        assert_eq!(parent_balances, coins(920, ARCH_DEMON));
+        let platform_balances = PlatformContract::query_balances(&app, contract.addr()).unwrap();
+        assert_eq!(platform_balances, coins(100, ARCH_DEMON));
+
+        // the slashed deposit is withdrawn by an admin
+        let err = contract
+            .withdraw_slashed(&mut app, parent(), None)
+            .unwrap_err();
+        assert_eq!(ContractError::Unauthorized {}, err.downcast().unwrap());
+
+        contract
+            .withdraw_slashed(&mut app, owner(), Some(bob().to_string()))
+            .unwrap();
+        let platform_balances = PlatformContract::query_balances(&app, contract.addr()).unwrap();
+        assert!(platform_balances.is_empty());
+
+        let err = contract
+            .withdraw_slashed(&mut app, owner(), None)
+            .unwrap_err();
+        assert_eq!(ContractError::NothingSlashed {}, err.downcast().unwrap());
+    }
+
+    #[test]
+    fn private_lotteries_should_works() {
+        let mut app = mock_app(|router, _api, storage| {
+            for player in [alice(), bob()] {
+                router
+                    .bank
+                    .init_balance(storage, &player, coins(300, ARCH_DEMON))
+                    .unwrap();
+            }
+        });
+        let code_id = PlatformCodeId::store_code(&mut app);
+        let lottery_code_id = LotteryCodeId::store_code(&mut app);
+        let contract = code_id
+            .instantiate(
+                &mut app,
+                owner(),
+                "PLATFORM",
+                lottery_code_id.into(),
+                "label",
+            )
+            .unwrap();
+
+        // employees of the corporate customer
+        let group_code_id = app.store_code(Box::new(ContractWrapper::new(
+            cw4_group::contract::execute,
+            cw4_group::contract::instantiate,
+            cw4_group::contract::query,
+        )));
+        let group = app
+            .instantiate_contract(
+                group_code_id,
+                owner(),
+                &cw4_group::msg::InstantiateMsg {
+                    admin: None,
+                    members: vec![Member {
+                        addr: alice().to_string(),
+                        weight: 1,
+                    }],
+                },
+                &[],
+                "employees",
+                None,
+            )
+            .unwrap();
+
+        let expiration = Utc::now().timestamp() as u64;
+        let new_lottery = |name: &str, allowlist| CreateLotteryMsg {
+            name: name.to_string(),
+            symbol: name.to_string(),
+            unit_price_amount: 100,
+            unit_price_denom: ARCH_DEMON.to_string(),
+            period: "hour".to_string(),
+            expiration,
+            selection: WinnerSelection::Jackpot {},
+            max_players: 3,
+            label: name.to_string(),
+            allowlist: Some(allowlist),
+            nft_gate: None,
+            referral_bps: None,
+            pricing: None,
+        };
+        contract
+            .create_lotteries(
+                &mut app,
+                owner(),
+                vec![
+                    new_lottery(
+                        "GROUP",
+                        AllowlistMsg::Group {
+                            group: group.to_string(),
+                        },
+                    ),
+                    new_lottery(
+                        "INLINE",
+                        AllowlistMsg::Addresses {
+                            addresses: vec![bob().to_string()],
+                        },
+                    ),
+                ],
+                &[],
+            )
+            .unwrap();
+
+        let lotteries = contract.lotteries(&app).unwrap().lotteries;
+        let lottery_of = |name: &str| -> LotteryContract {
+            lotteries
+                .iter()
+                .find(|lottery| lottery.name == name)
+                .unwrap()
+                .contract_addr
+                .clone()
+                .into()
+        };
+        let (by_group, inline) = (lottery_of("GROUP"), lottery_of("INLINE"));
+
+        let err = by_group
+            .buy_ticket(&mut app, bob(), ARCH_DEMON, None, &coins(100, ARCH_DEMON))
+            .unwrap_err();
+        assert_eq!(
+            LotteryContractError::NotAllowlisted { player: bob() },
+            err.downcast().unwrap()
+        );
+        by_group
+            .buy_ticket(&mut app, alice(), ARCH_DEMON, None, &coins(100, ARCH_DEMON))
+            .unwrap();
+
+        // the group manages its own members
+        let err = contract
+            .update_allowlist(
+                &mut app,
+                owner(),
+                by_group.addr().as_str(),
+                vec![bob().to_string()],
+                vec![],
+            )
+            .unwrap_err();
+        assert_eq!(
+            LotteryContractError::AllowlistNotInline {},
+            err.downcast().unwrap()
+        );
+
+        // only the creator or an admin edits an inline list
+        let err = contract
+            .update_allowlist(
+                &mut app,
+                bob(),
+                inline.addr().as_str(),
+                vec![alice().to_string()],
+                vec![],
+            )
+            .unwrap_err();
+        assert_eq!(ContractError::Unauthorized {}, err.downcast().unwrap());
+
+        contract
+            .update_allowlist(
+                &mut app,
+                owner(),
+                inline.addr().as_str(),
+                vec![alice().to_string()],
+                vec![],
+            )
+            .unwrap();
+        assert_eq!(inline.allowlist(&app).unwrap().members.len(), 2);
+
+        inline
+            .buy_ticket(&mut app, alice(), ARCH_DEMON, None, &coins(100, ARCH_DEMON))
+            .unwrap();
+    }
+
+    #[test]
+    fn progressive_jackpot_should_works() {
+        let mut app = mock_app(|router, _api, storage| {
+            for player in [alice(), bob()] {
+                router
+                    .bank
+                    .init_balance(storage, &player, coins(300, ARCH_DEMON))
+                    .unwrap();
+            }
+        });
+        let code_id = PlatformCodeId::store_code(&mut app);
+        let lottery_code_id = LotteryCodeId::store_code(&mut app);
+        let contract = code_id
+            .instantiate(
+                &mut app,
+                owner(),
+                "PLATFORM",
+                lottery_code_id.into(),
+                "label",
+            )
+            .unwrap();
+
+        let err = contract
+            .update_jackpot_config(
+                &mut app,
+                owner(),
+                Some(ProgressiveJackpot {
+                    contribution_bps: 1_000,
+                    odds: 0,
+                }),
+            )
+            .unwrap_err();
+        assert_eq!(
+            ContractError::LotteryContractErr(LotteryContractError::InvalidProgressiveJackpot {}),
+            err.downcast().unwrap()
+        );
+
+        // 10% of every pot, practically never won but by a yearly lottery
+        let config = ProgressiveJackpot {
+            contribution_bps: 1_000,
+            odds: u32::MAX,
+        };
+        let err = contract
+            .update_jackpot_config(&mut app, alice(), Some(config.clone()))
+            .unwrap_err();
+        assert_eq!(ContractError::Unauthorized {}, err.downcast().unwrap());
+        contract
+            .update_jackpot_config(&mut app, owner(), Some(config.clone()))
+            .unwrap();
+        assert_eq!(contract.jackpot(&app).unwrap().config, Some(config));
+
+        let expiration = Utc::now().timestamp() as u64;
+        let new_lottery = |name: &str, period: &str| CreateLotteryMsg {
+            name: name.to_string(),
+            symbol: name.to_string(),
+            unit_price_amount: 100,
+            unit_price_denom: ARCH_DEMON.to_string(),
+            period: period.to_string(),
+            expiration,
+            selection: WinnerSelection::Jackpot {},
+            max_players: 2,
+            label: name.to_string(),
+            allowlist: None,
+            nft_gate: None,
+            referral_bps: None,
+            pricing: None,
+        };
+        contract
+            .create_lotteries(
+                &mut app,
+                owner(),
+                vec![new_lottery("HOURLY", "hour"), new_lottery("YEARLY", "year")],
+                &[],
+            )
+            .unwrap();
+        let lotteries = contract.lotteries(&app).unwrap().lotteries;
+        let hourly = lotteries
+            .iter()
+            .find(|lottery| lottery.symbol == "HOURLY")
+            .unwrap()
+            .contract_addr
+            .clone();
+        let yearly = lotteries
+            .iter()
+            .find(|lottery| lottery.symbol == "YEARLY")
+            .unwrap()
+            .contract_addr
+            .clone();
+
+        let hourly_contract: LotteryContract = hourly.clone().into();
+        for player in [alice(), bob()] {
+            hourly_contract
+                .buy_ticket(&mut app, player, ARCH_DEMON, None, &coins(100, ARCH_DEMON))
+                .unwrap();
+        }
+        contract
+            .draw_lottery(&mut app, owner(), hourly.as_str())
+            .unwrap();
+
+        // the jackpot takes its share of the pot
+        let winner = &hourly_contract.winner(&app).unwrap().winner[0];
+        assert_eq!(winner.prize, coins(180, ARCH_DEMON));
+        assert_eq!(
+            contract.jackpot(&app).unwrap().balance,
+            coins(20, ARCH_DEMON)
+        );
+        let history = contract.jackpot_history(&app).unwrap().records;
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].lottery, hourly);
+        assert_eq!(
+            history[0].event,
+            JackpotEvent::Contributed {
+                amount: coin(20, ARCH_DEMON)
+            }
+        );
+
+        // the draw of a yearly lottery always wins the jackpot
+        let yearly_contract: LotteryContract = yearly.clone().into();
+        yearly_contract
+            .buy_ticket(&mut app, alice(), ARCH_DEMON, None, &coins(100, ARCH_DEMON))
+            .unwrap();
+        let before = app.wrap().query_balance(alice(), ARCH_DEMON).unwrap();
+        contract
+            .draw_lottery(&mut app, owner(), yearly.as_str())
+            .unwrap();
+
+        let after = app.wrap().query_balance(alice(), ARCH_DEMON).unwrap();
+        assert_eq!((after.amount - before.amount).u128(), 30);
+        assert!(contract.jackpot(&app).unwrap().balance.is_empty());
+
+        let history = contract.jackpot_history(&app).unwrap().records;
+        assert_eq!(history.len(), 3);
+        assert_eq!(
+            history[2].event,
+            JackpotEvent::Won {
+                winner: alice(),
+                ticket_id: "1".to_string(),
+                amount: coin(30, ARCH_DEMON),
+            }
+        );
+    }
+
+    #[test]
+    fn pause_should_works() {
+        let mut app = mock_app(|router, _api, storage| {
+            router
+                .bank
+                .init_balance(storage, &alice(), coins(300, ARCH_DEMON))
+                .unwrap();
+        });
+        let code_id = PlatformCodeId::store_code(&mut app);
+        let lottery_code_id = LotteryCodeId::store_code(&mut app);
+        let contract = code_id
+            .instantiate(
+                &mut app,
+                owner(),
+                "PLATFORM",
+                lottery_code_id.into(),
+                "label",
+            )
+            .unwrap();
+
+        let expiration = Utc::now().timestamp() as u64;
+        let new_lottery = |name: &str| CreateLotteryMsg {
+            name: name.to_string(),
+            symbol: name.to_string(),
+            unit_price_amount: 100,
+            unit_price_denom: ARCH_DEMON.to_string(),
+            period: "hour".to_string(),
+            expiration,
+            selection: WinnerSelection::Jackpot {},
+            max_players: 2,
+            label: name.to_string(),
+            allowlist: None,
+            nft_gate: None,
+            referral_bps: None,
+            pricing: None,
+        };
+        contract
+            .create_lotteries(
+                &mut app,
+                owner(),
+                vec![new_lottery("FIRST"), new_lottery("SECOND")],
+                &[],
+            )
+            .unwrap();
+        let lotteries: Vec<LotteryContract> = contract
+            .lotteries(&app)
+            .unwrap()
+            .lotteries
+            .into_iter()
+            .map(|lottery| lottery.contract_addr.into())
+            .collect();
+
+        // guardians pause, like admins
+        let err = contract.pause(&mut app, bob(), "bug", None).unwrap_err();
+        assert_eq!(ContractError::Unauthorized {}, err.downcast().unwrap());
+        contract
+            .grant_role(&mut app, owner(), Role::Guardian, bob().as_str())
+            .unwrap();
+        contract.pause(&mut app, bob(), "bug", None).unwrap();
+        assert_eq!(
+            contract.pause_info(&app).unwrap().pause.unwrap().reason,
+            "bug"
+        );
+
+        let paused = ContractError::Paused {
+            reason: "bug".to_string(),
+        };
+        let err = contract
+            .create_lotteries(&mut app, owner(), vec![new_lottery("THIRD")], &[])
+            .unwrap_err();
+        assert_eq!(paused, err.downcast().unwrap());
+        let err = contract
+            .draw_lottery(&mut app, owner(), lotteries[0].addr().as_str())
+            .unwrap_err();
+        assert_eq!(paused, err.downcast().unwrap());
+
+        // the lotteries only stop selling tickets once paused themselves, one batch at a time
+        lotteries[0]
+            .buy_ticket(&mut app, alice(), ARCH_DEMON, None, &coins(100, ARCH_DEMON))
+            .unwrap();
+        let last = contract
+            .pause_lotteries(&mut app, bob(), "bug", None, None, Some(1))
+            .unwrap();
+        assert!(last.is_some());
+        let paused_count = |app: &App| {
+            lotteries
+                .iter()
+                .filter(|lottery| lottery.pause_info(app).unwrap().pause.is_some())
+                .count()
+        };
+        assert_eq!(paused_count(&app), 1);
+        let last = contract
+            .pause_lotteries(
+                &mut app,
+                bob(),
+                "bug",
+                None,
+                last.map(String::from),
+                Some(1),
+            )
+            .unwrap();
+        assert_eq!(paused_count(&app), 2);
+        let last = contract
+            .pause_lotteries(
+                &mut app,
+                bob(),
+                "bug",
+                None,
+                last.map(String::from),
+                Some(1),
+            )
+            .unwrap();
+        assert_eq!(last, None);
+        for lottery in &lotteries {
+            let pause = lottery.pause_info(&app).unwrap().pause.unwrap();
+            assert_eq!(pause.paused_by, contract.addr());
+            let err = lottery
+                .buy_ticket(&mut app, alice(), ARCH_DEMON, None, &coins(100, ARCH_DEMON))
+                .unwrap_err();
+            assert_eq!(
+                LotteryContractError::Paused {
+                    reason: "bug".to_string()
+                },
+                err.downcast().unwrap()
+            );
+        }
+
+        contract.unpause(&mut app, bob()).unwrap();
+        contract
+            .unpause_lotteries(&mut app, bob(), None, None)
+            .unwrap();
+        assert_eq!(paused_count(&app), 0);
+        assert!(contract.pause_info(&app).unwrap().pause.is_none());
+        lotteries[1]
+            .buy_ticket(&mut app, alice(), ARCH_DEMON, None, &coins(100, ARCH_DEMON))
+            .unwrap();
+        contract
+            .draw_lottery(&mut app, owner(), lotteries[0].addr().as_str())
+            .unwrap();
+    }
+
+    #[test]
+    fn ownership_should_works() {
+        let mut app = mock_app(|_, _, _| {});
+        let code_id = PlatformCodeId::store_code(&mut app);
+        let lottery_code_id = LotteryCodeId::store_code(&mut app);
+        let contract = code_id
+            .instantiate(
+                &mut app,
+                owner(),
+                "PLATFORM",
+                lottery_code_id.into(),
+                "label",
+            )
+            .unwrap();
+        let height = app.block_info().height;
+
+        let err = contract
+            .propose_owner(&mut app, alice(), alice().as_str(), None)
+            .unwrap_err();
+        assert_eq!(ContractError::Unauthorized {}, err.downcast().unwrap());
+
+        contract
+            .propose_owner(
+                &mut app,
+                owner(),
+                alice().as_str(),
+                Some(Expiration::AtHeight(height + 5)),
+            )
+            .unwrap();
+        let err = contract.accept_ownership(&mut app, bob()).unwrap_err();
+        assert_eq!(
+            ContractError::LotteryContractErr(LotteryContractError::Unauthorized {}),
+            err.downcast().unwrap()
+        );
+        app.update_block(|block| block.height += 5);
+        let err = contract.accept_ownership(&mut app, alice()).unwrap_err();
+        assert_eq!(
+            ContractError::LotteryContractErr(LotteryContractError::OwnershipTransferExpired {}),
+            err.downcast().unwrap()
+        );
+
+        contract
+            .cancel_ownership_transfer(&mut app, owner())
+            .unwrap();
+        let err = contract
+            .cancel_ownership_transfer(&mut app, owner())
+            .unwrap_err();
+        assert_eq!(
+            ContractError::LotteryContractErr(LotteryContractError::NoPendingOwner {}),
+            err.downcast().unwrap()
+        );
+
+        // the new owner is an admin, the previous one is not anymore
+        contract
+            .propose_owner(&mut app, owner(), alice().as_str(), None)
+            .unwrap();
+        contract.accept_ownership(&mut app, alice()).unwrap();
+        let resp = contract.ownership(&app).unwrap();
+        assert_eq!(resp.owner, Some(alice()));
+        assert_eq!(resp.pending_owner, None);
+        let err = contract.pause(&mut app, owner(), "bug", None).unwrap_err();
+        assert_eq!(ContractError::Unauthorized {}, err.downcast().unwrap());
+
+        // granted admins keep their role once the ownership is renounced
+        contract
+            .grant_role(&mut app, alice(), Role::Admin, bob().as_str())
+            .unwrap();
+        contract.renounce_ownership(&mut app, alice()).unwrap();
+        assert_eq!(contract.ownership(&app).unwrap().owner, None);
+        let err = contract.pause(&mut app, alice(), "bug", None).unwrap_err();
+        assert_eq!(ContractError::Unauthorized {}, err.downcast().unwrap());
+        contract.pause(&mut app, bob(), "bug", None).unwrap();
+    }
+
+    #[test]
+    fn ticket_resale_royalty_should_works() {
+        let mut app = mock_app(|router, _api, storage| {
+            for player in [alice(), bob()] {
+                router
+                    .bank
+                    .init_balance(storage, &player, coins(300, ARCH_DEMON))
+                    .unwrap();
+            }
+        });
+        let code_id = PlatformCodeId::store_code(&mut app);
+        let lottery_code_id = LotteryCodeId::store_code(&mut app);
+        let contract = code_id
+            .instantiate(
+                &mut app,
+                owner(),
+                "PLATFORM",
+                lottery_code_id.into(),
+                "label",
+            )
+            .unwrap();
+
+        let err = contract
+            .update_resale_royalty(&mut app, alice(), Some(1_000))
+            .unwrap_err();
+        assert_eq!(ContractError::Unauthorized {}, err.downcast().unwrap());
+        let err = contract
+            .update_resale_royalty(&mut app, owner(), Some(10_001))
+            .unwrap_err();
+        assert_eq!(
+            ContractError::LotteryContractErr(LotteryContractError::InvalidResaleRoyalty {
+                bps: 10_001
+            }),
+            err.downcast().unwrap()
+        );
+
+        // 10% of every resale
+        contract
+            .update_resale_royalty(&mut app, owner(), Some(1_000))
+            .unwrap();
+        assert_eq!(contract.resale_royalty(&app).unwrap().bps, Some(1_000));
+
+        let expiration = Utc::now().timestamp() as u64;
+        let lottery_addr = contract
+            .create_lottery(
+                &mut app,
+                owner(),
+                "LOTTERY",
+                "LOTTER",
+                100,
+                ARCH_DEMON,
+                "hour",
+                expiration,
+                WinnerSelection::Jackpot {},
+                2,
+                "label",
+            )
+            .unwrap()
+            .unwrap()
+            .addr;
+        let lottery: LotteryContract = lottery_addr.into();
+
+        lottery
+            .buy_ticket(&mut app, alice(), ARCH_DEMON, None, &coins(100, ARCH_DEMON))
+            .unwrap();
+        lottery
+            .list_ticket(&mut app, alice(), "1", coin(150, ARCH_DEMON))
+            .unwrap();
+        lottery
+            .buy_listed_ticket(&mut app, bob(), "1", &coins(150, ARCH_DEMON))
+            .unwrap();
+
+        let balance = app.wrap().query_balance(alice(), ARCH_DEMON).unwrap();
+        assert_eq!(balance.amount.u128(), 335);
+        let balance = app
+            .wrap()
+            .query_balance(contract.addr(), ARCH_DEMON)
+            .unwrap();
+        assert_eq!(balance.amount.u128(), 15);
+    }
+
+    #[test]
+    fn burned_jackpot_ticket_should_works() {
+        let mut app = mock_app(|router, _api, storage| {
+            router
+                .bank
+                .init_balance(storage, &alice(), coins(100, ARCH_DEMON))
+                .unwrap();
+        });
+        let code_id = PlatformCodeId::store_code(&mut app);
+        let lottery_code_id = LotteryCodeId::store_code(&mut app);
+        let contract = code_id
+            .instantiate(
+                &mut app,
+                owner(),
+                "PLATFORM",
+                lottery_code_id.into(),
+                "label",
+            )
+            .unwrap();
+        let config = ProgressiveJackpot {
+            contribution_bps: 1_000,
+            odds: u32::MAX,
+        };
+        contract
+            .update_jackpot_config(&mut app, owner(), Some(config))
+            .unwrap();
+
+        let lottery: LotteryContract = contract
+            .create_lottery(
+                &mut app,
+                owner(),
+                "YEARLY",
+                "YEARLY",
+                100,
+                ARCH_DEMON,
+                "year",
+                Utc::now().timestamp() as u64,
+                WinnerSelection::Jackpot {},
+                2,
+                "YEARLY",
+            )
+            .unwrap()
+            .unwrap()
+            .addr
+            .into();
+        lottery
+            .buy_ticket(&mut app, alice(), ARCH_DEMON, None, &coins(100, ARCH_DEMON))
+            .unwrap();
+        lottery
+            .burn_ticket(&mut app, alice(), "1".to_string())
+            .unwrap();
+
+        // the jackpot of a burned ticket goes to its buyer
+        contract
+            .draw_lottery(&mut app, owner(), lottery.addr().as_str())
+            .unwrap();
+
+        let balance = app.wrap().query_balance(alice(), ARCH_DEMON).unwrap();
+        assert_eq!(balance, coin(10, ARCH_DEMON));
+        let history = contract.jackpot_history(&app).unwrap().records;
+        assert_eq!(
+            history[1].event,
+            JackpotEvent::Won {
+                winner: alice(),
+                ticket_id: "1".to_string(),
+                amount: coin(10, ARCH_DEMON),
+            }
+        );
+    }
 }
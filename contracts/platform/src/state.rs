@@ -1,8 +1,12 @@
+use std::fmt;
+
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{Addr, Coin, Timestamp};
-use cw_storage_plus::{Item, Map};
+use cosmwasm_std::{Addr, Coin, Coins, Decimal, Empty, StdResult, Storage, Timestamp};
+use cw_storage_plus::{Index, IndexList, IndexedMap, Item, Map, MultiIndex};
 
-use lottery::state::{LotteryPeriod, WinnerSelection};
+use lottery::state::{
+    LotteryPeriod, PauseInfo, PendingOwner, ProgressiveJackpot, WinnerInfo, WinnerSelection,
+};
 
 #[cw_serde]
 pub struct State {
@@ -46,13 +50,327 @@ pub struct LotteryInfo {
     pub selection: WinnerSelection,
     pub max_players: u64,
     pub contract_addr: Addr,
+    pub code_id: u64,
+    pub status: LotteryStatus,
+    pub player_count: u64,
+    pub pot: Coin,
+    pub winner: Vec<WinnerInfo>,
+    pub claimed: bool,
+    pub creator: Addr,
+    /// Creation deposit held by the platform, until it's refunded or slashed
+    pub deposit: Option<Coin>,
+    /// Share of the pot paid to the creator at draw
+    pub commission: Decimal,
+    pub commission_paid: Option<Coin>,
+}
+
+impl LotteryInfo {
+    pub fn is_outdated(&self, state: &State) -> bool {
+        self.code_id != state.lottery_code_id
+    }
+}
+
+#[cw_serde]
+pub enum LotteryStatus {
+    Active,
+    Drawn,
+    Cancelled,
+}
+
+impl fmt::Display for LotteryStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let status = match self {
+            Self::Active => "active",
+            Self::Drawn => "drawn",
+            Self::Cancelled => "cancelled",
+        };
+
+        f.write_str(status)
+    }
+}
+
+/// Operators of the platform, the owner is always an admin
+#[cw_serde]
+#[derive(Copy)]
+pub enum Role {
+    /// Grants and revokes roles, cancels lotteries and manages the lottery code
+    Admin,
+    /// Creates lotteries
+    Creator,
+    /// Draws lotteries
+    Drawer,
+    /// Pauses and unpauses the platform and its lotteries, as admins can
+    Guardian,
+}
+
+impl Role {
+    pub const ALL: [Role; 4] = [Role::Admin, Role::Creator, Role::Drawer, Role::Guardian];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Admin => "admin",
+            Self::Creator => "creator",
+            Self::Drawer => "drawer",
+            Self::Guardian => "guardian",
+        }
+    }
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+pub struct LotteryIndexes<'a> {
+    pub period: MultiIndex<'a, String, LotteryInfo, &'a Addr>,
+    pub status: MultiIndex<'a, String, LotteryInfo, &'a Addr>,
+    pub denom: MultiIndex<'a, String, LotteryInfo, &'a Addr>,
+    pub created_at: MultiIndex<'a, u64, LotteryInfo, &'a Addr>,
+    pub creator: MultiIndex<'a, String, LotteryInfo, &'a Addr>,
+}
+
+impl<'a> IndexList<LotteryInfo> for LotteryIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<LotteryInfo>> + '_> {
+        let v: Vec<&dyn Index<LotteryInfo>> = vec![
+            &self.period,
+            &self.status,
+            &self.denom,
+            &self.created_at,
+            &self.creator,
+        ];
+        Box::new(v.into_iter())
+    }
+}
+
+/// Terms of hosting a lottery without the creator role
+#[cw_serde]
+#[derive(Default)]
+pub struct CreationConfig {
+    /// Paid per lottery, creation is restricted to the creator role without it
+    pub deposit: Option<Coin>,
+    pub commission: Decimal,
+}
+
+#[cw_serde]
+pub struct LotteryCode {
+    pub code_id: u64,
+    pub height: u64,
+    pub activated_at: Timestamp,
+}
+
+/// A ticket of a player in one of the platform's lotteries
+#[cw_serde]
+pub struct PlayerEntry {
+    pub lottery: Addr,
+    pub ticket_id: String,
+    pub paid: Coin,
+    pub height: u64,
+    pub bought_at: Timestamp,
+}
+
+#[cw_serde]
+pub struct PlayerWin {
+    pub lottery: Addr,
+    pub ticket_id: String,
+    pub prize: Vec<Coin>,
+    pub claimed: bool,
+}
+
+#[cw_serde]
+#[derive(Default)]
+pub struct PlayerTotals {
+    pub entries: u64,
+    pub wins: u64,
+    pub spent: Vec<Coin>,
+    pub won: Vec<Coin>,
+    pub unclaimed: Vec<Coin>,
+}
+
+impl PlayerTotals {
+    pub fn add_entry(&mut self, paid: Coin) -> StdResult<()> {
+        self.entries += 1;
+        add_coins(&mut self.spent, vec![paid])
+    }
+
+    pub fn add_win(&mut self, prize: &[Coin]) -> StdResult<()> {
+        self.wins += 1;
+        add_coins(&mut self.won, prize.to_vec())?;
+        add_coins(&mut self.unclaimed, prize.to_vec())
+    }
+
+    pub fn claim(&mut self, prize: &[Coin]) -> StdResult<()> {
+        let mut unclaimed = Coins::try_from(self.unclaimed.clone())?;
+        for coin in prize {
+            unclaimed.sub(coin.clone())?;
+        }
+        self.unclaimed = unclaimed.into_vec();
+        Ok(())
+    }
+}
+
+#[cw_serde]
+#[derive(Default)]
+pub struct CreatorTotals {
+    pub lotteries: u64,
+    pub earned: Vec<Coin>,
+    /// Deposits of the creator's lotteries which are not released yet
+    pub deposits: Vec<Coin>,
+    pub slashed: Vec<Coin>,
+}
+
+impl CreatorTotals {
+    pub fn add_lottery(&mut self, deposit: Option<Coin>) -> StdResult<()> {
+        self.lotteries += 1;
+        add_coins(&mut self.deposits, deposit.into_iter().collect())
+    }
+
+    pub fn add_earning(&mut self, commission: Coin) -> StdResult<()> {
+        add_coins(&mut self.earned, vec![commission])
+    }
+
+    pub fn refund(&mut self, deposit: Coin) -> StdResult<()> {
+        sub_coins(&mut self.deposits, deposit)
+    }
+
+    pub fn slash(&mut self, deposit: Coin) -> StdResult<()> {
+        sub_coins(&mut self.deposits, deposit.clone())?;
+        add_coins(&mut self.slashed, vec![deposit])
+    }
+}
+
+/// Referrals across the platform's lotteries, `earned` is the rewards paid out
+#[cw_serde]
+#[derive(Default)]
+pub struct ReferrerTotals {
+    pub referrals: u64,
+    pub earned: Vec<Coin>,
+}
+
+impl ReferrerTotals {
+    pub fn add_earning(&mut self, reward: Coin) -> StdResult<()> {
+        add_coins(&mut self.earned, vec![reward])
+    }
+}
+
+/// A contribution to, or a payout of, the progressive jackpot
+#[cw_serde]
+pub struct JackpotRecord {
+    pub id: u64,
+    pub lottery: Addr,
+    pub height: u64,
+    pub time: Timestamp,
+    pub event: JackpotEvent,
+}
+
+#[cw_serde]
+pub enum JackpotEvent {
+    Contributed {
+        amount: Coin,
+    },
+    Won {
+        winner: Addr,
+        ticket_id: String,
+        amount: Coin,
+    },
+}
+
+pub fn take_coin(total: &mut Vec<Coin>, denom: &str) -> Option<Coin> {
+    let idx = total.iter().position(|coin| coin.denom == denom)?;
+    Some(total.remove(idx))
+}
+
+pub fn add_coins(total: &mut Vec<Coin>, coins: Vec<Coin>) -> StdResult<()> {
+    let mut sum = Coins::try_from(total.clone())?;
+    for coin in coins {
+        sum.add(coin)?;
+    }
+    *total = sum.into_vec();
+    Ok(())
+}
+
+fn sub_coins(total: &mut Vec<Coin>, coin: Coin) -> StdResult<()> {
+    let mut sum = Coins::try_from(total.clone())?;
+    sum.sub(coin)?;
+    *total = sum.into_vec();
+    Ok(())
+}
+
+pub fn save_lottery_code(storage: &mut dyn Storage, code: &LotteryCode) -> StdResult<()> {
+    let id = NEXT_LOTTERY_CODE_ID.may_load(storage)?.unwrap_or(1);
+    NEXT_LOTTERY_CODE_ID.save(storage, &(id + 1))?;
+
+    LOTTERY_CODES.save(storage, id, code)
 }
 
 /// Storage
 pub const OWNER: Item<Addr> = Item::new("owner");
+pub const PENDING_OWNER: Item<PendingOwner> = Item::new("pending_owner");
 pub const STATE: Item<State> = Item::new("state");
-pub const LOTTERIES: Map<&Addr, LotteryInfo> = Map::new("lotteries"); // (lottery address, lottery info)
-                                                                      // pub const PLAYERS: Map<&Addr, PlayerInfo> = Map::new("players");    // (player address, playing info)
+pub const LOTTERIES: IndexedMap<&Addr, LotteryInfo, LotteryIndexes> = IndexedMap::new(
+    "lotteries",
+    LotteryIndexes {
+        period: MultiIndex::new(
+            |_pk, lottery| lottery.period.to_string(),
+            "lotteries",
+            "lotteries__period",
+        ),
+        status: MultiIndex::new(
+            |_pk, lottery| lottery.status.to_string(),
+            "lotteries",
+            "lotteries__status",
+        ),
+        denom: MultiIndex::new(
+            |_pk, lottery| lottery.unit_price.denom.clone(),
+            "lotteries",
+            "lotteries__denom",
+        ),
+        created_at: MultiIndex::new(
+            |_pk, lottery| lottery.created_at.seconds(),
+            "lotteries",
+            "lotteries__created_at",
+        ),
+        creator: MultiIndex::new(
+            |_pk, lottery| lottery.creator.to_string(),
+            "lotteries",
+            "lotteries__creator",
+        ),
+    },
+); // (lottery address, lottery info)
+
+pub const PLAYER_ENTRIES: Map<(&Addr, &Addr), PlayerEntry> = Map::new("player_entries"); // ((player, lottery), entry)
+pub const PLAYER_WINS: Map<(&Addr, &Addr), PlayerWin> = Map::new("player_wins"); // ((player, lottery), win)
+pub const PLAYER_TOTALS: Map<&Addr, PlayerTotals> = Map::new("player_totals");
+
+pub const CREATION_CONFIG: Item<CreationConfig> = Item::new("creation_config");
+pub const CREATOR_TOTALS: Map<&Addr, CreatorTotals> = Map::new("creator_totals");
+pub const REFERRER_TOTALS: Map<&Addr, ReferrerTotals> = Map::new("referrer_totals");
+/// Deposits slashed from the creators and not withdrawn yet, by denom
+pub const SLASHED_DEPOSITS: Item<Vec<Coin>> = Item::new("slashed_deposits");
+
+/// Every activation of a lottery code, a code activated again gets a new entry
+pub const LOTTERY_CODES: Map<u64, LotteryCode> = Map::new("lottery_codes"); // (activation id, activation info)
+pub const NEXT_LOTTERY_CODE_ID: Item<u64> = Item::new("next_lottery_code_id");
+
+pub const LOTTERY_ROUNDS: Map<(&Addr, &str), u64> = Map::new("lottery_rounds"); // ((creator, series), last round)
+/// Prize tiers left without winners, funding the next round of the series
+pub const ROLLOVERS: Map<(&Addr, &str), Vec<Coin>> = Map::new("rollovers"); // ((creator, series), rollover)
+
+/// Lottery creation and draws are stopped while the pause is active
+pub const PAUSE: Item<PauseInfo> = Item::new("pause");
+
+/// Contribution of the lotteries created from now on, no jackpot without it
+pub const JACKPOT_CONFIG: Item<ProgressiveJackpot> = Item::new("jackpot_config");
+pub const RESALE_ROYALTY_BPS: Item<u16> = Item::new("resale_royalty_bps");
+/// Progressive jackpot held by the platform, by denom
+pub const JACKPOT: Item<Vec<Coin>> = Item::new("jackpot");
+pub const JACKPOT_HISTORY: Map<u64, JackpotRecord> = Map::new("jackpot_history"); // (record id, record)
+pub const NEXT_JACKPOT_RECORD_ID: Item<u64> = Item::new("next_jackpot_record_id");
+
+pub const ROLE_MEMBERS: Map<(&str, &Addr), Empty> = Map::new("role_members"); // ((role, member), _)
+/// Members of a cw4 group hold the role as well
+pub const ROLE_GROUPS: Map<&str, Addr> = Map::new("role_groups"); // (role, cw4 group)
 
-/// Cache lottery info
-pub const PENDING_LOTTERY: Item<LotteryInfo> = Item::new("pending_lottery");
+/// Predicted lottery addresses, checked against the instantiate reply
+pub const PENDING_LOTTERIES: Map<u64, Addr> = Map::new("pending_lotteries"); // (reply id, lottery address)
+pub const NEXT_REPLY_ID: Item<u64> = Item::new("next_reply_id");
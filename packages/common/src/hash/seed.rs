@@ -2,6 +2,10 @@ use base64ct::{Base64, Encoding};
 use cosmwasm_std::Addr;
 use sha2::{Digest, Sha256};
 
+use crate::error::CommonError;
+
+use super::pcg64_from_seed;
+
 pub fn init(id: &str, block_height: u64) -> String {
     let mut sha256 = Sha256::new();
     sha256.update(id.as_bytes());
@@ -48,3 +52,41 @@ pub fn finalize(
 
     Base64::encode_string(&hash)
 }
+
+/// Digest of a buy memo, as recorded in the draw proof
+pub fn memo_digest(memo: &Option<String>) -> Option<String> {
+    memo.as_ref()
+        .map(|memo| Base64::encode_string(&Sha256::digest(memo.as_bytes())))
+}
+
+/// Random numbers derived from the final seed, one per winner
+pub fn random_numbers(seed: &str, count: usize) -> Result<Vec<u64>, CommonError> {
+    let mut rng = pcg64_from_seed(seed)?;
+
+    Ok((0..count).map(|_| rng.next_u64()).collect())
+}
+
+/// Ticket indices, starting from 1, chosen by the random numbers
+pub fn winner_indices(random_numbers: &[u64], ticket_count: u64) -> Vec<u64> {
+    random_numbers
+        .iter()
+        .map(|num| num % ticket_count + 1)
+        .collect()
+}
+
+/// Distinct numbers from 1 to `max_number`, one per random number, in ascending order.
+/// There must not be more random numbers than `max_number`.
+pub fn pick_numbers(random_numbers: &[u64], max_number: u8) -> Vec<u8> {
+    let mut pool: Vec<u8> = (1..=max_number).collect();
+
+    // Partial Fisher-Yates: the i-th random number picks among the numbers not drawn yet
+    for (i, num) in random_numbers.iter().enumerate() {
+        let remaining = (pool.len() - i) as u64;
+        let j = i + (num % remaining) as usize;
+        pool.swap(i, j);
+    }
+
+    let mut numbers = pool[..random_numbers.len()].to_vec();
+    numbers.sort_unstable();
+    numbers
+}